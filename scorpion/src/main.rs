@@ -1,87 +1,210 @@
-use std::fs::File;
-use std::io::BufReader;
-use exif::Reader;
-use chrono::offset::Utc;
-use chrono::DateTime;
-use std::time::SystemTime;
-use std::io::Error;
+mod output;
+mod report;
+mod strip;
+mod tests_output;
+mod tests_strip;
+
+use output::Format;
+use serde_json::json;
+use std::path::{Path, PathBuf};
+use strip::Keep;
+
+/// Settings given on the command line
+struct Options {
+    format: Format,
+    /// Remove the metadata instead of printing it
+    strip: bool,
+    keep: Keep,
+    /// Where the stripped images go: a file, or a directory for several images
+    output: Option<String>,
+    files: Vec<String>,
+}
 
 fn main() {
     let args: Vec<String> = std::env::args().collect();
     let args: Vec<String> = args[1..].to_vec();
 
-    if args.is_empty() {
+    let options = match parse_args(&args) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("🚫 {}", err);
+            std::process::exit(2);
+        }
+    };
+    if options.files.is_empty() {
         println!("🦂 Scorpion 🦂");
         println!("A simple command line tool to view metadata and EXIF data of files");
-        println!("Usage: scorpion <file1> <file2> ...");
+        println!("Usage: scorpion [--format json|csv|text] <file1> <file2> ...");
+        println!("       scorpion --strip [--keep TAG,...] [--output PATH] <file1> <file2> ...");
         return;
     }
-    
-    for arg in args {
-        if !file_exists(&arg) {
-            println!("🚫 File not found: {}", arg);
-            continue;
-        }
-        println!("🖼️  {}", arg);
-        print_metadata(&arg);
-        let r = print_exit_data(&arg);
-        if r.is_err() {
-            println!("🚫 No EXIF data found");
+    if options.strip {
+        if !strip_files(&options) {
+            std::process::exit(1);
         }
-        println!("\n{}\n", "─".repeat(50));
+        return;
     }
-}
 
-/// Fucntion to check if the file exists
-/// It takes a path to a file as an argument
-/// It returns a boolean
-fn file_exists(file: &str) -> bool {
-    std::fs::metadata(file).is_ok()
+    if options.format == Format::Csv {
+        println!("{}", output::CSV_HEADER);
+    }
+    for file in &options.files {
+        let report = match report::read(file) {
+            Ok(report) => report,
+            Err(err) => {
+                match options.format {
+                    Format::Text => println!("🚫 {}: {}", err, file),
+                    Format::Json => println!("{}", output::error_json(file, &err)),
+                    Format::Csv => eprintln!("🚫 {}: {}", err, file),
+                }
+                continue;
+            }
+        };
+        match options.format {
+            Format::Text => output::print_text(&report),
+            Format::Json => println!("{}", output::to_json(&report)),
+            Format::Csv => {
+                for row in output::csv_rows(&report) {
+                    println!("{}", row);
+                }
+            }
+        }
+    }
 }
 
-/// Function to print EXIF data from a file
-/// It takes a path to a file as an argument
-/// It returnes nothing
-/// It prints the EXIF data of the file
-fn print_exit_data(file: &str) -> Result<(), ()> {
-    let file = File::open(file).expect("Failed to open file");
-    let mut reader = BufReader::new(&file);
-
-    let exif_reader = Reader::new();
-    let exif_data = exif_reader.read_from_container(&mut reader);
-    let exif_data = match exif_data {
-        Ok(exif_data) => exif_data,
-        Err(_) => return Err(()),
+/// Function that parses the command line
+/// It takes the arguments without the program name
+/// It returns the options, the format is text by default
+fn parse_args(args: &[String]) -> Result<Options, String> {
+    let mut options = Options {
+        format: Format::Text,
+        strip: false,
+        keep: Keep::default(),
+        output: None,
+        files: Vec::new(),
     };
-
-    for field in exif_data.fields() {
-        println!("{}: {}", field.tag, field.display_value().with_unit(&exif_data));
+    let mut keep = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let (name, value) = match arg.split_once('=') {
+            Some((name, value)) if name.starts_with("--") => (name, Some(value.to_string())),
+            _ => (arg.as_str(), None),
+        };
+        let mut value = |expected: &str| {
+            value
+                .clone()
+                .or_else(|| args.next().cloned())
+                .ok_or(format!("{} needs a value: {}", name, expected))
+        };
+        match name {
+            "--format" => options.format = Format::parse(&value("json, csv or text")?)?,
+            "--strip" => options.strip = true,
+            "--keep" => keep = Some(value("a list of EXIF tags, xmp, iptc or icc")?),
+            "-o" | "--output" => options.output = Some(value("a file or a directory")?),
+            _ => options.files.push(arg.clone()),
+        }
+    }
+    if !options.strip && (keep.is_some() || options.output.is_some()) {
+        return Err("--keep and --output can only be used with --strip".to_string());
     }
-    Ok(())
+    if options.strip && options.format == Format::Csv {
+        return Err("--strip prints text or json".to_string());
+    }
+    if let Some(keep) = keep {
+        options.keep = Keep::parse(&keep)?;
+    }
+    Ok(options)
 }
 
-/// Function that prints all the metadata of a file
-/// It takes a path to a file as an argument
-/// It returns nothing
-fn print_metadata(file: &str) {
-    let metadata = std::fs::metadata(file).expect("Failed to read metadata");
-    println!("Size: {:.2} MB", metadata.len() as f64 / 1_000_000.0);
-    println!("Modified: {}", format_time(metadata.modified()));
-    println!("Created: {:?}", format_time(metadata.created()));
-    println!("Accessed: {:?}", format_time(metadata.accessed()));
-    println!();
+/// Function that strips the metadata of the files, in place or to `--output`
+/// It prints what was removed from each file
+/// It returns false if a file could not be stripped
+fn strip_files(options: &Options) -> bool {
+    let mut success = true;
+    let mut destinations: Vec<PathBuf> = Vec::new();
+    for file in &options.files {
+        let result = destination(file, options, &mut destinations).and_then(|destination| {
+            let data = std::fs::read(file).map_err(|err| err.to_string())?;
+            let stripped = strip::strip(&data, &options.keep)?;
+            strip::write_verified(&destination, &stripped.data, &options.keep)?;
+            Ok((destination, stripped))
+        });
+        let (destination, stripped) = match result {
+            Ok(result) => result,
+            Err(err) => {
+                match options.format {
+                    Format::Json => println!("{}", output::error_json(file, &err)),
+                    _ => eprintln!("🚫 {}: {}", file, err),
+                }
+                success = false;
+                continue;
+            }
+        };
+        let mut removed: Vec<String> = Vec::new();
+        for kind in &stripped.removed {
+            if !removed.contains(&kind.to_string()) {
+                removed.push(kind.to_string());
+            }
+        }
+        if options.format == Format::Json {
+            let object = json!({
+                "file": file,
+                "output": destination.display().to_string(),
+                "removed": removed,
+                "kept": stripped.kept_tags,
+                "verified": true,
+            });
+            println!("{}", object);
+            continue;
+        }
+        let removed = match removed.is_empty() {
+            true => "no metadata found".to_string(),
+            false => format!("removed {}", removed.join(", ")),
+        };
+        let kept = match stripped.kept_tags.is_empty() {
+            true => String::new(),
+            false => format!(", kept {}", stripped.kept_tags.join(", ")),
+        };
+        println!(
+            "🧹 {} -> {}: {}{}",
+            file,
+            destination.display(),
+            removed,
+            kept
+        );
+    }
+    success
 }
 
-/// It takes a SystemTime as an argument
-/// It returns a string
-/// It formats the time into a readable format
-/// It uses the chrono library
-fn format_time(time: Result<SystemTime, Error>) -> String {
-    let time = match time {
-        Ok(time) => time,
-        Err(_) => return "N/A".to_string(),
+/// Function that returns where the stripped image of a file is written
+/// It is the file itself without `--output`
+/// It fails when another file already got the same destination
+fn destination(
+    file: &str,
+    options: &Options,
+    destinations: &mut Vec<PathBuf>,
+) -> Result<PathBuf, String> {
+    let output = match &options.output {
+        Some(output) => Path::new(output),
+        None => return Ok(PathBuf::from(file)),
     };
-    let datetime: DateTime<Utc> = time.into();
-    let time_readable = format!("{}", datetime.format("%d/%m/%Y %T"));
-    time_readable
+    if output.is_dir() {
+        let file_name = Path::new(file).file_name().ok_or("invalid file name")?;
+        let destination = output.join(file_name);
+        if destinations.contains(&destination) {
+            return Err(format!(
+                "{} would overwrite the output of another file",
+                destination.display()
+            ));
+        }
+        destinations.push(destination.clone());
+        return Ok(destination);
+    }
+    if options.files.len() > 1 {
+        return Err(format!(
+            "--output must be a directory for several files: {}",
+            output.display()
+        ));
+    }
+    Ok(output.to_path_buf())
 }
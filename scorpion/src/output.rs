@@ -0,0 +1,203 @@
+use chrono::offset::Utc;
+use chrono::{DateTime, SecondsFormat};
+use exif::{Field, Value};
+use serde_json::{json, Map};
+use std::time::SystemTime;
+
+use crate::report::FileReport;
+
+/// How the reports are printed
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Format {
+    /// Lines for a human reader
+    Text,
+    /// One JSON object per line and per file
+    Json,
+    /// One row per EXIF field, after a header row
+    Csv,
+}
+
+impl Format {
+    /// Function that parses the value of `--format`
+    pub fn parse(format: &str) -> Result<Format, String> {
+        match format {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            _ => Err(format!(
+                "invalid format '{}', expected json, csv or text",
+                format
+            )),
+        }
+    }
+}
+
+/// Columns of the CSV output
+pub const CSV_HEADER: &str = "file,size,modified,created,accessed,ifd,tag,type,raw,display";
+
+/// Function that prints the report of a file for a human reader
+pub fn print_text(report: &FileReport) {
+    println!("🖼️  {}", report.file);
+    println!("Size: {:.2} MB", report.size as f64 / 1_000_000.0);
+    println!("Modified: {}", format_time(report.modified));
+    println!("Created: {:?}", format_time(report.created));
+    println!("Accessed: {:?}", format_time(report.accessed));
+    println!();
+    match &report.exif {
+        Some(exif) => {
+            for field in exif.fields() {
+                println!("{}: {}", field.tag, field.display_value().with_unit(exif));
+            }
+        }
+        None => println!("🚫 No EXIF data found"),
+    }
+    println!("\n{}\n", "─".repeat(50));
+}
+
+/// Function that builds the JSON object of a file
+/// The EXIF fields are grouped by IFD (`primary`, `thumbnail`) and keyed by tag name
+/// `exif` is null when the file has no EXIF data
+pub fn to_json(report: &FileReport) -> serde_json::Value {
+    let exif = report.exif.as_ref().map(|exif| {
+        let mut ifds = Map::new();
+        for field in exif.fields() {
+            let ifd = ifds
+                .entry(field.ifd_num.to_string())
+                .or_insert_with(|| json!({}));
+            ifd[field.tag.to_string()] = json!({
+                "type": value_type(&field.value),
+                "raw": raw_value(&field.value),
+                "display": field.display_value().with_unit(exif).to_string(),
+            });
+        }
+        ifds
+    });
+    json!({
+        "file": report.file,
+        "size": report.size,
+        "modified": timestamp(report.modified),
+        "created": timestamp(report.created),
+        "accessed": timestamp(report.accessed),
+        "exif": exif,
+    })
+}
+
+/// Function that builds the JSON object of a file that could not be read
+pub fn error_json(file: &str, error: &str) -> serde_json::Value {
+    json!({ "file": file, "error": error })
+}
+
+/// Function that builds the CSV rows of a file, one per EXIF field
+/// A file without EXIF data has one row with empty EXIF columns
+pub fn csv_rows(report: &FileReport) -> Vec<String> {
+    let file_columns = [
+        csv_field(&report.file),
+        report.size.to_string(),
+        timestamp(report.modified).unwrap_or_default(),
+        timestamp(report.created).unwrap_or_default(),
+        timestamp(report.accessed).unwrap_or_default(),
+    ]
+    .join(",");
+    let exif = match &report.exif {
+        Some(exif) => exif,
+        None => return vec![format!("{},,,,,", file_columns)],
+    };
+    exif.fields()
+        .map(|field| format!("{},{}", file_columns, exif_columns(field, exif)))
+        .collect()
+}
+
+fn exif_columns(field: &Field, exif: &exif::Exif) -> String {
+    [
+        field.ifd_num.to_string(),
+        field.tag.to_string(),
+        value_type(&field.value).to_string(),
+        csv_field(&raw_value(&field.value).to_string()),
+        csv_field(&field.display_value().with_unit(exif).to_string()),
+    ]
+    .join(",")
+}
+
+/// Function that quotes a CSV field if it holds a comma, a quote or a line break
+pub fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        return format!("\"{}\"", field.replace('"', "\"\""));
+    }
+    field.to_string()
+}
+
+/// Function that returns the TIFF type of an EXIF value
+pub fn value_type(value: &Value) -> &'static str {
+    match value {
+        Value::Byte(_) => "BYTE",
+        Value::Ascii(_) => "ASCII",
+        Value::Short(_) => "SHORT",
+        Value::Long(_) => "LONG",
+        Value::Rational(_) => "RATIONAL",
+        Value::SByte(_) => "SBYTE",
+        Value::Undefined(_, _) => "UNDEFINED",
+        Value::SShort(_) => "SSHORT",
+        Value::SLong(_) => "SLONG",
+        Value::SRational(_) => "SRATIONAL",
+        Value::Float(_) => "FLOAT",
+        Value::Double(_) => "DOUBLE",
+        Value::Unknown(_, _, _) => "UNKNOWN",
+    }
+}
+
+/// Function that converts an EXIF value to JSON, as stored in the file
+/// A value with one component is a scalar, otherwise an array
+/// Rationals are `[numerator, denominator]`, undefined bytes a hex string
+pub fn raw_value(value: &Value) -> serde_json::Value {
+    let values: Vec<serde_json::Value> = match value {
+        Value::Byte(bytes) => bytes.iter().map(|byte| json!(byte)).collect(),
+        Value::Ascii(strings) => strings
+            .iter()
+            .map(|string| json!(String::from_utf8_lossy(string)))
+            .collect(),
+        Value::Short(shorts) => shorts.iter().map(|short| json!(short)).collect(),
+        Value::Long(longs) => longs.iter().map(|long| json!(long)).collect(),
+        Value::Rational(rationals) => rationals
+            .iter()
+            .map(|rational| json!([rational.num, rational.denom]))
+            .collect(),
+        Value::SByte(bytes) => bytes.iter().map(|byte| json!(byte)).collect(),
+        Value::Undefined(bytes, _) => {
+            let hex: String = bytes.iter().map(|byte| format!("{:02x}", byte)).collect();
+            return json!(hex);
+        }
+        Value::SShort(shorts) => shorts.iter().map(|short| json!(short)).collect(),
+        Value::SLong(longs) => longs.iter().map(|long| json!(long)).collect(),
+        Value::SRational(rationals) => rationals
+            .iter()
+            .map(|rational| json!([rational.num, rational.denom]))
+            .collect(),
+        Value::Float(floats) => floats.iter().map(|float| json!(float)).collect(),
+        Value::Double(doubles) => doubles.iter().map(|double| json!(double)).collect(),
+        Value::Unknown(_, _, _) => return serde_json::Value::Null,
+    };
+    match values.len() {
+        1 => values.into_iter().next().unwrap(),
+        _ => serde_json::Value::Array(values),
+    }
+}
+
+/// Function that formats a time as RFC 3339 in UTC, for the JSON and CSV output
+fn timestamp(time: Option<SystemTime>) -> Option<String> {
+    let datetime: DateTime<Utc> = time?.into();
+    Some(datetime.to_rfc3339_opts(SecondsFormat::Secs, true))
+}
+
+/// It takes a SystemTime as an argument
+/// It returns a string
+/// It formats the time into a readable format
+/// It uses the chrono library
+fn format_time(time: Option<SystemTime>) -> String {
+    let time = match time {
+        Some(time) => time,
+        None => return "N/A".to_string(),
+    };
+    let datetime: DateTime<Utc> = time.into();
+    let time_readable = format!("{}", datetime.format("%d/%m/%Y %T"));
+    time_readable
+}
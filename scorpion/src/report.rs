@@ -0,0 +1,41 @@
+use exif::{Exif, Reader};
+use std::fs::File;
+use std::io::BufReader;
+use std::time::SystemTime;
+
+/// Filesystem metadata and EXIF data of a file
+pub struct FileReport {
+    pub file: String,
+    pub size: u64,
+    pub modified: Option<SystemTime>,
+    pub created: Option<SystemTime>,
+    pub accessed: Option<SystemTime>,
+    /// `None` if the file has no EXIF data
+    pub exif: Option<Exif>,
+}
+
+/// Function that reads the metadata and the EXIF data of a file
+/// It takes a path to a file as an argument
+/// It returns the report, or why the file could not be read
+pub fn read(file: &str) -> Result<FileReport, String> {
+    let metadata = match std::fs::metadata(file) {
+        Ok(metadata) => metadata,
+        Err(_) => return Err("File not found".to_string()),
+    };
+    Ok(FileReport {
+        file: file.to_string(),
+        size: metadata.len(),
+        modified: metadata.modified().ok(),
+        created: metadata.created().ok(),
+        accessed: metadata.accessed().ok(),
+        exif: read_exif(file),
+    })
+}
+
+/// Function that reads the EXIF data of a file
+/// It returns None if the file can not be opened or has no EXIF data
+fn read_exif(file: &str) -> Option<Exif> {
+    let file = File::open(file).ok()?;
+    let mut reader = BufReader::new(&file);
+    Reader::new().read_from_container(&mut reader).ok()
+}
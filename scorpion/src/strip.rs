@@ -0,0 +1,546 @@
+use exif::experimental::Writer;
+use exif::{Context, Error, Exif, Field, In, Reader, Tag};
+use std::fmt;
+use std::fs::File;
+use std::io::{BufReader, Cursor, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+
+const JPEG_SIGNATURE: [u8; 2] = [0xff, 0xd8];
+const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+const EXIF_ID: &[u8] = b"Exif\0\0";
+
+/// Metadata that can be removed from an image
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Kind {
+    Exif,
+    Xmp,
+    Iptc,
+    Icc,
+    /// Comments, text chunks, timestamps and application segments
+    Other,
+    /// Data after the end of the image, like the appended images of MPF or a Motion Photo video
+    Trailing,
+}
+
+impl fmt::Display for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Kind::Exif => write!(f, "EXIF"),
+            Kind::Xmp => write!(f, "XMP"),
+            Kind::Iptc => write!(f, "IPTC"),
+            Kind::Icc => write!(f, "ICC"),
+            Kind::Other => write!(f, "other metadata"),
+            Kind::Trailing => write!(f, "data after the image"),
+        }
+    }
+}
+
+/// What to keep when stripping an image
+/// `tags` are EXIF tag names of the primary image, like `Orientation`
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Keep {
+    pub tags: Vec<String>,
+    pub xmp: bool,
+    pub iptc: bool,
+    pub icc: bool,
+}
+
+impl Keep {
+    /// Function that parses the value of `--keep`
+    /// It takes a comma separated list of EXIF tag names, `xmp`, `iptc` and `icc`
+    /// Tag names are not case sensitive, an unknown name is an error
+    pub fn parse(list: &str) -> Result<Keep, String> {
+        let mut keep = Keep::default();
+        for name in list.split(',').map(|name| name.trim()) {
+            match name.to_lowercase().as_str() {
+                "" => {}
+                "xmp" => keep.xmp = true,
+                "iptc" => keep.iptc = true,
+                "icc" => keep.icc = true,
+                _ => match tag_name(name) {
+                    Some(tag) => keep.tags.push(tag),
+                    None => {
+                        return Err(format!(
+                            "unknown EXIF tag '{}' in --keep, expected tag names like Orientation, xmp, iptc or icc",
+                            name
+                        ))
+                    }
+                },
+            }
+        }
+        Ok(keep)
+    }
+
+    fn keeps(&self, kind: Kind) -> bool {
+        match kind {
+            Kind::Xmp => self.xmp,
+            Kind::Iptc => self.iptc,
+            Kind::Icc => self.icc,
+            Kind::Exif | Kind::Other | Kind::Trailing => false,
+        }
+    }
+
+    fn keeps_tag(&self, field: &Field) -> bool {
+        field.ifd_num == In::PRIMARY && self.tags.contains(&field.tag.to_string())
+    }
+}
+
+/// Function that returns the name of a known EXIF tag, as the reader writes it
+/// It takes a tag name in any case
+fn tag_name(name: &str) -> Option<String> {
+    let contexts = [Context::Tiff, Context::Exif, Context::Gps, Context::Interop];
+    contexts
+        .iter()
+        .flat_map(|context| (0..=u16::MAX).map(move |number| Tag(*context, number)))
+        .filter(|tag| tag.description().is_some())
+        .map(|tag| tag.to_string())
+        .find(|tag| tag.eq_ignore_ascii_case(name))
+}
+
+/// An image without its metadata
+pub struct Stripped {
+    pub data: Vec<u8>,
+    /// Kinds of metadata removed, in the order of the file
+    pub removed: Vec<Kind>,
+    /// EXIF tags kept by the allowlist
+    pub kept_tags: Vec<String>,
+}
+
+/// Function that removes the metadata of a JPEG or PNG image
+/// The pixels are copied as they are, without being decoded
+/// It returns an error for the other formats
+pub fn strip(data: &[u8], keep: &Keep) -> Result<Stripped, String> {
+    if data.starts_with(&JPEG_SIGNATURE) {
+        strip_jpeg(data, keep)
+    } else if data.starts_with(&PNG_SIGNATURE) {
+        strip_png(data, keep)
+    } else {
+        Err("unsupported format, only JPEG and PNG images can be stripped".to_string())
+    }
+}
+
+/// Function that lists the metadata left in a JPEG or PNG image
+pub fn metadata_kinds(data: &[u8]) -> Result<Vec<Kind>, String> {
+    let (mut kinds, end): (Vec<Kind>, usize) = if data.starts_with(&JPEG_SIGNATURE) {
+        let segments = jpeg_segments(data)?;
+        let kinds = segments
+            .iter()
+            .filter_map(|segment| segment.kind(data))
+            .collect();
+        (
+            kinds,
+            segments.last().map_or(data.len(), |segment| segment.end),
+        )
+    } else {
+        let chunks = png_chunks(data)?;
+        let kinds = chunks
+            .iter()
+            .filter_map(|chunk| png_kind(chunk.kind(data), chunk.payload(data)))
+            .collect();
+        (kinds, chunks.last().map_or(data.len(), |chunk| chunk.end))
+    };
+    if end < data.len() {
+        kinds.push(Kind::Trailing);
+    }
+    Ok(kinds)
+}
+
+/// A JPEG marker segment, from its `0xff` to the end of its payload
+/// A start of scan (`SOS`) segment runs to the end of the scan data that follows it
+struct Segment {
+    marker: u8,
+    start: usize,
+    end: usize,
+    /// The segment comes after the first scan
+    after_scan: bool,
+}
+
+impl Segment {
+    fn payload<'a>(&self, data: &'a [u8]) -> &'a [u8] {
+        match self.end - self.start > 4 {
+            true => &data[self.start + 4..self.end],
+            false => &[],
+        }
+    }
+
+    fn kind(&self, data: &[u8]) -> Option<Kind> {
+        jpeg_kind(self.marker, self.payload(data), self.after_scan)
+    }
+}
+
+/// Function that splits a JPEG image into its segments, up to the end of image (`EOI`)
+/// The scans of a progressive image are walked too, so nothing before `EOI` is skipped
+fn jpeg_segments(data: &[u8]) -> Result<Vec<Segment>, String> {
+    let mut segments = Vec::new();
+    let mut after_scan = false;
+    let mut pos = 2;
+    while pos < data.len() {
+        if data[pos] != 0xff {
+            return Err("malformed JPEG: expected a marker".to_string());
+        }
+        let mut marker_pos = pos + 1;
+        while marker_pos < data.len() && data[marker_pos] == 0xff {
+            marker_pos += 1;
+        }
+        let marker = *data.get(marker_pos).ok_or("truncated JPEG")?;
+        let start = marker_pos - 1;
+        let end = match marker {
+            // Markers without payload: TEM, RSTn and EOI.
+            0x01 | 0xd0..=0xd7 | 0xd9 => marker_pos + 1,
+            _ => {
+                let length = data
+                    .get(marker_pos + 1..marker_pos + 3)
+                    .ok_or("truncated JPEG")?;
+                let end = marker_pos + 1 + u16::from_be_bytes([length[0], length[1]]) as usize;
+                if end > data.len() {
+                    return Err("truncated JPEG".to_string());
+                }
+                end
+            }
+        };
+        // SOS: the scan data follows its header, up to the next marker.
+        let end = match marker {
+            0xda => scan_end(data, end)?,
+            _ => end,
+        };
+        segments.push(Segment {
+            marker,
+            start,
+            end,
+            after_scan,
+        });
+        if marker == 0xd9 {
+            return Ok(segments);
+        }
+        after_scan |= marker == 0xda;
+        pos = end;
+    }
+    Err("truncated JPEG: no end of image".to_string())
+}
+
+/// Function that finds the end of the scan data starting at `pos`
+/// In scan data, `0xff` is followed by a stuffed `0x00` or a restart marker
+fn scan_end(data: &[u8], mut pos: usize) -> Result<usize, String> {
+    while pos + 1 < data.len() {
+        if data[pos] == 0xff && data[pos + 1] != 0x00 && !(0xd0..=0xd7).contains(&data[pos + 1]) {
+            return Ok(pos);
+        }
+        pos += 1;
+    }
+    Err("truncated JPEG: no end of image".to_string())
+}
+
+/// Function that tells which metadata a JPEG segment holds
+/// JFIF (`APP0`) and Adobe (`APP14`) are kept in the header, they change how the pixels
+/// are decoded. After the first scan, every application segment and comment is metadata
+fn jpeg_kind(marker: u8, payload: &[u8], after_scan: bool) -> Option<Kind> {
+    match marker {
+        0xe1 if payload.starts_with(EXIF_ID) => Some(Kind::Exif),
+        0xe1 if payload.starts_with(b"http://ns.adobe.com/") => Some(Kind::Xmp),
+        0xed if payload.starts_with(b"Photoshop 3.0\0") => Some(Kind::Iptc),
+        0xe2 if payload.starts_with(b"ICC_PROFILE\0") => Some(Kind::Icc),
+        0xe0 | 0xee if !after_scan => None,
+        0xe0..=0xef | 0xfe => Some(Kind::Other),
+        _ => None,
+    }
+}
+
+fn strip_jpeg(data: &[u8], keep: &Keep) -> Result<Stripped, String> {
+    let mut stripped = Stripped {
+        data: JPEG_SIGNATURE.to_vec(),
+        removed: Vec::new(),
+        kept_tags: Vec::new(),
+    };
+    let segments = jpeg_segments(data)?;
+    for segment in &segments {
+        let payload = segment.payload(data);
+        let kind = match segment.kind(data) {
+            Some(kind) if !keep.keeps(kind) => kind,
+            _ => {
+                stripped
+                    .data
+                    .extend_from_slice(&data[segment.start..segment.end]);
+                continue;
+            }
+        };
+        stripped.removed.push(kind);
+        if kind != Kind::Exif || segment.after_scan || !stripped.kept_tags.is_empty() {
+            continue;
+        }
+        if let Some(kept) = kept_exif(&payload[EXIF_ID.len()..], keep)? {
+            let length = 2 + EXIF_ID.len() + kept.tiff.len();
+            if length > u16::MAX as usize {
+                return Err("the kept EXIF fields are too big".to_string());
+            }
+            stripped.data.extend_from_slice(&[0xff, 0xe1]);
+            stripped
+                .data
+                .extend_from_slice(&(length as u16).to_be_bytes());
+            stripped.data.extend_from_slice(EXIF_ID);
+            stripped.data.extend_from_slice(&kept.tiff);
+            stripped.kept_tags = kept.tags;
+        }
+    }
+    // Whatever follows the end of the image is dropped.
+    if segments
+        .last()
+        .is_some_and(|segment| segment.end < data.len())
+    {
+        stripped.removed.push(Kind::Trailing);
+    }
+    Ok(stripped)
+}
+
+/// A PNG chunk, from its length to its CRC
+struct Chunk {
+    start: usize,
+    end: usize,
+}
+
+impl Chunk {
+    fn kind<'a>(&self, data: &'a [u8]) -> &'a [u8] {
+        &data[self.start + 4..self.start + 8]
+    }
+
+    fn payload<'a>(&self, data: &'a [u8]) -> &'a [u8] {
+        &data[self.start + 8..self.end - 4]
+    }
+}
+
+/// Function that splits a PNG image into its chunks, up to `IEND`
+fn png_chunks(data: &[u8]) -> Result<Vec<Chunk>, String> {
+    let mut chunks = Vec::new();
+    let mut pos = PNG_SIGNATURE.len();
+    while pos < data.len() {
+        let length = data.get(pos..pos + 4).ok_or("truncated PNG")?;
+        let length = u32::from_be_bytes([length[0], length[1], length[2], length[3]]) as usize;
+        let end = pos + 12 + length;
+        if end > data.len() {
+            return Err("truncated PNG".to_string());
+        }
+        let chunk = Chunk { start: pos, end };
+        let is_end = chunk.kind(data) == b"IEND";
+        chunks.push(chunk);
+        if is_end {
+            return Ok(chunks);
+        }
+        pos = end;
+    }
+    Err("truncated PNG: no IEND chunk".to_string())
+}
+
+/// Function that tells which metadata a PNG chunk holds
+/// Text chunks are sorted by keyword, ImageMagick stores its raw profiles in them
+fn png_kind(kind: &[u8], payload: &[u8]) -> Option<Kind> {
+    match kind {
+        b"eXIf" => Some(Kind::Exif),
+        b"iCCP" => Some(Kind::Icc),
+        b"tIME" => Some(Kind::Other),
+        b"tEXt" | b"zTXt" | b"iTXt" => {
+            let keyword = payload.split(|byte| *byte == 0).next().unwrap_or(&[]);
+            let keyword = String::from_utf8_lossy(keyword).to_lowercase();
+            match keyword.as_str() {
+                "xml:com.adobe.xmp" | "raw profile type xmp" => Some(Kind::Xmp),
+                "raw profile type iptc" | "raw profile type 8bim" => Some(Kind::Iptc),
+                "raw profile type exif" | "raw profile type app1" => Some(Kind::Exif),
+                "raw profile type icc" | "raw profile type icm" => Some(Kind::Icc),
+                _ => Some(Kind::Other),
+            }
+        }
+        _ => None,
+    }
+}
+
+fn strip_png(data: &[u8], keep: &Keep) -> Result<Stripped, String> {
+    let mut stripped = Stripped {
+        data: PNG_SIGNATURE.to_vec(),
+        removed: Vec::new(),
+        kept_tags: Vec::new(),
+    };
+    let chunks = png_chunks(data)?;
+    for chunk in &chunks {
+        let kind = match png_kind(chunk.kind(data), chunk.payload(data)) {
+            Some(kind) if !keep.keeps(kind) => kind,
+            _ => {
+                stripped
+                    .data
+                    .extend_from_slice(&data[chunk.start..chunk.end]);
+                continue;
+            }
+        };
+        stripped.removed.push(kind);
+        if chunk.kind(data) != b"eXIf" || !stripped.kept_tags.is_empty() {
+            continue;
+        }
+        if let Some(kept) = kept_exif(chunk.payload(data), keep)? {
+            let mut crc = crc32fast::Hasher::new();
+            crc.update(b"eXIf");
+            crc.update(&kept.tiff);
+            stripped
+                .data
+                .extend_from_slice(&(kept.tiff.len() as u32).to_be_bytes());
+            stripped.data.extend_from_slice(b"eXIf");
+            stripped.data.extend_from_slice(&kept.tiff);
+            stripped
+                .data
+                .extend_from_slice(&crc.finalize().to_be_bytes());
+            stripped.kept_tags = kept.tags;
+        }
+    }
+    if chunks.last().is_some_and(|chunk| chunk.end < data.len()) {
+        stripped.removed.push(Kind::Trailing);
+    }
+    Ok(stripped)
+}
+
+/// EXIF data rebuilt with the fields of the allowlist
+struct KeptExif {
+    tiff: Vec<u8>,
+    tags: Vec<String>,
+}
+
+/// Function that rebuilds the EXIF data with only the fields of the allowlist
+/// It returns None if no field is kept
+fn kept_exif(tiff: &[u8], keep: &Keep) -> Result<Option<KeptExif>, String> {
+    if keep.tags.is_empty() {
+        return Ok(None);
+    }
+    let exif = match Reader::new().read_raw(tiff.to_vec()) {
+        Ok(exif) => exif,
+        // EXIF data that can not be read is removed entirely.
+        Err(_) => return Ok(None),
+    };
+    let fields: Vec<&Field> = exif
+        .fields()
+        .filter(|field| keep.keeps_tag(field) && !is_pointer(field.tag))
+        .collect();
+    if fields.is_empty() {
+        return Ok(None);
+    }
+    let mut writer = Writer::new();
+    for field in &fields {
+        writer.push_field(field);
+    }
+    let mut tiff = Cursor::new(Vec::new());
+    writer
+        .write(&mut tiff, exif.little_endian())
+        .map_err(|err| format!("cannot write the kept EXIF fields: {}", err))?;
+    let tags = fields.iter().map(|field| field.tag.to_string()).collect();
+    Ok(Some(KeptExif {
+        tiff: tiff.into_inner(),
+        tags,
+    }))
+}
+
+/// Function that tells if a tag only links the parts of the EXIF data together
+/// These tags are rebuilt by the writer
+fn is_pointer(tag: Tag) -> bool {
+    tag == Tag::ExifIFDPointer || tag == Tag::GPSInfoIFDPointer || tag == Tag::InteropIFDPointer
+}
+
+/// Function that checks a stripped image with the EXIF reader:
+/// only the EXIF fields of the allowlist may be left, and no other metadata
+/// The reader stops at the image data, so the whole file is scanned too
+pub fn verify(path: &Path, keep: &Keep) -> Result<(), String> {
+    let file = File::open(path).map_err(|err| err.to_string())?;
+    match Reader::new().read_from_container(&mut BufReader::new(&file)) {
+        Ok(exif) => check_exif(&exif, keep)?,
+        Err(Error::NotFound(_)) => {}
+        Err(err) => return Err(format!("cannot read the EXIF data back: {}", err)),
+    }
+    let data = std::fs::read(path).map_err(|err| err.to_string())?;
+    for kind in metadata_kinds(&data)? {
+        // The EXIF fields left were checked by the reader.
+        let kept_exif = kind == Kind::Exif && !keep.tags.is_empty();
+        if !keep.keeps(kind) && !kept_exif {
+            return Err(format!("{} is still present", kind));
+        }
+    }
+    // Nothing after the first scan may look like EXIF data.
+    if data.starts_with(&JPEG_SIGNATURE) {
+        let scan = jpeg_segments(&data)?
+            .into_iter()
+            .find(|segment| segment.marker == 0xda);
+        if let Some(scan) = scan {
+            let after_scan = &data[scan.start..];
+            if after_scan
+                .windows(EXIF_ID.len())
+                .any(|window| window == EXIF_ID)
+            {
+                return Err("EXIF data is still present after the image data".to_string());
+            }
+        }
+    }
+    Ok(())
+}
+
+fn check_exif(exif: &Exif, keep: &Keep) -> Result<(), String> {
+    for field in exif.fields() {
+        if !is_pointer(field.tag) && !keep.keeps_tag(field) {
+            return Err(format!("the EXIF field {} is still present", field.tag));
+        }
+    }
+    Ok(())
+}
+
+/// Function that creates a new temporary file next to the destination
+/// It never opens a file that already exists, the name has the process id and a counter
+/// It returns the path and the opened file
+fn create_temp_file(directory: &Path, file_name: &str) -> Result<(PathBuf, File), String> {
+    for attempt in 0..100 {
+        let temp_path = directory.join(format!(
+            ".{}.{}-{}.scorpion-tmp",
+            file_name,
+            std::process::id(),
+            attempt
+        ));
+        match File::create_new(&temp_path) {
+            Ok(file) => return Ok((temp_path, file)),
+            Err(err) if err.kind() == ErrorKind::AlreadyExists => continue,
+            Err(err) => return Err(format!("cannot write {}: {}", temp_path.display(), err)),
+        }
+    }
+    Err(format!(
+        "cannot create a temporary file in {}",
+        directory.display()
+    ))
+}
+
+/// Function that gives the temporary file the permissions of the file it replaces
+/// A new destination keeps the permissions of the temporary file
+fn keep_permissions(file: &File, destination: &Path) -> std::io::Result<()> {
+    match std::fs::metadata(destination) {
+        Ok(metadata) => file.set_permissions(metadata.permissions()),
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(()),
+        Err(err) => Err(err),
+    }
+}
+
+/// Function that writes a stripped image to its destination
+/// The image is written to a temporary file next to it and verified,
+/// then moved in place: the destination is never left half written
+pub fn write_verified(destination: &Path, data: &[u8], keep: &Keep) -> Result<(), String> {
+    let directory = destination
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let file_name = destination
+        .file_name()
+        .ok_or("invalid destination")?
+        .to_string_lossy();
+    let (temp_path, mut file) = create_temp_file(directory, &file_name)?;
+    let result = file
+        .write_all(data)
+        .and_then(|_| keep_permissions(&file, destination))
+        .and_then(|_| file.sync_all())
+        .map_err(|err| format!("cannot write {}: {}", temp_path.display(), err))
+        .and_then(|_| verify(&temp_path, keep))
+        .and_then(|_| {
+            std::fs::rename(&temp_path, destination)
+                .map_err(|err| format!("cannot write {}: {}", destination.display(), err))
+        });
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temp_path);
+    }
+    result
+}
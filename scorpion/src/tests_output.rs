@@ -0,0 +1,208 @@
+#[cfg(test)]
+use super::output::{self, Format};
+#[cfg(test)]
+use super::parse_args;
+#[cfg(test)]
+use super::report::FileReport;
+#[cfg(test)]
+use exif::experimental::Writer;
+#[cfg(test)]
+use exif::{Field, In, Rational, Reader, Tag, Value};
+#[cfg(test)]
+use serde_json::json;
+#[cfg(test)]
+use std::io::Cursor;
+#[cfg(test)]
+use std::time::{Duration, UNIX_EPOCH};
+
+/// Function that builds an EXIF with a make in the primary image
+/// and a resolution in the primary image and in the thumbnail, from a small TIFF
+#[cfg(test)]
+fn exif() -> exif::Exif {
+    let make = Field {
+        tag: Tag::Make,
+        ifd_num: In::PRIMARY,
+        value: Value::Ascii(vec![b"Acme, \"Pro\"".to_vec()]),
+    };
+    let resolution = Field {
+        tag: Tag::XResolution,
+        ifd_num: In::PRIMARY,
+        value: Value::Rational(vec![Rational { num: 72, denom: 1 }]),
+    };
+    let thumbnail_resolution = Field {
+        tag: Tag::XResolution,
+        ifd_num: In::THUMBNAIL,
+        value: Value::Rational(vec![Rational { num: 96, denom: 1 }]),
+    };
+    let mut writer = Writer::new();
+    writer.push_field(&make);
+    writer.push_field(&resolution);
+    writer.push_field(&thumbnail_resolution);
+    let mut tiff = Cursor::new(Vec::new());
+    writer.write(&mut tiff, false).unwrap();
+    Reader::new().read_raw(tiff.into_inner()).unwrap()
+}
+
+#[cfg(test)]
+fn report(exif: Option<exif::Exif>) -> FileReport {
+    FileReport {
+        file: "a,b.jpg".to_string(),
+        size: 1234,
+        modified: Some(UNIX_EPOCH + Duration::from_secs(1_700_000_000)),
+        created: None,
+        accessed: Some(UNIX_EPOCH),
+        exif,
+    }
+}
+
+#[cfg(test)]
+fn args(args: &[&str]) -> Vec<String> {
+    args.iter().map(|arg| arg.to_string()).collect()
+}
+
+#[test]
+fn test_format_parse() {
+    assert_eq!(Format::parse("text"), Ok(Format::Text));
+    assert_eq!(Format::parse("json"), Ok(Format::Json));
+    assert_eq!(Format::parse("csv"), Ok(Format::Csv));
+    assert_eq!(
+        Format::parse("xml"),
+        Err("invalid format 'xml', expected json, csv or text".to_string())
+    );
+    assert!(Format::parse("JSON").is_err());
+}
+
+#[test]
+fn test_parse_args() {
+    let options = parse_args(&args(&["--format=json", "a.jpg", "b.png"])).unwrap();
+    assert_eq!(options.format, Format::Json);
+    assert_eq!(options.files, vec!["a.jpg", "b.png"]);
+    let options = parse_args(&args(&["a.jpg", "--format", "csv"])).unwrap();
+    assert_eq!(options.format, Format::Csv);
+    assert_eq!(options.files, vec!["a.jpg"]);
+    assert_eq!(parse_args(&[]).unwrap().format, Format::Text);
+}
+
+#[test]
+fn test_parse_args_errors() {
+    assert!(parse_args(&args(&["--format", "xml", "a.jpg"])).is_err());
+    assert_eq!(
+        parse_args(&args(&["--format"])).err(),
+        Some("--format needs a value: json, csv or text".to_string())
+    );
+    assert!(parse_args(&args(&["--keep", "Orientation", "a.jpg"])).is_err());
+    assert!(parse_args(&args(&["-o", "out.jpg", "a.jpg"])).is_err());
+    assert!(parse_args(&args(&["--strip", "--format", "csv", "a.jpg"])).is_err());
+    assert!(parse_args(&args(&["--strip", "--keep", "Orientaton", "a.jpg"])).is_err());
+}
+
+#[test]
+fn test_csv_field() {
+    assert_eq!(output::csv_field("plain"), "plain");
+    assert_eq!(output::csv_field("a,b"), "\"a,b\"");
+    assert_eq!(output::csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+    assert_eq!(output::csv_field("two\nlines"), "\"two\nlines\"");
+    assert_eq!(output::csv_field("two\rlines"), "\"two\rlines\"");
+    assert_eq!(output::csv_field(""), "");
+}
+
+#[test]
+fn test_raw_value() {
+    assert_eq!(output::raw_value(&Value::Short(vec![1])), json!(1));
+    assert_eq!(output::raw_value(&Value::Short(vec![1, 2])), json!([1, 2]));
+    assert_eq!(
+        output::raw_value(&Value::Rational(vec![Rational { num: 72, denom: 1 }])),
+        json!([72, 1])
+    );
+    assert_eq!(
+        output::raw_value(&Value::Rational(vec![
+            Rational { num: 1, denom: 2 },
+            Rational { num: 3, denom: 4 }
+        ])),
+        json!([[1, 2], [3, 4]])
+    );
+    assert_eq!(
+        output::raw_value(&Value::Ascii(vec![b"Acme".to_vec()])),
+        json!("Acme")
+    );
+    assert_eq!(
+        output::raw_value(&Value::Undefined(b"0230".to_vec(), 0)),
+        json!("30323330")
+    );
+    assert_eq!(
+        output::raw_value(&Value::Unknown(99, 1, 0)),
+        serde_json::Value::Null
+    );
+}
+
+#[test]
+fn test_value_type() {
+    assert_eq!(output::value_type(&Value::Short(vec![1])), "SHORT");
+    assert_eq!(output::value_type(&Value::Rational(vec![])), "RATIONAL");
+    assert_eq!(
+        output::value_type(&Value::Undefined(vec![], 0)),
+        "UNDEFINED"
+    );
+    assert_eq!(output::value_type(&Value::Unknown(99, 1, 0)), "UNKNOWN");
+}
+
+#[test]
+fn test_to_json() {
+    let object = output::to_json(&report(Some(exif())));
+    assert_eq!(object["file"], "a,b.jpg");
+    assert_eq!(object["size"], 1234);
+    assert_eq!(object["modified"], "2023-11-14T22:13:20Z");
+    assert_eq!(object["created"], serde_json::Value::Null);
+    assert_eq!(object["accessed"], "1970-01-01T00:00:00Z");
+    // The fields are grouped by IFD, then keyed by tag name.
+    assert_eq!(
+        object["exif"]["primary"]["XResolution"],
+        json!({"type": "RATIONAL", "raw": [72, 1], "display": "72 pixels per inch"})
+    );
+    assert_eq!(object["exif"]["primary"]["Make"]["raw"], "Acme, \"Pro\"");
+    assert_eq!(
+        object["exif"]["thumbnail"]["XResolution"]["raw"],
+        json!([96, 1])
+    );
+    assert!(object["exif"]["thumbnail"].get("Make").is_none());
+}
+
+#[test]
+fn test_to_json_without_exif() {
+    let object = output::to_json(&report(None));
+    assert_eq!(object["exif"], serde_json::Value::Null);
+    assert_eq!(
+        output::error_json("a.jpg", "File not found"),
+        json!({"file": "a.jpg", "error": "File not found"})
+    );
+}
+
+#[test]
+fn test_csv_rows() {
+    let rows = output::csv_rows(&report(Some(exif())));
+    assert_eq!(rows.len(), 3);
+    let file_columns = "\"a,b.jpg\",1234,2023-11-14T22:13:20Z,,1970-01-01T00:00:00Z";
+    assert!(rows.contains(&format!(
+        "{},primary,XResolution,RATIONAL,\"[72,1]\",72 pixels per inch",
+        file_columns
+    )));
+    assert!(rows.contains(&format!(
+        "{},thumbnail,XResolution,RATIONAL,\"[96,1]\",96 pixels per inch",
+        file_columns
+    )));
+    // The quotes and the comma of the make are escaped.
+    assert!(rows.contains(&format!(
+        r#"{},primary,Make,ASCII,"""Acme, \""Pro\""""","""Acme, \""Pro\""""""#,
+        file_columns
+    )));
+    assert_eq!(output::CSV_HEADER.split(',').count(), 10);
+}
+
+#[test]
+fn test_csv_rows_without_exif() {
+    let rows = output::csv_rows(&report(None));
+    assert_eq!(
+        rows,
+        vec!["\"a,b.jpg\",1234,2023-11-14T22:13:20Z,,1970-01-01T00:00:00Z,,,,,"]
+    );
+}
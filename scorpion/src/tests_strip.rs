@@ -0,0 +1,327 @@
+#[cfg(test)]
+use super::strip::{self, Keep, Kind};
+#[cfg(test)]
+use exif::experimental::Writer;
+#[cfg(test)]
+use exif::{Field, In, Reader, Tag, Value};
+#[cfg(test)]
+use std::io::Cursor;
+
+/// Function that builds a small TIFF with an orientation, a make and a thumbnail resolution
+#[cfg(test)]
+fn tiff() -> Vec<u8> {
+    let orientation = Field {
+        tag: Tag::Orientation,
+        ifd_num: In::PRIMARY,
+        value: Value::Short(vec![6]),
+    };
+    let make = Field {
+        tag: Tag::Make,
+        ifd_num: In::PRIMARY,
+        value: Value::Ascii(vec![b"Acme".to_vec()]),
+    };
+    let thumbnail_orientation = Field {
+        tag: Tag::Orientation,
+        ifd_num: In::THUMBNAIL,
+        value: Value::Short(vec![1]),
+    };
+    let mut writer = Writer::new();
+    writer.push_field(&orientation);
+    writer.push_field(&make);
+    writer.push_field(&thumbnail_orientation);
+    let mut tiff = Cursor::new(Vec::new());
+    writer.write(&mut tiff, false).unwrap();
+    tiff.into_inner()
+}
+
+#[cfg(test)]
+fn segment(marker: u8, payload: &[u8]) -> Vec<u8> {
+    let mut segment = vec![0xff, marker];
+    segment.extend_from_slice(&(payload.len() as u16 + 2).to_be_bytes());
+    segment.extend_from_slice(payload);
+    segment
+}
+
+/// Function that builds a JPEG with every kind of metadata around a single scan
+/// The pixels are not valid, only the structure of the file matters
+#[cfg(test)]
+fn jpeg() -> Vec<u8> {
+    let mut exif = b"Exif\0\0".to_vec();
+    exif.extend_from_slice(&tiff());
+    let mut data = vec![0xff, 0xd8];
+    data.extend(segment(0xe0, b"JFIF\0\x01\x01\0\0\x01\0\x01\0\0"));
+    data.extend(segment(0xe1, &exif));
+    data.extend(segment(0xe1, b"http://ns.adobe.com/xap/1.0/\0<x:xmpmeta/>"));
+    data.extend(segment(0xe2, b"ICC_PROFILE\0\x01\x01profile"));
+    data.extend(segment(0xed, b"Photoshop 3.0\08BIM\x04\x04"));
+    data.extend(segment(0xee, b"Adobe\0\x64\0\0\0\0\x01"));
+    data.extend(segment(0xfe, b"a comment"));
+    data.extend(segment(0xc0, b"\x08\0\x01\0\x01\x01\x01\x11\0"));
+    // The scan data holds a stuffed 0xff and a restart marker.
+    data.extend(segment(0xda, b"\x01\x01\0\0\x3f\0"));
+    data.extend_from_slice(&[0x12, 0xff, 0x00, 0x34, 0xff, 0xd0, 0x56]);
+    data.extend_from_slice(&[0xff, 0xd9]);
+    data
+}
+
+#[cfg(test)]
+fn chunk(kind: &[u8], payload: &[u8]) -> Vec<u8> {
+    let mut crc = crc32fast::Hasher::new();
+    crc.update(kind);
+    crc.update(payload);
+    let mut chunk = (payload.len() as u32).to_be_bytes().to_vec();
+    chunk.extend_from_slice(kind);
+    chunk.extend_from_slice(payload);
+    chunk.extend_from_slice(&crc.finalize().to_be_bytes());
+    chunk
+}
+
+/// Function that builds a PNG with every kind of metadata
+#[cfg(test)]
+fn png() -> Vec<u8> {
+    let mut data = b"\x89PNG\r\n\x1a\n".to_vec();
+    data.extend(chunk(b"IHDR", b"\0\0\0\x01\0\0\0\x01\x08\0\0\0\0"));
+    data.extend(chunk(b"eXIf", &tiff()));
+    data.extend(chunk(b"iCCP", b"profile\0\0compressed"));
+    data.extend(chunk(b"iTXt", b"XML:com.adobe.xmp\0\0\0\0\0<x:xmpmeta/>"));
+    data.extend(chunk(b"tEXt", b"Raw profile type iptc\0data"));
+    data.extend(chunk(b"tIME", b"\x07\xe8\x01\x02\x03\x04\x05"));
+    data.extend(chunk(b"IDAT", b"\x78\x9c\x63\x60\0\0\0\x02\0\x01"));
+    data.extend(chunk(b"IEND", b""));
+    data
+}
+
+/// Function that splits a PNG into its chunk types and payloads, checking every CRC
+#[cfg(test)]
+fn png_chunks(data: &[u8]) -> Vec<(Vec<u8>, Vec<u8>)> {
+    let mut chunks = Vec::new();
+    let mut pos = 8;
+    while pos < data.len() {
+        let length = u32::from_be_bytes(data[pos..pos + 4].try_into().unwrap()) as usize;
+        let kind = &data[pos + 4..pos + 8];
+        let payload = &data[pos + 8..pos + 8 + length];
+        let crc = &data[pos + 8 + length..pos + 12 + length];
+        let mut hasher = crc32fast::Hasher::new();
+        hasher.update(kind);
+        hasher.update(payload);
+        assert_eq!(crc, hasher.finalize().to_be_bytes());
+        chunks.push((kind.to_vec(), payload.to_vec()));
+        pos += 12 + length;
+    }
+    chunks
+}
+
+#[cfg(test)]
+fn contains(data: &[u8], part: &[u8]) -> bool {
+    data.windows(part.len()).any(|window| window == part)
+}
+
+#[cfg(test)]
+fn keep(list: &str) -> Keep {
+    Keep::parse(list).unwrap()
+}
+
+#[test]
+fn test_keep_parse() {
+    assert_eq!(
+        keep("orientation, XMP,icc"),
+        Keep {
+            tags: vec!["Orientation".to_string()],
+            xmp: true,
+            iptc: false,
+            icc: true,
+        }
+    );
+    assert_eq!(keep(""), Keep::default());
+    assert_eq!(keep("GPSLatitude").tags, vec!["GPSLatitude".to_string()]);
+    assert!(Keep::parse("Orientaton").is_err());
+    assert!(Keep::parse("exif").is_err());
+    assert!(Keep::parse("Orientation,Tag(Tiff, 1)").is_err());
+}
+
+#[test]
+fn test_strip_jpeg() {
+    let data = jpeg();
+    let stripped = strip::strip(&data, &Keep::default()).unwrap();
+    assert_eq!(
+        stripped.removed,
+        vec![Kind::Exif, Kind::Xmp, Kind::Icc, Kind::Iptc, Kind::Other]
+    );
+    assert!(stripped.kept_tags.is_empty());
+    assert!(!contains(&stripped.data, b"Exif\0\0"));
+    assert!(!contains(&stripped.data, b"xmpmeta"));
+    assert!(!contains(&stripped.data, b"ICC_PROFILE"));
+    assert!(!contains(&stripped.data, b"Photoshop"));
+    assert!(!contains(&stripped.data, b"a comment"));
+    // JFIF and Adobe change how the pixels are decoded.
+    assert!(contains(&stripped.data, b"JFIF\0"));
+    assert!(contains(&stripped.data, b"Adobe\0"));
+    assert!(stripped
+        .data
+        .ends_with(&[0x12, 0xff, 0x00, 0x34, 0xff, 0xd0, 0x56, 0xff, 0xd9]));
+    assert_eq!(strip::metadata_kinds(&stripped.data).unwrap(), vec![]);
+    assert!(matches!(
+        Reader::new().read_from_container(&mut Cursor::new(&stripped.data)),
+        Err(exif::Error::NotFound(_))
+    ));
+}
+
+#[test]
+fn test_strip_jpeg_keep_orientation() {
+    let stripped = strip::strip(&jpeg(), &keep("Orientation")).unwrap();
+    assert_eq!(stripped.kept_tags, vec!["Orientation".to_string()]);
+    assert_eq!(stripped.removed[0], Kind::Exif);
+    let exif = Reader::new()
+        .read_from_container(&mut Cursor::new(&stripped.data))
+        .unwrap();
+    let fields: Vec<&Field> = exif.fields().collect();
+    assert_eq!(fields.len(), 1);
+    assert_eq!(fields[0].tag, Tag::Orientation);
+    assert_eq!(fields[0].ifd_num, In::PRIMARY);
+    assert_eq!(fields[0].value.get_uint(0), Some(6));
+}
+
+#[test]
+fn test_strip_jpeg_keep_kinds() {
+    let stripped = strip::strip(&jpeg(), &keep("xmp,iptc,icc")).unwrap();
+    assert_eq!(stripped.removed, vec![Kind::Exif, Kind::Other]);
+    assert!(contains(&stripped.data, b"xmpmeta"));
+    assert!(contains(&stripped.data, b"Photoshop 3.0\0"));
+    assert!(contains(&stripped.data, b"ICC_PROFILE\0"));
+    assert!(!contains(&stripped.data, b"Exif\0\0"));
+    assert_eq!(
+        strip::metadata_kinds(&stripped.data).unwrap(),
+        vec![Kind::Xmp, Kind::Icc, Kind::Iptc]
+    );
+}
+
+#[test]
+fn test_strip_jpeg_after_the_image() {
+    let mut data = jpeg();
+    let end = data.len();
+    // Metadata between two scans, then an appended image with its own EXIF.
+    data.truncate(end - 2);
+    data.extend(segment(0xe1, b"Exif\0\0between scans"));
+    data.extend(segment(0xe0, b"JFIF\0"));
+    data.extend(segment(0xda, b"\x01\x01\0\0\x3f\0"));
+    data.extend_from_slice(&[0x78, 0xff, 0xd9]);
+    data.extend_from_slice(&jpeg());
+    let stripped = strip::strip(&data, &keep("Orientation")).unwrap();
+    assert_eq!(
+        stripped.removed[5..],
+        [Kind::Exif, Kind::Other, Kind::Trailing]
+    );
+    assert!(!contains(&stripped.data, b"between scans"));
+    assert!(stripped.data.ends_with(&[0x78, 0xff, 0xd9]));
+    assert_eq!(
+        stripped
+            .data
+            .windows(2)
+            .filter(|window| window == &[0xff, 0xd8])
+            .count(),
+        1
+    );
+    assert_eq!(
+        strip::metadata_kinds(&data).unwrap().last(),
+        Some(&Kind::Trailing)
+    );
+}
+
+#[test]
+fn test_strip_png() {
+    let stripped = strip::strip(&png(), &Keep::default()).unwrap();
+    assert_eq!(
+        stripped.removed,
+        vec![Kind::Exif, Kind::Icc, Kind::Xmp, Kind::Iptc, Kind::Other]
+    );
+    let kinds: Vec<Vec<u8>> = png_chunks(&stripped.data)
+        .into_iter()
+        .map(|(kind, _)| kind)
+        .collect();
+    assert_eq!(
+        kinds,
+        vec![b"IHDR".to_vec(), b"IDAT".to_vec(), b"IEND".to_vec()]
+    );
+    assert_eq!(strip::metadata_kinds(&stripped.data).unwrap(), vec![]);
+}
+
+#[test]
+fn test_strip_png_keep_orientation() {
+    let stripped = strip::strip(&png(), &keep("orientation,icc")).unwrap();
+    assert_eq!(stripped.kept_tags, vec!["Orientation".to_string()]);
+    let chunks = png_chunks(&stripped.data);
+    let kinds: Vec<&[u8]> = chunks.iter().map(|(kind, _)| kind.as_slice()).collect();
+    assert_eq!(
+        kinds,
+        vec![&b"IHDR"[..], b"eXIf", b"iCCP", b"IDAT", b"IEND"]
+    );
+    let exif = Reader::new()
+        .read_from_container(&mut Cursor::new(&stripped.data))
+        .unwrap();
+    let fields: Vec<&Field> = exif.fields().collect();
+    assert_eq!(fields.len(), 1);
+    assert_eq!(fields[0].tag, Tag::Orientation);
+    assert_eq!(fields[0].value.get_uint(0), Some(6));
+}
+
+#[test]
+fn test_strip_png_after_the_image() {
+    let mut data = png();
+    data.extend_from_slice(b"Exif\0\0appended");
+    let stripped = strip::strip(&data, &Keep::default()).unwrap();
+    assert_eq!(stripped.removed.last(), Some(&Kind::Trailing));
+    assert!(!contains(&stripped.data, b"appended"));
+    assert_eq!(png_chunks(&stripped.data).len(), 3);
+}
+
+#[test]
+fn test_strip_truncated() {
+    let data = jpeg();
+    for end in [3, 5, 30, data.len() - 6, data.len() - 1] {
+        assert!(strip::strip(&data[..end], &Keep::default()).is_err());
+        assert!(strip::metadata_kinds(&data[..end]).is_err());
+    }
+    let data = png();
+    for end in [9, 12, 40, data.len() - 4, data.len() - 12] {
+        assert!(strip::strip(&data[..end], &Keep::default()).is_err());
+        assert!(strip::metadata_kinds(&data[..end]).is_err());
+    }
+    assert!(strip::strip(b"GIF89a", &Keep::default()).is_err());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_write_verified_keeps_permissions() {
+    use std::os::unix::fs::PermissionsExt;
+    let directory = std::env::temp_dir().join(format!("scorpion-test-mode-{}", std::process::id()));
+    std::fs::create_dir_all(&directory).unwrap();
+    let path = directory.join("a.jpg");
+    // 0644 is also what a new file gets with the usual umask, the other modes are not.
+    for mode in [0o644, 0o600, 0o664] {
+        std::fs::write(&path, jpeg()).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(mode)).unwrap();
+        // The file is stripped in place, like without --output.
+        let stripped = strip::strip(&std::fs::read(&path).unwrap(), &Keep::default()).unwrap();
+        strip::write_verified(&path, &stripped.data, &Keep::default()).unwrap();
+        let permissions = std::fs::metadata(&path).unwrap().permissions();
+        assert_eq!(permissions.mode() & 0o777, mode);
+    }
+    std::fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn test_write_verified() {
+    let directory = std::env::temp_dir().join(format!("scorpion-test-{}", std::process::id()));
+    std::fs::create_dir_all(&directory).unwrap();
+    let destination = directory.join("a.jpg");
+    let stripped = strip::strip(&jpeg(), &keep("Orientation")).unwrap();
+    strip::write_verified(&destination, &stripped.data, &keep("Orientation")).unwrap();
+    assert_eq!(std::fs::read(&destination).unwrap(), stripped.data);
+    // Without the allowlist, the kept orientation makes the check fail.
+    assert!(strip::write_verified(&destination, &stripped.data, &Keep::default()).is_err());
+    assert!(strip::write_verified(&destination, &jpeg(), &keep("xmp,iptc,icc")).is_err());
+    assert_eq!(std::fs::read(&destination).unwrap(), stripped.data);
+    let files = std::fs::read_dir(&directory).unwrap().count();
+    std::fs::remove_dir_all(&directory).unwrap();
+    assert_eq!(files, 1);
+}
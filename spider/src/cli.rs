@@ -0,0 +1,736 @@
+use clap::error::ErrorKind;
+use clap::parser::ValueSource;
+use clap::{command, value_parser, Arg, ArgAction, ArgMatches, Command};
+use std::ffi::OsString;
+use std::time::Duration;
+use toml::{Table, Value};
+
+use spider::config::{ConfigFile, Setting};
+use spider::crawler::budget::{parse_duration, parse_size};
+use spider::crawler::ImageFound;
+use spider::http::auth::{parse_basic, Auth};
+use spider::http::tls::TlsOptions;
+use spider::http::ClientOptions;
+use spider::http::{parse_form_field, parse_header};
+use spider::logging::{self, progress, Format, Level};
+use spider::parsing::domain;
+use spider::parsing::links::PageLinks;
+use spider::parsing::normalize::{parse_strip_params, DEFAULT_STRIP_PARAMS};
+#[cfg(feature = "render")]
+use spider::render::RenderOptions;
+use spider::storage::{ArchiveStorage, Existing, FsStorage, S3Config, S3Storage};
+use spider::{Crawler, CrawlerBuilder};
+
+/// Function that declares the command line of Spider.
+pub fn command() -> Command {
+    with_render_args(command!()
+    .version("1.0")
+    .author("hboissel")
+    .about("Downloads images from a website")
+    .arg(
+        Arg::new("URL")
+            .required(true)
+            .index(1)
+            .value_parser(parse_url)
+            .help("The URL to download images from"),
+    )
+    .arg(
+        Arg::new("recursive")
+            .short('r')
+            .long("recursive")
+            .action(ArgAction::SetTrue)
+            .help("Recursively download images"),
+    )
+    .arg(
+        Arg::new("level")
+            .short('l')
+            .long("level")
+            .value_name("N")
+            .value_parser(value_parser!(u16))
+            .help("Maximum depth level for recursive download, implies -r (default is 5)"),
+    )
+    .arg(
+        Arg::new("config")
+            .long("config")
+            .value_name("FILE.toml")
+            .help("Read the settings from a TOML file, the command line options override them"),
+    )
+    .arg(
+        Arg::new("profile")
+            .long("profile")
+            .value_name("NAME")
+            .requires("config")
+            .help("Use the [profiles.NAME] settings of the config file"),
+    )
+    .arg(
+        Arg::new("print-config")
+            .long("print-config")
+            .action(ArgAction::SetTrue)
+            .help("Print the effective settings as TOML and exit"),
+    )
+    .arg(
+        Arg::new("scope")
+            .long("scope")
+            .value_name("DOMAIN")
+            .action(ArgAction::Append)
+            .help("Domain the crawl may visit, can be repeated (default is the domain of URL)"),
+    )
+    .arg(
+        Arg::new("path")
+            .short('p')
+            .long("path")
+            .default_value("./data")
+            .help("Path to save downloaded files"),
+    )
+    .arg(
+        Arg::new("archive")
+            .long("archive")
+            .value_name("FILE")
+            .conflicts_with_all(["path", "s3"])
+            .help("Write the images and a manifest.json into a .zip, .tar or .tar.gz archive"),
+    )
+    .arg(
+        Arg::new("s3")
+            .long("s3")
+            .value_name("BUCKET[/PREFIX]")
+            .conflicts_with("path")
+            .help("Upload the images to an S3-compatible bucket instead of --path (credentials from AWS_ACCESS_KEY_ID/AWS_SECRET_ACCESS_KEY)"),
+    )
+    .arg(
+        Arg::new("overwrite")
+            .long("overwrite")
+            .action(ArgAction::SetTrue)
+            .conflicts_with_all(["skip-existing", "rename", "archive", "s3"])
+            .help("Replace the images already in --path"),
+    )
+    .arg(
+        Arg::new("skip-existing")
+            .long("skip-existing")
+            .action(ArgAction::SetTrue)
+            .conflicts_with_all(["rename", "archive", "s3"])
+            .help("Keep the images already in --path and do not download them again (default)"),
+    )
+    .arg(
+        Arg::new("rename")
+            .long("rename")
+            .action(ArgAction::SetTrue)
+            .conflicts_with_all(["archive", "s3"])
+            .help("Save the images already in --path under a new name, as name-1.ext"),
+    )
+    .arg(
+        Arg::new("s3-endpoint")
+            .long("s3-endpoint")
+            .value_name("URL")
+            .default_value("https://s3.amazonaws.com")
+            .help("Endpoint of the S3-compatible object store"),
+    )
+    .arg(
+        Arg::new("s3-region")
+            .long("s3-region")
+            .value_name("REGION")
+            .help("Region of the bucket (default is AWS_REGION or us-east-1)"),
+    )
+    .arg(
+        Arg::new("concurrency")
+            .short('c')
+            .long("concurrency")
+            .default_value("1")
+            .value_parser(parse_positive)
+            .help("Number of images downloaded at the same time"),
+    )
+    .arg(
+        Arg::new("max-pages")
+            .long("max-pages")
+            .value_name("N")
+            .value_parser(parse_positive)
+            .help("Stop the crawl after fetching N pages"),
+    )
+    .arg(
+        Arg::new("max-images")
+            .long("max-images")
+            .value_name("N")
+            .value_parser(parse_positive)
+            .help("Stop the crawl after saving N images"),
+    )
+    .arg(
+        Arg::new("max-bytes")
+            .long("max-bytes")
+            .value_name("SIZE")
+            .value_parser(parse_size)
+            .help("Stop the crawl after downloading SIZE bytes, pages included (500K, 20M, 1G)"),
+    )
+    .arg(
+        Arg::new("max-time")
+            .long("max-time")
+            .value_name("DURATION")
+            .value_parser(parse_duration)
+            .help("Stop the crawl after DURATION (90s, 10m, 2h)"),
+    )
+    .arg(
+        Arg::new("strip-params")
+            .long("strip-params")
+            .value_name("LIST")
+            .default_value(DEFAULT_STRIP_PARAMS)
+            .help("Comma separated query parameters ignored when comparing URLs, `prefix*` matches a prefix"),
+    )
+    .arg(
+        Arg::new("cookies")
+            .long("cookies")
+            .value_name("FILE")
+            .help("Load cookies from a Netscape cookies.txt file"),
+    )
+    .arg(
+        Arg::new("login-url")
+            .long("login-url")
+            .value_name("URL")
+            .help("POST the login form to this URL once before crawling"),
+    )
+    .arg(
+        Arg::new("login-field")
+            .long("login-field")
+            .value_name("KEY=VALUE")
+            .action(ArgAction::Append)
+            .requires("login-url")
+            .value_parser(parse_form_field)
+            .help("Field of the login form, can be repeated"),
+    )
+    .arg(
+        Arg::new("basic-auth")
+            .long("basic-auth")
+            .value_name("USER:PASSWORD")
+            .value_parser(parse_basic)
+            .conflicts_with("bearer")
+            .help("Send HTTP Basic credentials to the authenticated hosts"),
+    )
+    .arg(
+        Arg::new("bearer")
+            .long("bearer")
+            .value_name("TOKEN")
+            .help("Send a Bearer token to the authenticated hosts"),
+    )
+    .arg(
+        Arg::new("auth-host")
+            .long("auth-host")
+            .value_name("HOST")
+            .action(ArgAction::Append)
+            .help("Host allowed to receive the credentials, can be repeated (default is the domain of URL)"),
+    )
+    .arg(
+        Arg::new("proxy")
+            .long("proxy")
+            .value_name("URL")
+            .help("HTTP, HTTPS or SOCKS5 proxy (default is taken from HTTP_PROXY/HTTPS_PROXY)"),
+    )
+    .arg(
+        Arg::new("no-proxy")
+            .long("no-proxy")
+            .value_name("HOSTS")
+            .requires("proxy")
+            .help("Comma separated hosts reached without the proxy, added to NO_PROXY"),
+    )
+    .arg(
+        Arg::new("ca-cert")
+            .long("ca-cert")
+            .value_name("FILE")
+            .help("Trust the CA certificates of this PEM bundle"),
+    )
+    .arg(
+        Arg::new("client-cert")
+            .long("client-cert")
+            .value_name("FILE")
+            .help("PEM client certificate for mutual TLS"),
+    )
+    .arg(
+        Arg::new("client-key")
+            .long("client-key")
+            .value_name("FILE")
+            .requires("client-cert")
+            .help("PKCS#8 PEM key of the client certificate"),
+    )
+    .arg(
+        Arg::new("header")
+            .long("header")
+            .value_name("NAME: VALUE")
+            .action(ArgAction::Append)
+            .value_parser(parse_header)
+            .help("Header sent with every request, can be repeated"),
+    )
+    .arg(
+        Arg::new("delay")
+            .long("delay")
+            .value_name("DURATION")
+            .value_parser(parse_duration)
+            .help("Minimum delay between two requests (2s, 1m)"),
+    )
+    .arg(
+        Arg::new("max-redirects")
+            .long("max-redirects")
+            .value_name("N")
+            .value_parser(value_parser!(usize))
+            .help("Maximum number of redirects followed for one request (default is 10)"),
+    )
+    .arg(
+        Arg::new("insecure")
+            .long("insecure")
+            .action(ArgAction::SetTrue)
+            .help("DANGEROUS: do not verify TLS certificates"),
+    )
+    .arg(
+        Arg::new("offline")
+            .long("offline")
+            .value_name("DIR|FILE.warc[.gz]")
+            .help("Crawl from a directory mirror or a WARC file instead of the network"),
+    )
+    .arg(
+        Arg::new("inline-svg")
+            .long("inline-svg")
+            .action(ArgAction::SetTrue)
+            .help("Also save the inline <svg> elements of the pages as .svg files"),
+    )
+    .arg(
+        Arg::new("manifest")
+            .long("manifest")
+            .action(ArgAction::SetTrue)
+            .help("Write a manifest.json listing every image with its page, alt text, title, size and caption (always written with --archive)"),
+    )
+    .arg(
+        Arg::new("dry-run")
+            .long("dry-run")
+            .action(ArgAction::SetTrue)
+            .conflicts_with_all(["archive", "s3"])
+            .help("Crawl the pages and print the images that would be downloaded, without downloading them"),
+    )
+    .arg(
+        Arg::new("export")
+            .long("export")
+            .value_name("FILE.json")
+            .requires("dry-run")
+            .help("Write the images found by --dry-run with their pages to a JSON file"),
+    )
+    .arg(
+        Arg::new("list")
+            .long("list")
+            .action(ArgAction::SetTrue)
+            .conflicts_with_all(["dry-run", "recursive", "level", "archive", "s3", "warc"])
+            .help("Print the links of the page sorted by kind, without crawling or downloading"),
+    )
+    .arg(
+        Arg::new("verbose")
+            .short('v')
+            .long("verbose")
+            .action(ArgAction::Count)
+            .help("Log every page and image (-v), and the details (-vv)"),
+    )
+    .arg(
+        Arg::new("quiet")
+            .short('q')
+            .long("quiet")
+            .action(ArgAction::SetTrue)
+            .conflicts_with("verbose")
+            .help("Only log the errors, without progress bar"),
+    )
+    .arg(
+        Arg::new("log-format")
+            .long("log-format")
+            .value_name("FORMAT")
+            .value_parser(["text", "json"])
+            .default_value("text")
+            .help("Format of the logs written to stderr, json writes one object per line"),
+    )
+    .arg(
+        Arg::new("warc")
+            .long("warc")
+            .value_name("FILE.warc.gz")
+            .conflicts_with("offline")
+            .help("Record every request and response in a WARC/1.1 file"),
+    ))
+}
+
+/// Options of the command line that can not be set in a config file.
+const NOT_IN_CONFIG: [&str; 5] = ["config", "profile", "print-config", "help", "version"];
+
+/// Headers whose value is masked by `--print-config`.
+const SECRET_HEADERS: [&str; 3] = ["authorization", "proxy-authorization", "cookie"];
+
+/// Function that parses the command line. With `--config`, the settings of the
+/// file (and of the `--profile`) are used for the options not given on the
+/// command line, or conflicting with one given on the command line.
+pub fn get_matches() -> ArgMatches {
+    let args = std::env::args_os().collect::<Vec<OsString>>();
+    let first_pass = command()
+        .mut_arg("URL", |arg| arg.required(false))
+        .try_get_matches_from(&args);
+    let config_path = match &first_pass {
+        Ok(matches) => matches.get_one::<String>("config"),
+        Err(_) => None,
+    };
+    let config_path = match config_path {
+        Some(config_path) => config_path,
+        // Without config file, or with errors that clap reports itself.
+        None => return command().get_matches_from(args),
+    };
+    let first_pass = first_pass.as_ref().unwrap();
+    let settings = ConfigFile::load(config_path).and_then(|config| {
+        config.settings(first_pass.get_one::<String>("profile").map(String::as_str))
+    });
+    let config_args = settings.and_then(|settings| config_args(first_pass, &settings));
+    let config_args = match config_args {
+        Ok(config_args) => config_args,
+        Err(err) => command().error(ErrorKind::InvalidValue, err).exit(),
+    };
+    let mut merged = args[..1].to_vec();
+    merged.extend(config_args.into_iter().map(OsString::from));
+    merged.extend(args[1..].iter().cloned());
+    command().get_matches_from(merged)
+}
+
+/// Function that turns the settings of a config file into command line arguments,
+/// leaving out the ones overridden on the command line.
+fn config_args(command_line: &ArgMatches, settings: &[Setting]) -> Result<Vec<String>, String> {
+    let command = command();
+    let given = command
+        .get_arguments()
+        .filter(|arg| {
+            command_line.value_source(arg.get_id().as_str()) == Some(ValueSource::CommandLine)
+        })
+        .collect::<Vec<&Arg>>();
+    let mut args = Vec::new();
+    for (key, values) in settings {
+        let arg = command.get_arguments().find(|arg| match key.as_str() {
+            "url" => arg.get_id() == "URL",
+            _ => arg.get_long() == Some(key.as_str()),
+        });
+        let arg = match arg {
+            Some(arg) if !NOT_IN_CONFIG.contains(&key.as_str()) => arg,
+            _ => return Err(format!("unknown setting in the config file: {key}")),
+        };
+        let overridden = given.iter().any(|given| {
+            given.get_id() == arg.get_id()
+                || command.get_arg_conflicts_with(arg).contains(given)
+                || command.get_arg_conflicts_with(given).contains(&arg)
+        });
+        if overridden {
+            continue;
+        }
+        let invalid = || {
+            format!(
+                "invalid value for {key} in the config file: {}",
+                values.join(", ")
+            )
+        };
+        match arg.get_action() {
+            _ if arg.get_id() == "URL" => args.extend(values.iter().cloned()),
+            ArgAction::SetTrue => match values.as_slice() {
+                [value] if value == "true" => args.push(format!("--{key}")),
+                [value] if value == "false" => {}
+                _ => return Err(invalid()),
+            },
+            ArgAction::Count => {
+                let count = match values.as_slice() {
+                    [value] => value.parse::<u8>().map_err(|_| invalid())?,
+                    _ => return Err(invalid()),
+                };
+                args.extend((0..count).map(|_| format!("--{key}")));
+            }
+            ArgAction::Append => args.extend(values.iter().map(|value| format!("--{key}={value}"))),
+            _ => match values.as_slice() {
+                [value] => args.push(format!("--{key}={value}")),
+                _ => return Err(invalid()),
+            },
+        }
+    }
+    Ok(args)
+}
+
+/// Function that prints the effective settings, from the command line,
+/// the config file and the defaults, as a config file. Secrets are masked.
+pub fn print_config(matches: &ArgMatches) {
+    let mut table = Table::new();
+    for arg in command().get_arguments() {
+        let id = arg.get_id().as_str();
+        let key = match (id, arg.get_long()) {
+            ("URL", _) => "url",
+            (_, Some(long)) if !NOT_IN_CONFIG.contains(&long) => long,
+            _ => continue,
+        };
+        let value = match arg.get_action() {
+            ArgAction::SetTrue => Value::from(matches.get_flag(id)),
+            ArgAction::Count => Value::from(matches.get_count(id) as i64),
+            action => {
+                let values = match matches.get_raw(id) {
+                    Some(values) => values
+                        .map(|value| mask(key, &value.to_string_lossy()))
+                        .map(|value| match value.parse::<i64>() {
+                            Ok(number) => Value::from(number),
+                            Err(_) => Value::from(value),
+                        })
+                        .collect::<Vec<Value>>(),
+                    None => continue,
+                };
+                match action {
+                    ArgAction::Append => Value::from(values),
+                    _ => values.into_iter().next().unwrap(),
+                }
+            }
+        };
+        table.insert(key.to_string(), value);
+    }
+    // A depth makes the crawl recursive.
+    if table.contains_key("level") {
+        table.insert("recursive".to_string(), Value::from(true));
+    }
+    print!("{}", toml::to_string(&table).unwrap());
+}
+
+/// Function that hides the credentials of a setting: tokens, passwords,
+/// the values of the headers carrying credentials and the user of a proxy URL.
+fn mask(key: &str, value: &str) -> String {
+    match key {
+        "bearer" => "********".to_string(),
+        "basic-auth" | "login-field" => match value.split_once([':', '=']) {
+            Some((name, _)) => format!("{name}{}********", &value[name.len()..name.len() + 1]),
+            None => "********".to_string(),
+        },
+        "header" => match value.split_once(':') {
+            Some((name, _)) if SECRET_HEADERS.contains(&name.trim().to_lowercase().as_str()) => {
+                format!("{name}: ********")
+            }
+            _ => value.to_string(),
+        },
+        "proxy" => match value.split_once("://") {
+            Some((scheme, rest)) => {
+                let authority = &rest[..rest.find('/').unwrap_or(rest.len())];
+                match authority.rfind('@') {
+                    Some(at) => format!("{scheme}://********@{}", &rest[at + 1..]),
+                    None => value.to_string(),
+                }
+            }
+            None => value.to_string(),
+        },
+        _ => value.to_string(),
+    }
+}
+
+/// Function that checks that the URL is an absolute HTTP or HTTPS URL.
+fn parse_url(url: &str) -> Result<String, String> {
+    if !domain::is_valid(url) {
+        return Err(format!(
+            "expected an http:// or https:// URL such as https://example.com/, got '{url}'"
+        ));
+    }
+    Ok(url.to_string())
+}
+
+/// Function that parses a number that must be at least 1.
+fn parse_positive(number: &str) -> Result<usize, String> {
+    match number.parse::<usize>() {
+        Ok(number) if number > 0 => Ok(number),
+        _ => Err(format!("expected a number of at least 1, got '{number}'")),
+    }
+}
+
+#[cfg(feature = "render")]
+fn with_render_args(command: Command) -> Command {
+    command
+        .arg(
+            Arg::new("render")
+                .long("render")
+                .action(ArgAction::SetTrue)
+                .help("Render the pages in a headless Chromium before extracting the links"),
+        )
+        .arg(
+            Arg::new("browser")
+                .long("browser")
+                .value_name("PATH")
+                .requires("render")
+                .help("Chromium or Chrome executable (default is $SPIDER_BROWSER, then chromium)"),
+        )
+}
+
+#[cfg(not(feature = "render"))]
+fn with_render_args(command: Command) -> Command {
+    command
+}
+
+/// Function that turns the parsed command line into a crawler builder.
+pub fn builder_from_matches(matches: &ArgMatches) -> Result<CrawlerBuilder, String> {
+    let url: &String = matches.get_one::<String>("URL").unwrap();
+    let level = matches.get_one::<u16>("level").copied();
+    // Giving a depth only makes sense for a recursive crawl.
+    let recursive = matches.get_flag("recursive") || level.is_some();
+    let path: &String = matches.get_one::<String>("path").unwrap();
+    let concurrency: &usize = matches.get_one::<usize>("concurrency").unwrap();
+    let strip_params = parse_strip_params(matches.get_one::<String>("strip-params").unwrap());
+
+    let client_options = ClientOptions {
+        cookies_file: matches.get_one::<String>("cookies").cloned(),
+        login_url: matches.get_one::<String>("login-url").cloned(),
+        login_form: matches
+            .get_many::<(String, String)>("login-field")
+            .unwrap_or_default()
+            .cloned()
+            .collect(),
+        auth: matches.get_one::<Auth>("basic-auth").cloned().or_else(|| {
+            matches
+                .get_one::<String>("bearer")
+                .cloned()
+                .map(Auth::Bearer)
+        }),
+        auth_hosts: matches
+            .get_many::<String>("auth-host")
+            .unwrap_or_default()
+            .cloned()
+            .collect(),
+        proxy: matches.get_one::<String>("proxy").cloned(),
+        no_proxy: matches.get_one::<String>("no-proxy").cloned(),
+        tls: TlsOptions {
+            ca_cert: matches.get_one::<String>("ca-cert").cloned(),
+            client_cert: matches.get_one::<String>("client-cert").cloned(),
+            client_key: matches.get_one::<String>("client-key").cloned(),
+            insecure: matches.get_flag("insecure"),
+        },
+        max_redirects: matches.get_one::<usize>("max-redirects").copied(),
+        offline: matches.get_one::<String>("offline").cloned(),
+        warc: matches.get_one::<String>("warc").cloned(),
+        headers: matches
+            .get_many::<(String, String)>("header")
+            .unwrap_or_default()
+            .cloned()
+            .collect(),
+        delay: matches.get_one::<Duration>("delay").copied(),
+    };
+
+    let builder = Crawler::builder(url)
+        .recursive(recursive)
+        .depth(if recursive { level.unwrap_or(5) } else { 0 })
+        .concurrency(*concurrency)
+        .strip_params(strip_params)
+        .inline_svg(matches.get_flag("inline-svg"))
+        .manifest(matches.get_flag("manifest"))
+        .dry_run(matches.get_flag("dry-run"))
+        .client_options(client_options.clone());
+    let scope = matches
+        .get_many::<String>("scope")
+        .unwrap_or_default()
+        .map(|domain| domain.as_str())
+        .collect::<Vec<&str>>();
+    let builder = match scope.is_empty() {
+        true => builder,
+        false => builder.scope(&scope),
+    };
+    let builder = match matches.get_one::<usize>("max-pages") {
+        Some(max_pages) => builder.max_pages(*max_pages),
+        None => builder,
+    };
+    let builder = match matches.get_one::<usize>("max-images") {
+        Some(max_images) => builder.max_images(*max_images),
+        None => builder,
+    };
+    let builder = match matches.get_one::<u64>("max-bytes") {
+        Some(max_bytes) => builder.max_bytes(*max_bytes),
+        None => builder,
+    };
+    let builder = match matches.get_one::<Duration>("max-time") {
+        Some(max_time) => builder.max_time(*max_time),
+        None => builder,
+    };
+    let builder = match (
+        matches.get_one::<String>("archive"),
+        matches.get_one::<String>("s3"),
+    ) {
+        (Some(archive), _) => {
+            let storage = ArchiveStorage::new(archive).map_err(|err| err.to_string())?;
+            builder.storage(storage).manifest(true)
+        }
+        (None, Some(target)) => {
+            let config = S3Config::from_env(
+                matches.get_one::<String>("s3-endpoint").unwrap(),
+                target,
+                matches
+                    .get_one::<String>("s3-region")
+                    .map(|region| region.as_str()),
+            )?;
+            let storage = S3Storage::with_options(config, &client_options)
+                .map_err(|_| "could not build the S3 client".to_string())?;
+            builder.storage(storage)
+        }
+        (None, None) => {
+            let existing = match (matches.get_flag("overwrite"), matches.get_flag("rename")) {
+                (true, _) => Existing::Overwrite,
+                (_, true) => Existing::Rename,
+                _ => Existing::Skip,
+            };
+            builder.storage(FsStorage::new(path).existing(existing))
+        }
+    };
+    #[cfg(feature = "render")]
+    let builder = match matches.get_flag("render") {
+        true => {
+            let mut options = RenderOptions::default();
+            if let Some(browser) = matches.get_one::<String>("browser") {
+                options.browser = browser.clone();
+            }
+            builder.render(options)
+        }
+        false => builder,
+    };
+    Ok(builder)
+}
+
+/// Function that prints the images found by a dry run with the page they come from.
+pub fn print_found_images(images: &[ImageFound]) {
+    for image in images {
+        println!("{}\t{}", image.url, image.page_url);
+    }
+}
+
+/// Function that writes the images found by a dry run to a JSON file.
+pub fn export_found_images(path: &str, images: &[ImageFound]) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(images).map_err(|err| err.to_string())?;
+    std::fs::write(path, json).map_err(|err| format!("cannot write to {path}: {err}"))
+}
+
+/// Function that prints the links of a page sorted by kind, for `--list`.
+pub fn print_page_links(links: &PageLinks) {
+    println!("🔗 Links of {}", links.url);
+    if let Some(canonical) = &links.canonical {
+        println!("canonical: {canonical}");
+    }
+    let groups = [
+        ("pages", &links.pages),
+        ("unknown (checked with HEAD)", &links.unknown),
+        ("images", &links.images),
+        ("stylesheets", &links.stylesheets),
+        ("assets", &links.assets),
+        ("ignored", &links.ignored),
+    ];
+    for (name, group) in groups {
+        if group.is_empty() {
+            continue;
+        }
+        println!("{name} ({}):", group.len());
+        for link in group {
+            println!("  {link}");
+        }
+    }
+    if links.inline_images > 0 {
+        println!("inline images: {}", links.inline_images);
+    }
+}
+
+/// Function that sets up the logs on stderr, and the progress bar
+/// when the logs are read by a person.
+pub fn init_logging(matches: &ArgMatches) {
+    let level = Level::from_verbosity(matches.get_count("verbose"), matches.get_flag("quiet"));
+    let format = match matches
+        .get_one::<String>("log-format")
+        .map(|format| format.as_str())
+    {
+        Some("json") => Format::Json,
+        _ => Format::Text,
+    };
+    logging::init(level, format);
+    if format == Format::Text && level != Level::Error && !matches.get_flag("list") {
+        progress::start();
+    }
+}
@@ -0,0 +1,111 @@
+use std::collections::BTreeMap;
+use toml::{Table, Value};
+
+mod tests_config;
+
+/// One setting of a config file: the long name of a command line option
+/// and its values, as they would be written on the command line.
+/// A flag is `"true"` or `"false"`, an array gives one value per item.
+pub type Setting = (String, Vec<String>);
+
+/// Settings read from a TOML config file.
+/// The top-level keys apply to every crawl, the `[profiles.<name>]` tables
+/// hold the settings of one kind of crawl and override the top-level ones.
+#[derive(Debug, Default, PartialEq)]
+pub struct ConfigFile {
+    pub path: String,
+    pub defaults: Vec<Setting>,
+    pub profiles: BTreeMap<String, Vec<Setting>>,
+}
+
+impl ConfigFile {
+    /// Function that reads and parses a config file.
+    pub fn load(path: &str) -> Result<ConfigFile, String> {
+        let text =
+            std::fs::read_to_string(path).map_err(|err| format!("cannot read {path}: {err}"))?;
+        ConfigFile::parse(path, &text)
+    }
+
+    pub fn parse(path: &str, text: &str) -> Result<ConfigFile, String> {
+        let mut table = text
+            .parse::<Table>()
+            .map_err(|err| format!("invalid config file {path}: {}", err.message()))?;
+        let mut config = ConfigFile {
+            path: path.to_string(),
+            ..ConfigFile::default()
+        };
+        if let Some(profiles) = table.remove("profiles") {
+            let profiles = match profiles {
+                Value::Table(profiles) => profiles,
+                _ => return Err(format!("{path}: profiles must be tables")),
+            };
+            for (name, profile) in profiles {
+                match profile {
+                    Value::Table(profile) => {
+                        let settings = settings(path, &profile)?;
+                        config.profiles.insert(name, settings);
+                    }
+                    _ => return Err(format!("{path}: profile {name} must be a table")),
+                }
+            }
+        }
+        config.defaults = settings(path, &table)?;
+        Ok(config)
+    }
+
+    /// Function that returns the settings of a profile, on top of the top-level ones.
+    pub fn settings(&self, profile: Option<&str>) -> Result<Vec<Setting>, String> {
+        let mut settings = self.defaults.clone();
+        let profile = match profile {
+            Some(profile) => profile,
+            None => return Ok(settings),
+        };
+        let profile_settings = self.profiles.get(profile).ok_or_else(|| {
+            let names = self.profiles.keys().cloned().collect::<Vec<String>>();
+            format!(
+                "no profile {profile} in {} (profiles: {})",
+                self.path,
+                names.join(", ")
+            )
+        })?;
+        for (key, values) in profile_settings {
+            settings.retain(|(existing, _)| existing != key);
+            settings.push((key.clone(), values.clone()));
+        }
+        Ok(settings)
+    }
+}
+
+/// Function that turns the keys of a table into settings.
+/// Nested tables are not settings, they are refused.
+fn settings(path: &str, table: &Table) -> Result<Vec<Setting>, String> {
+    let mut settings = Vec::new();
+    for (key, value) in table {
+        let values = match value {
+            Value::Array(items) => items
+                .iter()
+                .map(|item| scalar(item).ok_or(()))
+                .collect::<Result<Vec<String>, ()>>(),
+            _ => scalar(value).map(|value| vec![value]).ok_or(()),
+        };
+        match values {
+            Ok(values) => settings.push((key.clone(), values)),
+            Err(_) => {
+                return Err(format!(
+                    "{path}: unsupported value for {key}, expected a string, a number, a boolean or an array of them"
+                ))
+            }
+        }
+    }
+    Ok(settings)
+}
+
+fn scalar(value: &Value) -> Option<String> {
+    match value {
+        Value::String(value) => Some(value.clone()),
+        Value::Integer(value) => Some(value.to_string()),
+        Value::Float(value) => Some(value.to_string()),
+        Value::Boolean(value) => Some(value.to_string()),
+        _ => None,
+    }
+}
@@ -0,0 +1,83 @@
+#[cfg(test)]
+use super::ConfigFile;
+
+#[cfg(test)]
+const CONFIG: &str = r#"
+concurrency = 4
+strip-params = "utm_*,fbclid"
+header = ["User-Agent: spider"]
+
+[profiles.books]
+url = "https://books.toscrape.com/"
+level = 2
+scope = ["books.toscrape.com", "images.toscrape.com"]
+concurrency = 8
+
+[profiles.polite]
+delay = "2s"
+recursive = true
+"#;
+
+#[cfg(test)]
+fn setting(key: &str, values: &[&str]) -> (String, Vec<String>) {
+    (
+        key.to_string(),
+        values.iter().map(|value| value.to_string()).collect(),
+    )
+}
+
+#[test]
+fn test_parse() {
+    let config = ConfigFile::parse("spider.toml", CONFIG).unwrap();
+    assert_eq!(
+        config.defaults,
+        vec![
+            setting("concurrency", &["4"]),
+            setting("strip-params", &["utm_*,fbclid"]),
+            setting("header", &["User-Agent: spider"]),
+        ]
+    );
+    assert_eq!(
+        config.profiles.keys().collect::<Vec<&String>>(),
+        vec!["books", "polite"]
+    );
+}
+
+#[test]
+fn test_profile_overrides_defaults() {
+    let config = ConfigFile::parse("spider.toml", CONFIG).unwrap();
+    let settings = config.settings(Some("books")).unwrap();
+    assert_eq!(
+        settings,
+        vec![
+            setting("strip-params", &["utm_*,fbclid"]),
+            setting("header", &["User-Agent: spider"]),
+            setting("url", &["https://books.toscrape.com/"]),
+            setting("level", &["2"]),
+            setting("scope", &["books.toscrape.com", "images.toscrape.com"]),
+            setting("concurrency", &["8"]),
+        ]
+    );
+    let settings = config.settings(Some("polite")).unwrap();
+    assert!(settings.contains(&setting("recursive", &["true"])));
+    assert!(settings.contains(&setting("concurrency", &["4"])));
+    assert_eq!(config.settings(None).unwrap(), config.defaults);
+}
+
+#[test]
+fn test_unknown_profile() {
+    let config = ConfigFile::parse("spider.toml", CONFIG).unwrap();
+    assert_eq!(
+        config.settings(Some("music")),
+        Err("no profile music in spider.toml (profiles: books, polite)".to_string())
+    );
+}
+
+#[test]
+fn test_invalid_files() {
+    assert!(ConfigFile::parse("spider.toml", "level = ").is_err());
+    assert!(ConfigFile::parse("spider.toml", "[output]\npath = \"data\"").is_err());
+    assert!(ConfigFile::parse("spider.toml", "profiles = 1").is_err());
+    assert!(ConfigFile::parse("spider.toml", "header = [[\"a\"]]").is_err());
+    assert!(ConfigFile::load("/nonexistent/spider.toml").is_err());
+}
@@ -0,0 +1,209 @@
+use std::fmt;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::logging;
+
+/// A budget of the crawl that can run out.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Limit {
+    Pages,
+    Images,
+    Bytes,
+    Time,
+}
+
+impl fmt::Display for Limit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Limit::Pages => write!(f, "page limit"),
+            Limit::Images => write!(f, "image limit"),
+            Limit::Bytes => write!(f, "download size limit"),
+            Limit::Time => write!(f, "time limit"),
+        }
+    }
+}
+
+/// Budgets of a crawl, unlimited when `None`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Limits {
+    /// Pages fetched, stylesheets excluded.
+    pub max_pages: Option<usize>,
+    /// Images saved.
+    pub max_images: Option<usize>,
+    /// Bytes downloaded, pages and images included.
+    pub max_bytes: Option<u64>,
+    pub max_time: Option<Duration>,
+}
+
+/// What the crawl has used of its limits.
+/// Once one of them runs out it stays exhausted, and the crawl stops
+/// fetching and downloading. Requests already sent are finished,
+/// but no body is read past the download size limit.
+pub struct Budget {
+    pub limits: Limits,
+    start: Instant,
+    pages: AtomicUsize,
+    images: AtomicUsize,
+    bytes: AtomicU64,
+    exhausted: Mutex<Option<Limit>>,
+}
+
+impl Budget {
+    pub fn new(limits: Limits) -> Budget {
+        Budget {
+            limits,
+            start: Instant::now(),
+            pages: AtomicUsize::new(0),
+            images: AtomicUsize::new(0),
+            bytes: AtomicU64::new(0),
+            exhausted: Mutex::new(None),
+        }
+    }
+
+    /// Function that returns the limit that ran out, if any.
+    pub fn exhausted(&self) -> Option<Limit> {
+        if let Some(max_time) = self.limits.max_time {
+            if self.start.elapsed() >= max_time {
+                self.stop(Limit::Time);
+            }
+        }
+        *self.exhausted.lock().unwrap()
+    }
+
+    /// Function that checks if one more page may be fetched.
+    pub fn can_fetch_page(&self) -> bool {
+        if let Some(max_pages) = self.limits.max_pages {
+            if self.pages.load(Ordering::SeqCst) >= max_pages {
+                self.stop(Limit::Pages);
+            }
+        }
+        self.exhausted().is_none()
+    }
+
+    pub fn add_page(&self) {
+        self.pages.fetch_add(1, Ordering::SeqCst);
+    }
+
+    /// Function that returns how many bytes may still be downloaded, `None` without limit.
+    pub fn remaining_bytes(&self) -> Option<u64> {
+        self.limits
+            .max_bytes
+            .map(|max_bytes| max_bytes.saturating_sub(self.bytes.load(Ordering::SeqCst)))
+    }
+
+    /// Function that counts downloaded bytes if they fit in what is left of the limit.
+    /// The budget runs out once the bytes reach the limit. Bytes that do not fit
+    /// are not counted, the budget runs out and it returns false.
+    pub fn take_bytes(&self, bytes: usize) -> bool {
+        let bytes = bytes as u64;
+        let taken = self
+            .bytes
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |total| {
+                match self.limits.max_bytes {
+                    Some(max_bytes) if total + bytes > max_bytes => None,
+                    _ => Some(total + bytes),
+                }
+            });
+        match taken {
+            Ok(total) => {
+                if self
+                    .limits
+                    .max_bytes
+                    .is_some_and(|max_bytes| total + bytes >= max_bytes)
+                {
+                    self.stop(Limit::Bytes);
+                }
+                true
+            }
+            Err(_) => {
+                self.stop(Limit::Bytes);
+                false
+            }
+        }
+    }
+
+    /// Function that reserves the right to save one image, before it is downloaded.
+    /// It returns false if the image budget is already spent.
+    pub fn take_image(&self) -> bool {
+        let reserved = self
+            .images
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |images| {
+                match self.limits.max_images {
+                    Some(max_images) if images >= max_images => None,
+                    _ => Some(images + 1),
+                }
+            });
+        if reserved.is_err() {
+            self.stop(Limit::Images);
+            return false;
+        }
+        true
+    }
+
+    /// Function that gives back an image reserved with `take_image` that was not saved.
+    pub fn release_image(&self) {
+        self.images.fetch_sub(1, Ordering::SeqCst);
+    }
+
+    fn stop(&self, limit: Limit) {
+        let mut exhausted = self.exhausted.lock().unwrap();
+        if exhausted.is_none() {
+            logging::warn(
+                &format!("⏹️ The {limit} is reached, stopping the crawl"),
+                &[("limit", &format!("{limit:?}").to_lowercase())],
+            );
+            *exhausted = Some(limit);
+        }
+    }
+}
+
+/// Function that parses a size in bytes, with an optional `K`, `M` or `G` suffix
+/// (powers of 1024), as in `500M`.
+pub fn parse_size(size: &str) -> Result<u64, String> {
+    let size = size.trim();
+    let upper = size.to_uppercase();
+    let upper = upper.strip_suffix('B').unwrap_or(&upper);
+    let (number, multiplier) = match upper.chars().last() {
+        Some('K') => (&upper[..upper.len() - 1], 1 << 10),
+        Some('M') => (&upper[..upper.len() - 1], 1 << 20),
+        Some('G') => (&upper[..upper.len() - 1], 1 << 30),
+        _ => (upper, 1),
+    };
+    match number.trim().parse::<u64>() {
+        Ok(number) => number
+            .checked_mul(multiplier)
+            .ok_or(format!("size too large: '{size}'")),
+        Err(_) => Err(format!(
+            "expected a size like 500K, 20M or 1G, got '{size}'"
+        )),
+    }
+}
+
+/// Function that parses a duration in seconds, with an optional
+/// `ms`, `s`, `m` or `h` suffix, as in `500ms`, `1.5s` or `2h`.
+pub fn parse_duration(duration: &str) -> Result<Duration, String> {
+    let duration = duration.trim();
+    let (number, multiplier) = match duration.strip_suffix("ms") {
+        Some(number) => (number, 0.001),
+        None => match duration.chars().last() {
+            Some('s') => (&duration[..duration.len() - 1], 1.0),
+            Some('m') => (&duration[..duration.len() - 1], 60.0),
+            Some('h') => (&duration[..duration.len() - 1], 3600.0),
+            _ => (duration, 1.0),
+        },
+    };
+    let seconds = number
+        .trim()
+        .parse::<f64>()
+        .map(|number| number * multiplier);
+    match seconds {
+        Ok(seconds) if seconds.is_finite() && seconds >= 0.0 => {
+            Ok(Duration::from_secs_f64(seconds))
+        }
+        _ => Err(format!(
+            "expected a duration like 500ms, 90s, 10m or 2h, got '{duration}'"
+        )),
+    }
+}
@@ -0,0 +1,63 @@
+use serde::Serialize;
+use std::sync::Arc;
+
+use crate::parsing::metadata::ImageMetadata;
+
+/// A page has been fetched and its links extracted.
+/// A page already crawled under another URL (redirect, canonical) is not reported again.
+#[derive(Clone, Debug)]
+pub struct PageFetched {
+    pub url: String,
+    pub final_url: String,
+    pub level: u16,
+    pub links: usize,
+    pub images: usize,
+}
+
+/// A new image link has been found on a page.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ImageFound {
+    pub url: String,
+    pub page_url: String,
+    pub metadata: ImageMetadata,
+}
+
+/// An image has been downloaded and written to the output.
+#[derive(Clone, Debug)]
+pub struct ImageSaved {
+    pub url: String,
+    pub page_url: String,
+    pub path: String,
+    pub bytes: usize,
+}
+
+pub type Callback<T> = Arc<dyn Fn(&T) + Send + Sync>;
+
+/// Callbacks registered on a crawler, called in registration order.
+/// Image callbacks may be called from the download threads.
+#[derive(Clone, Default)]
+pub struct Events {
+    pub page_fetched: Vec<Callback<PageFetched>>,
+    pub image_found: Vec<Callback<ImageFound>>,
+    pub image_saved: Vec<Callback<ImageSaved>>,
+}
+
+impl Events {
+    pub fn emit_page_fetched(&self, event: &PageFetched) {
+        for callback in &self.page_fetched {
+            callback(event);
+        }
+    }
+
+    pub fn emit_image_found(&self, event: &ImageFound) {
+        for callback in &self.image_found {
+            callback(event);
+        }
+    }
+
+    pub fn emit_image_saved(&self, event: &ImageSaved) {
+        for callback in &self.image_saved {
+            callback(event);
+        }
+    }
+}
@@ -0,0 +1,39 @@
+use serde::Serialize;
+
+use crate::parsing::metadata::ImageMetadata;
+
+/// An image written to the storage.
+/// `name` is the name given to the storage and `location` where it ended up.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct SavedImage {
+    pub url: String,
+    pub page_url: String,
+    pub name: String,
+    pub location: String,
+    pub content_type: String,
+    pub bytes: usize,
+    pub metadata: ImageMetadata,
+}
+
+/// List of the images of a crawl with the pages they come from,
+/// written as `manifest.json` next to the images.
+#[derive(Debug, Serialize)]
+pub struct Manifest<'a> {
+    pub seed: &'a str,
+    pub created: String,
+    pub images: &'a [SavedImage],
+}
+
+impl<'a> Manifest<'a> {
+    pub fn new(seed: &'a str, images: &'a [SavedImage]) -> Manifest<'a> {
+        Manifest {
+            seed,
+            created: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+            images,
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+}
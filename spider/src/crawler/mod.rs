@@ -0,0 +1,485 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::http::{ClientOptions, FetchError, SClient};
+use crate::logging::{self, progress};
+use crate::parsing::links::{self, PageLinks};
+use crate::parsing::normalize::{parse_strip_params, DEFAULT_STRIP_PARAMS};
+use crate::parsing::{css, domain};
+#[cfg(feature = "render")]
+use crate::render::{RenderOptions, Renderer};
+use crate::spider::SRequest;
+use crate::storage::{FsStorage, Storage};
+use crate::utils::{get_request_url, is_css, is_html_response};
+
+pub mod budget;
+pub mod events;
+pub mod manifest;
+pub mod stats;
+
+mod tests_budget;
+mod tests_crawler;
+mod tests_manifest;
+mod tests_stats;
+
+pub use budget::{Budget, Limit, Limits};
+pub use events::{Callback, Events, ImageFound, ImageSaved, PageFetched};
+pub use manifest::{Manifest, SavedImage};
+pub use stats::{CrawlStats, Stats};
+
+/// Name of the manifest written with the images.
+pub const MANIFEST_NAME: &str = "manifest.json";
+
+/// Settings of a crawl, shared by every request once the crawler is built.
+pub struct CrawlConfig {
+    pub seed: String,
+    pub recursive: bool,
+    pub max_level: u16,
+    pub scope: Vec<String>,
+    pub storage: Box<dyn Storage>,
+    pub concurrency: usize,
+    pub strip_params: Vec<String>,
+    pub client_options: ClientOptions,
+    pub client: SClient,
+    pub events: Events,
+    pub manifest: bool,
+    /// Export the inline `<svg>` elements of the pages as `.svg` files.
+    pub inline_svg: bool,
+    /// Crawl the pages without downloading or writing anything.
+    pub dry_run: bool,
+    pub found_images: Mutex<Vec<ImageFound>>,
+    pub saved_images: Mutex<Vec<SavedImage>>,
+    pub budget: Budget,
+    pub stats: Stats,
+    #[cfg(feature = "render")]
+    pub render: Option<RenderOptions>,
+    /// Headless browser, started by `Crawler::run` when `render` is set.
+    #[cfg(feature = "render")]
+    pub renderer: Option<Renderer>,
+}
+
+impl CrawlConfig {
+    /// Function that counts downloaded bytes against the budget, in the stats and the progress.
+    /// It returns false, without counting them, if they do not fit in the budget.
+    pub fn take_bytes(&self, bytes: usize) -> bool {
+        if !self.budget.take_bytes(bytes) {
+            return false;
+        }
+        self.stats.add_bytes(bytes);
+        progress::add_bytes(bytes);
+        true
+    }
+}
+
+/// Reason why a crawl could not run.
+#[derive(Debug, PartialEq)]
+pub enum CrawlError {
+    InvalidSeed(String),
+    InvalidConfig(String),
+    Output(String, String),
+    Client,
+    Login(String),
+    Seed(String, FetchError),
+    #[cfg(feature = "render")]
+    Render(String),
+}
+
+impl fmt::Display for CrawlError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CrawlError::InvalidSeed(url) => write!(f, "invalid URL: {url}"),
+            CrawlError::InvalidConfig(message) => write!(f, "invalid configuration: {message}"),
+            CrawlError::Output(destination, message) => {
+                write!(f, "cannot write to {destination}: {message}")
+            }
+            CrawlError::Client => write!(f, "could not build the HTTP client"),
+            CrawlError::Login(url) => write!(f, "login failed on {url}"),
+            CrawlError::Seed(url, FetchError::Tls(message)) => {
+                write!(f, "TLS error on {url}: {message}")
+            }
+            CrawlError::Seed(url, err) => write!(f, "cannot access URL: {url} ({err})"),
+            #[cfg(feature = "render")]
+            CrawlError::Render(message) => write!(f, "cannot render the pages: {message}"),
+        }
+    }
+}
+
+/// What a finished crawl found.
+pub struct CrawlReport {
+    pub seed: String,
+    pub visited_urls: HashSet<String>,
+    pub images: Vec<String>,
+    /// Every image link with the page it was first found on.
+    pub found_images: Vec<ImageFound>,
+    pub saved_images: Vec<SavedImage>,
+    /// The budget that stopped the crawl early, if one ran out.
+    pub budget_hit: Option<Limit>,
+    pub stats: CrawlStats,
+}
+
+/// Builder of a `Crawler`, created with `Crawler::builder`.
+pub struct CrawlerBuilder {
+    seed: String,
+    recursive: bool,
+    max_level: u16,
+    scope: Vec<String>,
+    storage: Box<dyn Storage>,
+    concurrency: usize,
+    strip_params: Vec<String>,
+    client_options: ClientOptions,
+    events: Events,
+    manifest: bool,
+    inline_svg: bool,
+    dry_run: bool,
+    limits: Limits,
+    #[cfg(feature = "render")]
+    render: Option<RenderOptions>,
+}
+
+impl CrawlerBuilder {
+    /// Enable or disable following the links of the pages.
+    pub fn recursive(mut self, recursive: bool) -> CrawlerBuilder {
+        self.recursive = recursive;
+        self
+    }
+
+    /// Maximum depth level for recursive download.
+    pub fn depth(mut self, max_level: u16) -> CrawlerBuilder {
+        self.max_level = max_level;
+        self
+    }
+
+    /// Domains the crawl may visit (default is the domain of the seed,
+    /// after its redirects).
+    pub fn scope(mut self, domains: &[&str]) -> CrawlerBuilder {
+        self.scope = domains.iter().map(|domain| domain.to_lowercase()).collect();
+        self
+    }
+
+    /// Directory the images are saved to, shortcut for a `FsStorage`.
+    pub fn output_dir(mut self, path: &str) -> CrawlerBuilder {
+        self.storage = Box::new(FsStorage::new(path));
+        self
+    }
+
+    /// Destination of the images (default is the `./data` directory).
+    pub fn storage(mut self, storage: impl Storage + 'static) -> CrawlerBuilder {
+        self.storage = Box::new(storage);
+        self
+    }
+
+    /// Number of images downloaded at the same time.
+    pub fn concurrency(mut self, concurrency: usize) -> CrawlerBuilder {
+        self.concurrency = concurrency;
+        self
+    }
+
+    /// Query parameters ignored when comparing URLs, see `normalize::normalize`.
+    pub fn strip_params(mut self, params: Vec<String>) -> CrawlerBuilder {
+        self.strip_params = params;
+        self
+    }
+
+    /// Cookies, credentials, proxy, TLS and redirect settings of the HTTP client.
+    pub fn client_options(mut self, options: ClientOptions) -> CrawlerBuilder {
+        self.client_options = options;
+        self
+    }
+
+    /// Write a `manifest.json` listing the source URL of every image to the storage.
+    pub fn manifest(mut self, manifest: bool) -> CrawlerBuilder {
+        self.manifest = manifest;
+        self
+    }
+
+    /// Save the inline `<svg>` elements of the pages as `.svg` files.
+    /// Images embedded as `data:` URIs are always saved.
+    pub fn inline_svg(mut self, inline_svg: bool) -> CrawlerBuilder {
+        self.inline_svg = inline_svg;
+        self
+    }
+
+    /// Crawl the pages and collect the image links without downloading the images.
+    /// Nothing is written to the storage.
+    pub fn dry_run(mut self, dry_run: bool) -> CrawlerBuilder {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Stop the crawl once this many pages have been fetched.
+    pub fn max_pages(mut self, max_pages: usize) -> CrawlerBuilder {
+        self.limits.max_pages = Some(max_pages);
+        self
+    }
+
+    /// Stop the crawl once this many images have been saved.
+    pub fn max_images(mut self, max_images: usize) -> CrawlerBuilder {
+        self.limits.max_images = Some(max_images);
+        self
+    }
+
+    /// Stop the crawl once this many bytes have been downloaded, pages included.
+    pub fn max_bytes(mut self, max_bytes: u64) -> CrawlerBuilder {
+        self.limits.max_bytes = Some(max_bytes);
+        self
+    }
+
+    /// Stop the crawl after this long.
+    pub fn max_time(mut self, max_time: Duration) -> CrawlerBuilder {
+        self.limits.max_time = Some(max_time);
+        self
+    }
+
+    /// Render every page in a headless Chromium before extracting its links,
+    /// for sites building their content with JavaScript.
+    #[cfg(feature = "render")]
+    pub fn render(mut self, options: RenderOptions) -> CrawlerBuilder {
+        self.render = Some(options);
+        self
+    }
+
+    pub fn on_page_fetched(
+        mut self,
+        callback: impl Fn(&PageFetched) + Send + Sync + 'static,
+    ) -> CrawlerBuilder {
+        self.events.page_fetched.push(Arc::new(callback));
+        self
+    }
+
+    pub fn on_image_found(
+        mut self,
+        callback: impl Fn(&ImageFound) + Send + Sync + 'static,
+    ) -> CrawlerBuilder {
+        self.events.image_found.push(Arc::new(callback));
+        self
+    }
+
+    pub fn on_image_saved(
+        mut self,
+        callback: impl Fn(&ImageSaved) + Send + Sync + 'static,
+    ) -> CrawlerBuilder {
+        self.events.image_saved.push(Arc::new(callback));
+        self
+    }
+
+    /// Function that validates the settings and builds the HTTP client.
+    pub fn build(self) -> Result<Crawler, CrawlError> {
+        if !domain::is_valid(&self.seed) {
+            return Err(CrawlError::InvalidSeed(self.seed));
+        }
+        if self.concurrency == 0 {
+            return Err(CrawlError::InvalidConfig(
+                "concurrency must be at least 1".to_string(),
+            ));
+        }
+        if self.limits.max_pages == Some(0) || self.limits.max_images == Some(0) {
+            return Err(CrawlError::InvalidConfig(
+                "the page and image limits must be at least 1".to_string(),
+            ));
+        }
+        let mut client_options = self.client_options;
+        // Credentials are only sent to the seed domain unless told otherwise.
+        if client_options.auth.is_some() && client_options.auth_hosts.is_empty() {
+            client_options.auth_hosts = vec![domain::get(&self.seed)];
+        }
+        let client = SClient::new(&client_options).map_err(|_| CrawlError::Client)?;
+        Ok(Crawler {
+            config: CrawlConfig {
+                seed: self.seed,
+                recursive: self.recursive,
+                max_level: self.max_level,
+                scope: self.scope,
+                storage: self.storage,
+                concurrency: self.concurrency,
+                strip_params: self.strip_params,
+                client_options,
+                client,
+                events: self.events,
+                manifest: self.manifest,
+                inline_svg: self.inline_svg,
+                dry_run: self.dry_run,
+                found_images: Mutex::new(vec![]),
+                saved_images: Mutex::new(vec![]),
+                budget: Budget::new(self.limits),
+                stats: Stats::default(),
+                #[cfg(feature = "render")]
+                render: self.render,
+                #[cfg(feature = "render")]
+                renderer: None,
+            },
+        })
+    }
+}
+
+/// Crawler downloading the images of a website.
+pub struct Crawler {
+    config: CrawlConfig,
+}
+
+impl Crawler {
+    /// Function that starts building a crawler for the given seed URL.
+    pub fn builder(seed: &str) -> CrawlerBuilder {
+        CrawlerBuilder {
+            seed: seed.to_string(),
+            recursive: false,
+            max_level: 5,
+            scope: vec![],
+            storage: Box::new(FsStorage::new("./data")),
+            concurrency: 1,
+            strip_params: parse_strip_params(DEFAULT_STRIP_PARAMS),
+            client_options: ClientOptions::default(),
+            events: Events::default(),
+            manifest: false,
+            inline_svg: false,
+            dry_run: false,
+            limits: Limits::default(),
+            #[cfg(feature = "render")]
+            render: None,
+        }
+    }
+
+    pub fn config(&self) -> &CrawlConfig {
+        &self.config
+    }
+
+    /// Function that runs the crawl: it checks the output directory, logs in
+    /// if a login form is configured, resolves the seed and crawls from it.
+    pub fn run(self) -> Result<CrawlReport, CrawlError> {
+        let mut config = self.config;
+        // The time budget starts with the crawl.
+        config.budget = Budget::new(config.budget.limits.clone());
+        if !config.dry_run {
+            if let Err(err) = config.storage.prepare() {
+                return Err(CrawlError::Output(
+                    config.storage.describe(),
+                    err.to_string(),
+                ));
+            }
+        }
+        login(&config)?;
+        // The seed is fetched once: the response checks that it can be reached
+        // and is then crawled.
+        let seed_response = match get_request_url(&config, &config.seed) {
+            Ok(response) => response,
+            Err(err) => return Err(CrawlError::Seed(config.seed, err)),
+        };
+        let seed = seed_response.url.clone();
+        // Without an explicit scope, the crawl is scoped to the domain the seed redirects to.
+        if config.scope.is_empty() {
+            config.scope = vec![domain::get(&seed)];
+        }
+        config.client.scope.set(&config.scope);
+        #[cfg(feature = "render")]
+        if let Some(options) = config.render.clone() {
+            config.renderer = Some(Renderer::launch(options).map_err(CrawlError::Render)?);
+        }
+
+        let config = Arc::new(config);
+        let mut visited_urls: HashSet<String> = HashSet::new();
+        let mut collected_images: Vec<String> = Vec::new();
+        let mut spider = SRequest::new(&seed, &config);
+        spider.response = Some(seed_response);
+        progress::pages_queued(1);
+        spider.get_all_image_links(&mut visited_urls, &mut collected_images);
+        progress::page_done();
+        let found_images = std::mem::take(&mut *config.found_images.lock().unwrap());
+        let saved_images = std::mem::take(&mut *config.saved_images.lock().unwrap());
+        let budget_hit = config.budget.exhausted();
+        if config.dry_run {
+            return Ok(CrawlReport {
+                seed,
+                visited_urls,
+                images: collected_images,
+                found_images,
+                saved_images,
+                budget_hit,
+                stats: config.stats.snapshot(),
+            });
+        }
+        if config.manifest {
+            let manifest = Manifest::new(&seed, &saved_images).to_json();
+            let result = config
+                .storage
+                .put(MANIFEST_NAME, manifest.as_bytes(), "application/json");
+            if let Err(err) = result {
+                return Err(CrawlError::Output(
+                    config.storage.describe(),
+                    err.to_string(),
+                ));
+            }
+        }
+        if let Err(err) = config.storage.finish() {
+            return Err(CrawlError::Output(
+                config.storage.describe(),
+                err.to_string(),
+            ));
+        }
+        Ok(CrawlReport {
+            seed,
+            visited_urls,
+            images: collected_images,
+            found_images,
+            saved_images,
+            budget_hit,
+            stats: config.stats.snapshot(),
+        })
+    }
+
+    /// Function that fetches the seed page only and returns its links sorted by kind,
+    /// without following them or downloading anything.
+    pub fn list(self) -> Result<PageLinks, CrawlError> {
+        let config = self.config;
+        login(&config)?;
+        let response = match get_request_url(&config, &config.seed) {
+            Ok(response) => response,
+            Err(err) => return Err(CrawlError::Seed(config.seed, err)),
+        };
+        if is_css(&response.content_type) {
+            let css_links = css::extract(&response.text);
+            return Ok(PageLinks {
+                url: response.url.clone(),
+                stylesheets: css::resolve(&response.url, &css_links.imports),
+                images: css::resolve(&response.url, &css_links.images),
+                ..PageLinks::default()
+            });
+        }
+        if !is_html_response(&response) {
+            logging::warn(
+                &format!(
+                    "⏭️ Not an HTML page: {} ({})",
+                    response.url, response.content_type
+                ),
+                &[
+                    ("url", &response.url),
+                    ("content_type", &response.content_type),
+                ],
+            );
+            return Ok(PageLinks {
+                url: response.url,
+                ..PageLinks::default()
+            });
+        }
+        Ok(links::list(&response.url, &response.text))
+    }
+}
+
+/// Function that posts the login form once, if one is configured.
+/// There is nobody to log in to when the responses are replayed.
+fn login(config: &CrawlConfig) -> Result<(), CrawlError> {
+    let login_url = match &config.client.replay {
+        Some(_) => None,
+        None => config.client_options.login_url.as_ref(),
+    };
+    if let Some(login_url) = login_url {
+        if config
+            .client
+            .login(login_url, &config.client_options.login_form)
+            .is_err()
+        {
+            return Err(CrawlError::Login(login_url.clone()));
+        }
+    }
+    Ok(())
+}
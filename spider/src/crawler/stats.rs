@@ -0,0 +1,73 @@
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+
+/// Counts of a finished crawl, for the end-of-run summary.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct CrawlStats {
+    /// Pages and stylesheets fetched.
+    pub pages_fetched: usize,
+    /// Pages and stylesheets that could not be fetched.
+    pub pages_failed: usize,
+    pub images_saved: usize,
+    /// Links that were not images, or images left out by the image limit.
+    pub images_skipped: usize,
+    /// Images that could not be downloaded or written.
+    pub images_failed: usize,
+    /// Bytes downloaded, pages and images included.
+    pub bytes: u64,
+}
+
+impl CrawlStats {
+    /// Function that tells if some pages or images failed, making the crawl partial.
+    pub fn has_failures(&self) -> bool {
+        self.pages_failed > 0 || self.images_failed > 0
+    }
+}
+
+/// Counters of a running crawl, updated from every download thread.
+#[derive(Debug, Default)]
+pub struct Stats {
+    pages_fetched: AtomicUsize,
+    pages_failed: AtomicUsize,
+    images_saved: AtomicUsize,
+    images_skipped: AtomicUsize,
+    images_failed: AtomicUsize,
+    bytes: AtomicU64,
+}
+
+impl Stats {
+    pub fn page_fetched(&self) {
+        self.pages_fetched.fetch_add(1, Ordering::SeqCst);
+    }
+
+    pub fn page_failed(&self) {
+        self.pages_failed.fetch_add(1, Ordering::SeqCst);
+    }
+
+    pub fn image_saved(&self) {
+        self.images_saved.fetch_add(1, Ordering::SeqCst);
+    }
+
+    pub fn image_skipped(&self) {
+        self.images_skipped.fetch_add(1, Ordering::SeqCst);
+    }
+
+    pub fn image_failed(&self) {
+        self.images_failed.fetch_add(1, Ordering::SeqCst);
+    }
+
+    pub fn add_bytes(&self, bytes: usize) {
+        self.bytes.fetch_add(bytes as u64, Ordering::SeqCst);
+    }
+
+    pub fn snapshot(&self) -> CrawlStats {
+        CrawlStats {
+            pages_fetched: self.pages_fetched.load(Ordering::SeqCst),
+            pages_failed: self.pages_failed.load(Ordering::SeqCst),
+            images_saved: self.images_saved.load(Ordering::SeqCst),
+            images_skipped: self.images_skipped.load(Ordering::SeqCst),
+            images_failed: self.images_failed.load(Ordering::SeqCst),
+            bytes: self.bytes.load(Ordering::SeqCst),
+        }
+    }
+}
@@ -0,0 +1,141 @@
+#[cfg(test)]
+use super::budget::{parse_duration, parse_size, Budget, Limit, Limits};
+#[cfg(test)]
+use std::time::Duration;
+
+#[test]
+fn test_unlimited() {
+    let budget = Budget::new(Limits::default());
+    for _ in 0..1000 {
+        budget.add_page();
+        assert!(budget.take_bytes(1 << 20));
+        assert!(budget.take_image());
+    }
+    assert!(budget.can_fetch_page());
+    assert_eq!(budget.exhausted(), None);
+}
+
+#[test]
+fn test_max_pages() {
+    let budget = Budget::new(Limits {
+        max_pages: Some(2),
+        ..Limits::default()
+    });
+    assert!(budget.can_fetch_page());
+    budget.add_page();
+    assert!(budget.can_fetch_page());
+    budget.add_page();
+    assert!(!budget.can_fetch_page());
+    assert_eq!(budget.exhausted(), Some(Limit::Pages));
+}
+
+#[test]
+fn test_max_images() {
+    let budget = Budget::new(Limits {
+        max_images: Some(2),
+        ..Limits::default()
+    });
+    assert!(budget.take_image());
+    assert!(budget.take_image());
+    // The limit is only reported once an image is refused.
+    assert_eq!(budget.exhausted(), None);
+    assert!(!budget.take_image());
+    assert_eq!(budget.exhausted(), Some(Limit::Images));
+    assert!(!budget.can_fetch_page());
+}
+
+#[test]
+fn test_release_image() {
+    let budget = Budget::new(Limits {
+        max_images: Some(1),
+        ..Limits::default()
+    });
+    assert!(budget.take_image());
+    // The image could not be saved, its slot goes to the next one.
+    budget.release_image();
+    assert!(budget.take_image());
+    assert_eq!(budget.exhausted(), None);
+    assert!(!budget.take_image());
+    assert_eq!(budget.exhausted(), Some(Limit::Images));
+}
+
+#[test]
+fn test_max_bytes() {
+    let budget = Budget::new(Limits {
+        max_bytes: Some(1000),
+        ..Limits::default()
+    });
+    assert_eq!(budget.remaining_bytes(), Some(1000));
+    assert!(budget.take_bytes(600));
+    assert_eq!(budget.exhausted(), None);
+    assert_eq!(budget.remaining_bytes(), Some(400));
+    // Bytes past the limit are refused and not counted.
+    assert!(!budget.take_bytes(600));
+    assert_eq!(budget.exhausted(), Some(Limit::Bytes));
+    assert_eq!(budget.remaining_bytes(), Some(400));
+    assert!(budget.take_bytes(400));
+    assert_eq!(budget.remaining_bytes(), Some(0));
+    assert!(!budget.take_bytes(1));
+}
+
+#[test]
+fn test_max_bytes_reached_exactly() {
+    let budget = Budget::new(Limits {
+        max_bytes: Some(10),
+        ..Limits::default()
+    });
+    assert!(budget.take_bytes(10));
+    assert_eq!(budget.exhausted(), Some(Limit::Bytes));
+    assert_eq!(Budget::new(Limits::default()).remaining_bytes(), None);
+}
+
+#[test]
+fn test_max_time() {
+    let budget = Budget::new(Limits {
+        max_time: Some(Duration::from_millis(50)),
+        ..Limits::default()
+    });
+    assert_eq!(budget.exhausted(), None);
+    std::thread::sleep(Duration::from_millis(60));
+    assert_eq!(budget.exhausted(), Some(Limit::Time));
+}
+
+#[test]
+fn test_first_limit_wins() {
+    let budget = Budget::new(Limits {
+        max_pages: Some(1),
+        max_bytes: Some(10),
+        ..Limits::default()
+    });
+    assert!(budget.take_bytes(10));
+    budget.add_page();
+    assert!(!budget.can_fetch_page());
+    assert_eq!(budget.exhausted(), Some(Limit::Bytes));
+}
+
+#[test]
+fn test_parse_size() {
+    assert_eq!(parse_size("1234"), Ok(1234));
+    assert_eq!(parse_size("500K"), Ok(500 * 1024));
+    assert_eq!(parse_size("20m"), Ok(20 * 1024 * 1024));
+    assert_eq!(parse_size("1GB"), Ok(1024 * 1024 * 1024));
+    assert!(parse_size("").is_err());
+    assert!(parse_size("M").is_err());
+    assert!(parse_size("12T").is_err());
+    assert!(parse_size("-1").is_err());
+}
+
+#[test]
+fn test_parse_duration() {
+    assert_eq!(parse_duration("30"), Ok(Duration::from_secs(30)));
+    assert_eq!(parse_duration("90s"), Ok(Duration::from_secs(90)));
+    assert_eq!(parse_duration("10m"), Ok(Duration::from_secs(600)));
+    assert_eq!(parse_duration("2h"), Ok(Duration::from_secs(7200)));
+    assert_eq!(parse_duration("500ms"), Ok(Duration::from_millis(500)));
+    assert_eq!(parse_duration("1.5s"), Ok(Duration::from_millis(1500)));
+    assert!(parse_duration("-1s").is_err());
+    assert!(parse_duration("infs").is_err());
+    assert!(parse_duration("").is_err());
+    assert!(parse_duration("1d").is_err());
+    assert!(parse_duration("soon").is_err());
+}
@@ -0,0 +1,115 @@
+#[cfg(test)]
+use super::{CrawlError, Crawler, Events, ImageFound};
+#[cfg(test)]
+use crate::http::auth::Auth;
+#[cfg(test)]
+use crate::http::ClientOptions;
+#[cfg(test)]
+use crate::parsing::metadata::ImageMetadata;
+#[cfg(test)]
+use std::sync::atomic::{AtomicUsize, Ordering};
+#[cfg(test)]
+use std::sync::Arc;
+
+#[test]
+fn test_builder_defaults() {
+    let crawler = Crawler::builder("https://example.com").build().unwrap();
+    let config = crawler.config();
+    assert_eq!(config.seed, "https://example.com");
+    assert!(!config.recursive);
+    assert_eq!(config.max_level, 5);
+    assert_eq!(config.storage.describe(), "./data");
+    assert_eq!(config.concurrency, 1);
+    assert!(config.scope.is_empty());
+    assert!(!config.strip_params.is_empty());
+    assert!(!config.dry_run);
+}
+
+#[test]
+fn test_builder_settings() {
+    let crawler = Crawler::builder("https://example.com")
+        .recursive(true)
+        .depth(2)
+        .scope(&["Example.com", "cdn.example.com"])
+        .output_dir("/tmp/images")
+        .concurrency(4)
+        .strip_params(vec![])
+        .build()
+        .unwrap();
+    let config = crawler.config();
+    assert!(config.recursive);
+    assert_eq!(config.max_level, 2);
+    assert_eq!(config.scope, vec!["example.com", "cdn.example.com"]);
+    assert_eq!(config.storage.describe(), "/tmp/images");
+    assert_eq!(config.concurrency, 4);
+    assert!(config.strip_params.is_empty());
+}
+
+#[test]
+fn test_builder_invalid_seed() {
+    let result = Crawler::builder("example.com").build();
+    assert_eq!(
+        result.err(),
+        Some(CrawlError::InvalidSeed("example.com".to_string()))
+    );
+}
+
+#[test]
+fn test_builder_zero_concurrency() {
+    let result = Crawler::builder("https://example.com")
+        .concurrency(0)
+        .build();
+    assert!(matches!(result, Err(CrawlError::InvalidConfig(_))));
+}
+
+#[test]
+fn test_builder_scopes_auth_to_seed_domain() {
+    let crawler = Crawler::builder("https://staging.example.com:8443/start")
+        .client_options(ClientOptions {
+            auth: Some(Auth::Bearer("token".to_string())),
+            ..Default::default()
+        })
+        .build()
+        .unwrap();
+    let config = crawler.config();
+    assert_eq!(
+        config.client_options.auth_hosts,
+        vec!["staging.example.com:8443"]
+    );
+    let auth = config.client.auth.as_ref().unwrap();
+    assert!(auth.applies_to("https://staging.example.com:8443/page"));
+    assert!(!auth.applies_to("https://example.com/page"));
+}
+
+#[test]
+fn test_builder_registers_callbacks() {
+    let found = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&found);
+    let crawler = Crawler::builder("https://example.com")
+        .on_image_found(move |_| {
+            counter.fetch_add(1, Ordering::SeqCst);
+        })
+        .build()
+        .unwrap();
+    let events = &crawler.config().events;
+    assert_eq!(events.image_found.len(), 1);
+    assert!(events.page_fetched.is_empty());
+    let event = ImageFound {
+        url: "https://example.com/a.jpg".to_string(),
+        page_url: "https://example.com".to_string(),
+        metadata: ImageMetadata::default(),
+    };
+    events.emit_image_found(&event);
+    events.emit_image_found(&event);
+    assert_eq!(found.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn test_events_without_callbacks() {
+    let events = Events::default();
+    events.emit_image_found(&ImageFound {
+        url: "https://example.com/a.jpg".to_string(),
+        page_url: "https://example.com".to_string(),
+        metadata: ImageMetadata::default(),
+    });
+}
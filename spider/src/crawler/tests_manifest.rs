@@ -0,0 +1,41 @@
+#[cfg(test)]
+use super::{Manifest, SavedImage};
+#[cfg(test)]
+use crate::parsing::metadata::ImageMetadata;
+
+#[test]
+fn test_manifest_to_json() {
+    let images = vec![SavedImage {
+        url: "https://example.com/img/a.jpg".to_string(),
+        page_url: "https://example.com/gallery".to_string(),
+        name: "example.com/img/a.jpg".to_string(),
+        location: "out.zip:example.com/img/a.jpg".to_string(),
+        content_type: "image/jpeg".to_string(),
+        bytes: 1234,
+        metadata: ImageMetadata {
+            alt: Some("A cat".to_string()),
+            ..ImageMetadata::default()
+        },
+    }];
+    let manifest = Manifest::new("https://example.com", &images);
+    let json: serde_json::Value = serde_json::from_str(&manifest.to_json()).unwrap();
+    assert_eq!(json["seed"], "https://example.com");
+    assert!(json["created"].as_str().unwrap().ends_with('Z'));
+    assert_eq!(json["images"].as_array().unwrap().len(), 1);
+    assert_eq!(json["images"][0]["url"], "https://example.com/img/a.jpg");
+    assert_eq!(json["images"][0]["page_url"], "https://example.com/gallery");
+    assert_eq!(
+        json["images"][0]["location"],
+        "out.zip:example.com/img/a.jpg"
+    );
+    assert_eq!(json["images"][0]["bytes"], 1234);
+    assert_eq!(json["images"][0]["metadata"]["alt"], "A cat");
+    assert!(json["images"][0]["metadata"].get("title").is_none());
+}
+
+#[test]
+fn test_manifest_without_images() {
+    let manifest = Manifest::new("https://example.com", &[]);
+    let json: serde_json::Value = serde_json::from_str(&manifest.to_json()).unwrap();
+    assert_eq!(json["images"].as_array().unwrap().len(), 0);
+}
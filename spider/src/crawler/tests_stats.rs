@@ -0,0 +1,35 @@
+#[cfg(test)]
+use super::stats::{CrawlStats, Stats};
+
+#[test]
+fn test_snapshot() {
+    let stats = Stats::default();
+    stats.page_fetched();
+    stats.page_fetched();
+    stats.image_saved();
+    stats.image_skipped();
+    stats.add_bytes(100);
+    stats.add_bytes(24);
+    assert_eq!(
+        stats.snapshot(),
+        CrawlStats {
+            pages_fetched: 2,
+            images_saved: 1,
+            images_skipped: 1,
+            bytes: 124,
+            ..CrawlStats::default()
+        }
+    );
+    assert!(!stats.snapshot().has_failures());
+}
+
+#[test]
+fn test_has_failures() {
+    let stats = Stats::default();
+    stats.page_failed();
+    assert!(stats.snapshot().has_failures());
+
+    let stats = Stats::default();
+    stats.image_failed();
+    assert!(stats.snapshot().has_failures());
+}
@@ -0,0 +1,60 @@
+use reqwest::blocking::RequestBuilder;
+
+use crate::parsing::domain;
+
+/// Credentials sent with the requests of a crawl.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Auth {
+    Basic {
+        user: String,
+        password: Option<String>,
+    },
+    Bearer(String),
+}
+
+/// Credentials restricted to a list of hosts, so that they are never
+/// sent to a domain the crawl only happens to link to.
+#[derive(Clone, Debug)]
+pub struct ScopedAuth {
+    pub auth: Auth,
+    pub hosts: Vec<String>,
+}
+
+impl ScopedAuth {
+    /// Function that check if the credentials may be sent to the given URL.
+    pub fn applies_to(&self, url: &str) -> bool {
+        let url_domain = domain::get(url);
+        if url_domain.is_empty() {
+            return false;
+        }
+        self.hosts
+            .iter()
+            .any(|host| host.eq_ignore_ascii_case(&url_domain))
+    }
+
+    /// Function that adds the credentials to the request if the URL is in scope.
+    pub fn apply(&self, url: &str, request: RequestBuilder) -> RequestBuilder {
+        if !self.applies_to(url) {
+            return request;
+        }
+        match &self.auth {
+            Auth::Basic { user, password } => request.basic_auth(user, password.as_ref()),
+            Auth::Bearer(token) => request.bearer_auth(token),
+        }
+    }
+}
+
+/// Function that parses `user:password` (or just `user`) given on the command line.
+pub fn parse_basic(credentials: &str) -> Result<Auth, String> {
+    let (user, password) = match credentials.split_once(':') {
+        Some((user, password)) => (user, Some(password.to_string())),
+        None => (credentials, None),
+    };
+    if user.is_empty() {
+        return Err(format!("expected user:password, got '{credentials}'"));
+    }
+    Ok(Auth::Basic {
+        user: user.to_string(),
+        password,
+    })
+}
@@ -0,0 +1,84 @@
+use reqwest::cookie::Jar;
+use std::time::{SystemTime, UNIX_EPOCH};
+use url::Url;
+
+use crate::logging;
+
+/// A cookie read from a Netscape `cookies.txt` file, ready to be added to a jar.
+pub struct NetscapeCookie {
+    pub url: String,
+    pub header: String,
+}
+
+/// Function that parses one line of a Netscape `cookies.txt` file.
+/// Comments, blank lines and expired cookies are ignored.
+/// The `#HttpOnly_` prefix written by curl and browsers is supported.
+pub fn parse_netscape_line(line: &str) -> Option<NetscapeCookie> {
+    let line = line.trim_end_matches(['\r', '\n']);
+    let (line, http_only) = match line.strip_prefix("#HttpOnly_") {
+        Some(line) => (line, true),
+        None => (line, false),
+    };
+    if line.trim().is_empty() || line.starts_with('#') {
+        return None;
+    }
+    let fields = line.split('\t').collect::<Vec<&str>>();
+    if fields.len() != 7 {
+        return None;
+    }
+    let domain = fields[0];
+    let include_subdomains = fields[1].eq_ignore_ascii_case("TRUE");
+    let path = fields[2];
+    let secure = fields[3].eq_ignore_ascii_case("TRUE");
+    let expires = fields[4].parse::<u64>().ok()?;
+    let name = fields[5];
+    let value = fields[6];
+    if name.is_empty() {
+        return None;
+    }
+    if expires != 0 {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).ok()?.as_secs();
+        if expires < now {
+            return None;
+        }
+    }
+
+    let host = domain.trim_start_matches('.');
+    let scheme = if secure { "https" } else { "http" };
+    let url = format!("{scheme}://{host}{path}");
+    Url::parse(&url).ok()?;
+
+    let mut header = format!("{name}={value}; Path={path}");
+    if include_subdomains {
+        header.push_str(&format!("; Domain={host}"));
+    }
+    if secure {
+        header.push_str("; Secure");
+    }
+    if http_only {
+        header.push_str("; HttpOnly");
+    }
+    Some(NetscapeCookie { url, header })
+}
+
+/// Function that loads all the cookies of a Netscape `cookies.txt` file into the jar.
+/// It returns the number of cookies loaded.
+pub fn load_netscape_file(path: &str, jar: &Jar) -> Result<usize, ()> {
+    let content = std::fs::read_to_string(path);
+    if content.is_err() {
+        logging::error(
+            &format!("Could not read cookies file: {path}"),
+            &[("path", path)],
+        );
+        return Err(());
+    }
+    let mut count = 0;
+    for line in content.unwrap().lines() {
+        if let Some(cookie) = parse_netscape_line(line) {
+            let url = Url::parse(&cookie.url).unwrap();
+            jar.add_cookie_str(&cookie.header, &url);
+            count += 1;
+        }
+    }
+    Ok(count)
+}
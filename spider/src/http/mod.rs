@@ -0,0 +1,331 @@
+use reqwest::blocking::{Client, ClientBuilder, Request, RequestBuilder};
+use reqwest::cookie::{CookieStore, Jar};
+use reqwest::header::{self, HeaderMap, HeaderName, HeaderValue};
+use reqwest::redirect::Policy;
+use reqwest::{NoProxy, Proxy};
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+pub mod auth;
+pub mod cookies;
+pub mod redirect;
+pub mod replay;
+pub mod throttle;
+pub mod tls;
+
+mod tests_auth;
+mod tests_cookies;
+mod tests_headers;
+mod tests_proxy;
+mod tests_redirect;
+mod tests_replay;
+mod tests_throttle;
+mod tests_tls;
+
+use auth::{Auth, ScopedAuth};
+use redirect::RedirectScope;
+use replay::Replay;
+use throttle::Throttle;
+use tls::TlsOptions;
+
+use crate::logging;
+use crate::warc::WarcWriter;
+
+/// Options used to build the HTTP client shared by the whole crawl.
+#[derive(Clone, Default)]
+pub struct ClientOptions {
+    pub cookies_file: Option<String>,
+    pub login_url: Option<String>,
+    pub login_form: Vec<(String, String)>,
+    pub auth: Option<Auth>,
+    pub auth_hosts: Vec<String>,
+    pub proxy: Option<String>,
+    pub no_proxy: Option<String>,
+    pub tls: TlsOptions,
+    pub max_redirects: Option<usize>,
+    /// Directory mirror or WARC file answering the requests instead of the network.
+    pub offline: Option<String>,
+    /// WARC file where every request and response is recorded.
+    pub warc: Option<String>,
+    /// Headers sent with every request, such as a `User-Agent`.
+    pub headers: Vec<(String, String)>,
+    /// Minimum delay between two requests.
+    pub delay: Option<Duration>,
+}
+
+/// Reason why a request did not give a usable response.
+#[derive(Debug, PartialEq)]
+pub enum FetchError {
+    Tls(String),
+    Network,
+    Status(u16),
+    Body,
+    Redirect(String),
+    TooManyRedirects,
+    NotRecorded,
+    /// The body does not fit in the download size limit.
+    OverBudget,
+}
+
+impl FetchError {
+    /// Function that sorts a reqwest error into TLS failures and other network failures.
+    pub fn from_reqwest(err: &reqwest::Error) -> FetchError {
+        if err.is_redirect() {
+            return FetchError::TooManyRedirects;
+        }
+        match tls::tls_error_message(err) {
+            Some(message) => FetchError::Tls(message),
+            None => FetchError::Network,
+        }
+    }
+}
+
+impl fmt::Display for FetchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FetchError::Tls(message) => write!(f, "TLS error: {message}"),
+            FetchError::Network => write!(f, "network error"),
+            FetchError::Status(status_code) => write!(f, "HTTP status {status_code}"),
+            FetchError::Body => write!(f, "could not read the response body"),
+            FetchError::Redirect(location) => write!(f, "redirected out of scope to {location}"),
+            FetchError::TooManyRedirects => write!(f, "too many redirects"),
+            FetchError::NotRecorded => write!(f, "not found in the offline source"),
+            FetchError::OverBudget => write!(f, "download size limit reached"),
+        }
+    }
+}
+
+/// HTTP client shared by every request of a crawl.
+/// The client owns the cookie jar, so cookies set by one response
+/// are sent back with all the following requests.
+#[derive(Clone)]
+pub struct SClient {
+    pub client: Client,
+    pub auth: Option<ScopedAuth>,
+    pub scope: RedirectScope,
+    pub max_redirects: usize,
+    pub replay: Option<Arc<dyn Replay>>,
+    pub recorder: Option<Arc<WarcWriter>>,
+    pub throttle: Option<Arc<Throttle>>,
+    /// Headers the client adds to every request, kept to record what was sent.
+    pub default_headers: HeaderMap,
+    pub jar: Arc<Jar>,
+}
+
+impl SClient {
+    pub fn new(options: &ClientOptions) -> Result<SClient, ()> {
+        let jar = Arc::new(Jar::default());
+        if let Some(cookies_file) = &options.cookies_file {
+            let count = cookies::load_netscape_file(cookies_file, &jar)?;
+            logging::info(
+                &format!("🍪 Loaded {count} cookies from {cookies_file}"),
+                &[("path", cookies_file)],
+            );
+        }
+        let scope = RedirectScope::default();
+        let max_redirects = options
+            .max_redirects
+            .unwrap_or(redirect::DEFAULT_MAX_REDIRECTS);
+        // When recording, every hop of a redirect chain must reach the WARC file,
+        // so the redirects are followed one by one in `get_request_url`.
+        let redirect_policy = match &options.warc {
+            Some(_) => Policy::none(),
+            None => redirect::policy(scope.clone(), max_redirects),
+        };
+        let headers = build_headers(&options.headers)?;
+        let client = client_builder(options)?
+            .cookie_provider(Arc::clone(&jar))
+            .redirect(redirect_policy)
+            .default_headers(headers.clone())
+            .build();
+        if client.is_err() {
+            logging::error("Could not build the HTTP client", &[]);
+            return Err(());
+        }
+        let auth = options.auth.clone().map(|auth| ScopedAuth {
+            auth,
+            hosts: options.auth_hosts.clone(),
+        });
+        let replay = match &options.offline {
+            Some(path) => Some(Arc::from(replay::open(path)?)),
+            None => None,
+        };
+        let recorder = match &options.warc {
+            Some(path) => match WarcWriter::create(path) {
+                Ok(writer) => Some(Arc::new(writer)),
+                Err(err) => {
+                    logging::error(
+                        &format!("Could not create the WARC file {path}: {err}"),
+                        &[("path", path)],
+                    );
+                    return Err(());
+                }
+            },
+            None => None,
+        };
+        Ok(SClient {
+            client: client.unwrap(),
+            auth,
+            scope,
+            max_redirects,
+            replay,
+            recorder,
+            throttle: options.delay.map(|delay| Arc::new(Throttle::new(delay))),
+            // reqwest sends `Accept: */*` unless it is replaced.
+            default_headers: {
+                let mut default_headers = HeaderMap::new();
+                default_headers.insert(header::ACCEPT, HeaderValue::from_static("*/*"));
+                default_headers.extend(headers);
+                default_headers
+            },
+            jar,
+        })
+    }
+
+    /// Function that returns the headers the client sends with a built request:
+    /// its own headers, then the default headers and the cookies of the jar
+    /// it does not set itself, the way the client completes it.
+    pub fn sent_headers(&self, request: &Request) -> HeaderMap {
+        let mut headers = request.headers().clone();
+        for name in self.default_headers.keys() {
+            if !headers.contains_key(name) {
+                for value in self.default_headers.get_all(name) {
+                    headers.append(name, value.clone());
+                }
+            }
+        }
+        if !headers.contains_key(header::COOKIE) {
+            if let Some(cookies) = self.jar.cookies(request.url()) {
+                headers.insert(header::COOKIE, cookies);
+            }
+        }
+        headers
+    }
+
+    /// Function that prepares a GET request, with the credentials if the URL is in scope.
+    pub fn get(&self, url: &str) -> RequestBuilder {
+        self.with_auth(url, self.client.get(url))
+    }
+
+    /// Function that prepares a HEAD request, with the credentials if the URL is in scope.
+    pub fn head(&self, url: &str) -> RequestBuilder {
+        self.with_auth(url, self.client.head(url))
+    }
+
+    /// Function that prepares a POST request, with the credentials if the URL is in scope.
+    pub fn post(&self, url: &str) -> RequestBuilder {
+        self.with_auth(url, self.client.post(url))
+    }
+
+    /// Function that waits for the turn of the next request when a delay is set.
+    /// Every request sent with the client calls it right before it is sent.
+    pub fn before_send(&self) {
+        if let Some(throttle) = &self.throttle {
+            throttle.wait();
+        }
+    }
+
+    fn with_auth(&self, url: &str, request: RequestBuilder) -> RequestBuilder {
+        match &self.auth {
+            Some(auth) => auth.apply(url, request),
+            None => request,
+        }
+    }
+
+    /// Function that posts the login form once so that the session cookies
+    /// end up in the jar before the crawl starts.
+    pub fn login(&self, url: &str, form: &[(String, String)]) -> Result<(), ()> {
+        let request = self.post(url).form(form);
+        self.before_send();
+        let response = request.send();
+        if response.is_err() {
+            logging::error(
+                &format!("Could not send the login form to {url}"),
+                &[("url", url)],
+            );
+            return Err(());
+        }
+        let status_code = response.unwrap().status().as_u16();
+        if status_code >= 400 {
+            logging::error(
+                &format!("Login failed on {url}: {status_code}"),
+                &[("url", url), ("status", &status_code.to_string())],
+            );
+            return Err(());
+        }
+        logging::info(&format!("🔑 Logged in on {url}"), &[("url", url)]);
+        Ok(())
+    }
+}
+
+/// Function that starts a client going through the proxy and with the TLS settings
+/// of the options, so every connection of the crawl goes the same way.
+pub fn client_builder(options: &ClientOptions) -> Result<ClientBuilder, ()> {
+    let mut builder = Client::builder();
+    if let Some(proxy) = &options.proxy {
+        builder = builder.proxy(build_proxy(proxy, options.no_proxy.as_deref())?);
+    }
+    tls::configure(builder, &options.tls)
+}
+
+/// Function that parses a `key=value` pair given on the command line.
+pub fn parse_form_field(field: &str) -> Result<(String, String), String> {
+    match field.split_once('=') {
+        Some((key, value)) if !key.is_empty() => Ok((key.to_string(), value.to_string())),
+        _ => Err(format!("expected key=value, got '{field}'")),
+    }
+}
+
+/// Function that parses a `Name: value` header given on the command line.
+pub fn parse_header(header: &str) -> Result<(String, String), String> {
+    let error = || format!("expected Name: value, got '{header}'");
+    let (name, value) = header.split_once(':').ok_or_else(error)?;
+    let (name, value) = (name.trim(), value.trim());
+    if HeaderName::from_bytes(name.as_bytes()).is_err() || HeaderValue::from_str(value).is_err() {
+        return Err(error());
+    }
+    Ok((name.to_string(), value.to_string()))
+}
+
+/// Function that builds the headers sent with every request.
+pub fn build_headers(headers: &[(String, String)]) -> Result<HeaderMap, ()> {
+    let mut header_map = HeaderMap::new();
+    for (name, value) in headers {
+        let header_name = HeaderName::from_bytes(name.as_bytes());
+        let header_value = HeaderValue::from_str(value);
+        if header_name.is_err() || header_value.is_err() {
+            logging::error(&format!("Invalid header: {name}: {value}"), &[]);
+            return Err(());
+        }
+        header_map.append(header_name.unwrap(), header_value.unwrap());
+    }
+    Ok(header_map)
+}
+
+/// Function that builds the proxy used for every scheme.
+/// `http://`, `https://`, `socks5://` and `socks5h://` proxies are supported.
+/// Hosts excluded with `no_proxy` are merged with the `NO_PROXY` environment variable.
+/// Without an explicit proxy reqwest already honours `HTTP_PROXY`, `HTTPS_PROXY` and `NO_PROXY`.
+pub fn build_proxy(proxy: &str, no_proxy: Option<&str>) -> Result<Proxy, ()> {
+    let supported = ["http://", "https://", "socks5://", "socks5h://"];
+    if !supported.iter().any(|scheme| proxy.starts_with(scheme)) {
+        logging::error(&format!("Unsupported proxy scheme: {proxy}"), &[]);
+        return Err(());
+    }
+    let result = Proxy::all(proxy);
+    if result.is_err() {
+        logging::error(&format!("Invalid proxy URL: {proxy}"), &[]);
+        return Err(());
+    }
+    let env_no_proxy = std::env::var("NO_PROXY")
+        .or_else(|_| std::env::var("no_proxy"))
+        .unwrap_or_default();
+    let no_proxy = [no_proxy.unwrap_or(""), env_no_proxy.as_str()]
+        .iter()
+        .filter(|hosts| !hosts.is_empty())
+        .copied()
+        .collect::<Vec<&str>>()
+        .join(",");
+    Ok(result.unwrap().no_proxy(NoProxy::from_string(&no_proxy)))
+}
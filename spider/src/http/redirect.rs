@@ -0,0 +1,93 @@
+use reqwest::redirect::{Attempt, Policy};
+use std::sync::{Arc, RwLock};
+
+use crate::logging;
+use crate::parsing::domain;
+
+/// Default maximum number of redirects followed for one request.
+pub const DEFAULT_MAX_REDIRECTS: usize = 10;
+
+/// Domains the redirects are allowed to go to.
+/// It is empty until the seed URL has been resolved, so that the seed
+/// itself may redirect to another host (for example `www.`).
+#[derive(Clone, Default)]
+pub struct RedirectScope(Arc<RwLock<Vec<String>>>);
+
+impl RedirectScope {
+    pub fn set(&self, domains: &[String]) {
+        *self.0.write().unwrap() = domains.to_vec();
+    }
+
+    /// Function that check if a redirect to the given URL stays in scope.
+    pub fn allows(&self, url: &str) -> bool {
+        let scope = self.0.read().unwrap();
+        if scope.is_empty() {
+            return true;
+        }
+        let url_domain = domain::get(url);
+        scope
+            .iter()
+            .any(|domain| domain.eq_ignore_ascii_case(&url_domain))
+    }
+}
+
+/// What to do with one hop of a redirect chain.
+#[derive(Debug, PartialEq)]
+pub enum RedirectAction {
+    Follow,
+    Stop,
+    TooMany,
+}
+
+/// Function that decides if a redirect is followed.
+/// `hops` is the number of redirects already followed for this request.
+pub fn decide(
+    scope: &RedirectScope,
+    max_redirects: usize,
+    hops: usize,
+    to: &str,
+) -> RedirectAction {
+    if hops >= max_redirects {
+        return RedirectAction::TooMany;
+    }
+    if !scope.allows(to) {
+        return RedirectAction::Stop;
+    }
+    RedirectAction::Follow
+}
+
+/// Function that builds the redirect policy of the client.
+/// Every hop is logged, chains longer than `max_redirects` fail, and redirects
+/// leaving the scope are not followed so the 3xx response is returned as is.
+pub fn policy(scope: RedirectScope, max_redirects: usize) -> Policy {
+    Policy::custom(move |attempt: Attempt| {
+        let from = attempt.previous().last().map(|url| url.to_string());
+        let to = attempt.url().to_string();
+        let hops = attempt.previous().len() - 1;
+        match decide(&scope, max_redirects, hops, &to) {
+            RedirectAction::Follow => {
+                log_follow(&from.unwrap_or_default(), &to);
+                attempt.follow()
+            }
+            RedirectAction::Stop => {
+                log_blocked(&from.unwrap_or_default(), &to);
+                attempt.stop()
+            }
+            RedirectAction::TooMany => attempt.error("too many redirects"),
+        }
+    })
+}
+
+pub fn log_follow(from: &str, to: &str) {
+    logging::info(
+        &format!("↪️  Redirect: {from} -> {to}"),
+        &[("from", from), ("to", to)],
+    );
+}
+
+pub fn log_blocked(from: &str, to: &str) {
+    logging::warn(
+        &format!("⛔ Redirect out of scope blocked: {from} -> {to}"),
+        &[("from", from), ("to", to)],
+    );
+}
@@ -0,0 +1,272 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use url::Url;
+
+use super::redirect::{self, RedirectAction, RedirectScope};
+use super::FetchError;
+use crate::logging;
+use crate::parsing::normalize::normalize;
+use crate::spider::ResponseContent;
+use crate::warc;
+
+/// A single response, read from an offline source or received from the network.
+/// Redirects are not followed yet.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RecordedResponse {
+    pub status: u16,
+    pub content_type: String,
+    pub location: Option<String>,
+    pub body: Vec<u8>,
+}
+
+/// Source of recorded responses used to crawl without the network.
+pub trait Replay: Send + Sync {
+    /// Function that returns the response recorded for the URL, if any.
+    fn fetch(&self, url: &str) -> Option<RecordedResponse>;
+
+    /// Function that describes the source for the messages.
+    fn describe(&self) -> String;
+}
+
+/// Function that opens the offline source at the given path:
+/// a `.warc` or `.warc.gz` file, or a directory mirror.
+pub fn open(path: &str) -> Result<Box<dyn Replay>, ()> {
+    let lowercase = path.to_lowercase();
+    if lowercase.ends_with(".warc") || lowercase.ends_with(".warc.gz") {
+        return Ok(Box::new(WarcReplay::open(path)?));
+    }
+    if !Path::new(path).is_dir() {
+        logging::error(
+            &format!("Offline source not found: {path}"),
+            &[("path", path)],
+        );
+        return Err(());
+    }
+    Ok(Box::new(MirrorReplay::new(path)))
+}
+
+/// Function that gets a URL from an offline source, following the recorded redirects
+/// with the same scope and limit as the network client.
+pub fn get(
+    replay: &dyn Replay,
+    scope: &RedirectScope,
+    max_redirects: usize,
+    url: &str,
+) -> Result<ResponseContent, FetchError> {
+    follow(scope, max_redirects, url, |current_url| {
+        replay.fetch(current_url).ok_or(FetchError::NotRecorded)
+    })
+}
+
+/// Function that follows redirects one hop at a time, `fetch` getting a single response.
+/// Redirects are logged, checked against the scope and limited like in the client policy.
+pub fn follow(
+    scope: &RedirectScope,
+    max_redirects: usize,
+    url: &str,
+    mut fetch: impl FnMut(&str) -> Result<RecordedResponse, FetchError>,
+) -> Result<ResponseContent, FetchError> {
+    let mut current_url = url.to_string();
+    let mut hops = 0;
+    loop {
+        let response = fetch(&current_url)?;
+        if (300..400).contains(&response.status) {
+            let location = response.location.unwrap_or_default();
+            let next_url = Url::parse(&current_url)
+                .and_then(|base| base.join(&location))
+                .map(|next_url| next_url.to_string())
+                .unwrap_or(location);
+            match redirect::decide(scope, max_redirects, hops, &next_url) {
+                RedirectAction::Follow => {
+                    redirect::log_follow(&current_url, &next_url);
+                    current_url = next_url;
+                    hops += 1;
+                    continue;
+                }
+                RedirectAction::Stop => {
+                    redirect::log_blocked(&current_url, &next_url);
+                    return Err(FetchError::Redirect(next_url));
+                }
+                RedirectAction::TooMany => return Err(FetchError::TooManyRedirects),
+            }
+        }
+        if response.status != 200 {
+            return Err(FetchError::Status(response.status));
+        }
+        return Ok(ResponseContent {
+            url: current_url,
+            content_type: response.content_type,
+            text: String::from_utf8(response.body.clone()).unwrap_or_default(),
+            bytes: response.body,
+        });
+    }
+}
+
+/// Directory mirror, as written by `wget --mirror` or `httrack`.
+/// A URL is looked up under `root/host/path` first, then under `root/path`.
+pub struct MirrorReplay {
+    pub root: PathBuf,
+}
+
+impl MirrorReplay {
+    pub fn new(root: &str) -> MirrorReplay {
+        MirrorReplay {
+            root: PathBuf::from(root),
+        }
+    }
+
+    /// Function that lists the files that may hold the given URL, in lookup order.
+    pub fn candidates(&self, url: &str) -> Vec<PathBuf> {
+        let parsed_url = Url::parse(url);
+        if parsed_url.is_err() {
+            return Vec::new();
+        }
+        let parsed_url = parsed_url.unwrap();
+        let host = match parsed_url.port() {
+            Some(port) => format!("{}:{port}", parsed_url.host_str().unwrap_or("")),
+            None => parsed_url.host_str().unwrap_or("").to_string(),
+        };
+        let path = parsed_url.path().trim_start_matches('/').to_string();
+        let mut names = Vec::new();
+        if let Some(query) = parsed_url.query() {
+            names.push(format!("{path}?{query}"));
+        }
+        names.push(path);
+
+        let mut candidates = Vec::new();
+        for base in [self.root.join(&host), self.root.clone()] {
+            for name in &names {
+                if name.is_empty() || name.ends_with('/') {
+                    candidates.push(base.join(name).join("index.html"));
+                    continue;
+                }
+                let file = base.join(name);
+                candidates.push(file.join("index.html"));
+                candidates.push(file);
+            }
+        }
+        candidates
+    }
+}
+
+impl Replay for MirrorReplay {
+    fn fetch(&self, url: &str) -> Option<RecordedResponse> {
+        let file = self
+            .candidates(url)
+            .into_iter()
+            .find(|candidate| candidate.is_file())?;
+        let body = fs::read(&file).ok()?;
+        Some(RecordedResponse {
+            status: 200,
+            content_type: guess_content_type(&file).to_string(),
+            location: None,
+            body,
+        })
+    }
+
+    fn describe(&self) -> String {
+        self.root.display().to_string()
+    }
+}
+
+/// Function that guesses the content type of a mirrored file from its extension.
+pub fn guess_content_type(path: &Path) -> &'static str {
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(|extension| extension.to_lowercase())
+        .unwrap_or_default();
+    let extension = extension.split('?').next().unwrap_or("");
+    match extension {
+        "html" | "htm" | "php" | "asp" | "aspx" | "jsp" => "text/html",
+        "css" => "text/css",
+        "js" => "application/javascript",
+        "json" => "application/json",
+        "xml" => "application/xml",
+        "txt" => "text/plain",
+        "jpg" | "jpeg" => "image/jpeg",
+        "png" => "image/png",
+        "gif" => "image/gif",
+        "bmp" => "image/bmp",
+        "webp" => "image/webp",
+        "svg" => "image/svg+xml",
+        "ico" => "image/x-icon",
+        "" => "text/html",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Responses recorded in a WARC file, indexed by normalized URL.
+pub struct WarcReplay {
+    pub path: String,
+    pub responses: HashMap<String, RecordedResponse>,
+}
+
+impl WarcReplay {
+    pub fn open(path: &str) -> Result<WarcReplay, ()> {
+        let records = warc::read_file(path);
+        if let Err(err) = records {
+            logging::error(
+                &format!("Could not read the WARC file {path}: {err}"),
+                &[("path", path)],
+            );
+            return Err(());
+        }
+        let records = records.unwrap();
+        // Responses to a HEAD request have no body, they can not answer a GET.
+        let head_responses: HashSet<&str> = records
+            .iter()
+            .filter(|record| {
+                record.record_type() == Some("request") && record.block.starts_with(b"HEAD ")
+            })
+            .filter_map(|record| record.header("WARC-Concurrent-To"))
+            .collect();
+        let mut responses = HashMap::new();
+        for record in &records {
+            if record.record_type() != Some("response") {
+                continue;
+            }
+            if let Some(id) = record.header("WARC-Record-ID") {
+                if head_responses.contains(id) {
+                    continue;
+                }
+            }
+            let url = record.target_uri();
+            let response = warc::parse_http_response(&record.block);
+            if url.is_none() || response.is_none() {
+                continue;
+            }
+            let response = response.unwrap();
+            // The first capture of a URL wins, like a replay of the original crawl.
+            responses
+                .entry(normalize(url.unwrap(), &[]))
+                .or_insert(RecordedResponse {
+                    status: response.status,
+                    content_type: response.header("Content-Type").unwrap_or("").to_string(),
+                    location: response
+                        .header("Location")
+                        .map(|location| location.to_string()),
+                    body: response.body,
+                });
+        }
+        logging::info(
+            &format!("📼 Loaded {} responses from {path}", responses.len()),
+            &[("path", path)],
+        );
+        Ok(WarcReplay {
+            path: path.to_string(),
+            responses,
+        })
+    }
+}
+
+impl Replay for WarcReplay {
+    fn fetch(&self, url: &str) -> Option<RecordedResponse> {
+        self.responses.get(&normalize(url, &[])).cloned()
+    }
+
+    fn describe(&self) -> String {
+        self.path.clone()
+    }
+}
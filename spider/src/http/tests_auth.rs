@@ -0,0 +1,66 @@
+#[cfg(test)]
+use super::auth::{parse_basic, Auth, ScopedAuth};
+
+#[test]
+fn test_parse_basic_with_password() {
+    let auth = parse_basic("user:secret").unwrap();
+    assert_eq!(
+        auth,
+        Auth::Basic {
+            user: "user".to_string(),
+            password: Some("secret".to_string()),
+        }
+    );
+}
+
+#[test]
+fn test_parse_basic_password_with_colon() {
+    let auth = parse_basic("user:se:cret").unwrap();
+    assert_eq!(
+        auth,
+        Auth::Basic {
+            user: "user".to_string(),
+            password: Some("se:cret".to_string()),
+        }
+    );
+}
+
+#[test]
+fn test_parse_basic_without_password() {
+    let auth = parse_basic("user").unwrap();
+    assert_eq!(
+        auth,
+        Auth::Basic {
+            user: "user".to_string(),
+            password: None,
+        }
+    );
+}
+
+#[test]
+fn test_parse_basic_without_user() {
+    assert!(parse_basic(":secret").is_err());
+}
+
+#[test]
+fn test_scoped_auth_applies_to_listed_hosts_only() {
+    let scoped = ScopedAuth {
+        auth: Auth::Bearer("token".to_string()),
+        hosts: vec!["staging.example.com".to_string()],
+    };
+    assert!(scoped.applies_to("https://staging.example.com/page"));
+    assert!(scoped.applies_to("https://STAGING.example.com/page"));
+    assert!(!scoped.applies_to("https://example.com/page"));
+    assert!(!scoped.applies_to("https://cdn.other.org/image.jpg"));
+    assert!(!scoped.applies_to("not a url"));
+}
+
+#[test]
+fn test_scoped_auth_host_with_port() {
+    let scoped = ScopedAuth {
+        auth: Auth::Bearer("token".to_string()),
+        hosts: vec!["localhost:8080".to_string()],
+    };
+    assert!(scoped.applies_to("http://localhost:8080/page"));
+    assert!(!scoped.applies_to("http://localhost:9090/page"));
+}
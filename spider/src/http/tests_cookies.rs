@@ -0,0 +1,69 @@
+#[cfg(test)]
+use super::cookies;
+#[cfg(test)]
+use reqwest::cookie::{CookieStore, Jar};
+
+#[test]
+fn test_parse_netscape_line() {
+    let line = "example.com\tFALSE\t/\tFALSE\t0\tsession\tabc123";
+    let cookie = cookies::parse_netscape_line(line).unwrap();
+    assert_eq!(cookie.url, "http://example.com/");
+    assert_eq!(cookie.header, "session=abc123; Path=/");
+}
+
+#[test]
+fn test_parse_netscape_line_with_subdomains_and_secure() {
+    let line = ".example.com\tTRUE\t/app\tTRUE\t0\ttoken\txyz";
+    let cookie = cookies::parse_netscape_line(line).unwrap();
+    assert_eq!(cookie.url, "https://example.com/app");
+    assert_eq!(
+        cookie.header,
+        "token=xyz; Path=/app; Domain=example.com; Secure"
+    );
+}
+
+#[test]
+fn test_parse_netscape_line_http_only() {
+    let line = "#HttpOnly_example.com\tFALSE\t/\tFALSE\t0\tid\t42";
+    let cookie = cookies::parse_netscape_line(line).unwrap();
+    assert_eq!(cookie.header, "id=42; Path=/; HttpOnly");
+}
+
+#[test]
+fn test_parse_netscape_line_ignores_comments_and_blank_lines() {
+    assert!(cookies::parse_netscape_line("# Netscape HTTP Cookie File").is_none());
+    assert!(cookies::parse_netscape_line("").is_none());
+    assert!(cookies::parse_netscape_line("   ").is_none());
+}
+
+#[test]
+fn test_parse_netscape_line_ignores_malformed_lines() {
+    assert!(cookies::parse_netscape_line("example.com\tFALSE\t/").is_none());
+    assert!(cookies::parse_netscape_line("example.com\tFALSE\t/\tFALSE\tnever\tid\t42").is_none());
+}
+
+#[test]
+fn test_parse_netscape_line_ignores_expired_cookies() {
+    let line = "example.com\tFALSE\t/\tFALSE\t1\told\tvalue";
+    assert!(cookies::parse_netscape_line(line).is_none());
+}
+
+#[test]
+fn test_load_netscape_file() {
+    let path = std::env::temp_dir().join("spider_test_cookies.txt");
+    std::fs::write(
+        &path,
+        "# Netscape HTTP Cookie File\n\
+         example.com\tFALSE\t/\tFALSE\t0\tsession\tabc123\n\
+         other.org\tFALSE\t/\tFALSE\t0\tid\t42\n",
+    )
+    .unwrap();
+    let jar = Jar::default();
+    let count = cookies::load_netscape_file(path.to_str().unwrap(), &jar);
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(count, Ok(2));
+
+    let url = url::Url::parse("http://example.com/page").unwrap();
+    let header = jar.cookies(&url).unwrap();
+    assert_eq!(header.to_str().unwrap(), "session=abc123");
+}
@@ -0,0 +1,64 @@
+#[cfg(test)]
+use super::{build_headers, parse_header, ClientOptions, SClient};
+
+#[test]
+fn test_parse_header() {
+    assert_eq!(
+        parse_header("User-Agent: spider/1.0"),
+        Ok(("User-Agent".to_string(), "spider/1.0".to_string()))
+    );
+    assert_eq!(
+        parse_header("X-Token:abc:def"),
+        Ok(("X-Token".to_string(), "abc:def".to_string()))
+    );
+    assert!(parse_header("User-Agent").is_err());
+    assert!(parse_header(": value").is_err());
+    assert!(parse_header("Bad Name: value").is_err());
+}
+
+#[test]
+fn test_build_headers() {
+    let headers = build_headers(&[
+        ("Accept-Language".to_string(), "fr".to_string()),
+        ("X-Tag".to_string(), "a".to_string()),
+        ("X-Tag".to_string(), "b".to_string()),
+    ])
+    .unwrap();
+    assert_eq!(headers["accept-language"], "fr");
+    assert_eq!(headers.get_all("x-tag").iter().count(), 2);
+    assert!(build_headers(&[("X-Bad".to_string(), "line\nbreak".to_string())]).is_err());
+}
+
+#[test]
+fn test_sent_headers() {
+    let client = SClient::new(&ClientOptions {
+        headers: vec![("User-Agent".to_string(), "spider-test".to_string())],
+        ..ClientOptions::default()
+    })
+    .unwrap();
+    let url = "http://example.com/a".parse().unwrap();
+    client.jar.add_cookie_str("session=abc", &url);
+    let request = client
+        .client
+        .get("http://example.com/a")
+        .header("X-Tag", "a")
+        .build()
+        .unwrap();
+    let headers = client.sent_headers(&request);
+    assert_eq!(headers["user-agent"], "spider-test");
+    assert_eq!(headers["accept"], "*/*");
+    assert_eq!(headers["cookie"], "session=abc");
+    assert_eq!(headers["x-tag"], "a");
+
+    // The headers of the request replace the default ones.
+    let request = client
+        .client
+        .get("http://other.com/")
+        .header("Accept", "image/png")
+        .build()
+        .unwrap();
+    let headers = client.sent_headers(&request);
+    assert_eq!(headers.get_all("accept").iter().count(), 1);
+    assert_eq!(headers["accept"], "image/png");
+    assert!(!headers.contains_key("cookie"));
+}
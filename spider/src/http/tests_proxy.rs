@@ -0,0 +1,16 @@
+#[cfg(test)]
+use super::build_proxy;
+
+#[test]
+fn test_build_proxy_supported_schemes() {
+    assert!(build_proxy("http://proxy.local:3128", None).is_ok());
+    assert!(build_proxy("https://proxy.local:3128", None).is_ok());
+    assert!(build_proxy("socks5://127.0.0.1:1080", None).is_ok());
+    assert!(build_proxy("socks5h://127.0.0.1:1080", Some("localhost,.internal")).is_ok());
+}
+
+#[test]
+fn test_build_proxy_unsupported_scheme() {
+    assert!(build_proxy("ftp://proxy.local:21", None).is_err());
+    assert!(build_proxy("proxy.local:3128", None).is_err());
+}
@@ -0,0 +1,51 @@
+#[cfg(test)]
+use super::redirect::{decide, RedirectAction, RedirectScope};
+
+#[test]
+fn test_redirect_scope_unset_allows_everything() {
+    let scope = RedirectScope::default();
+    assert!(scope.allows("https://other.org/page"));
+}
+
+#[test]
+fn test_redirect_scope_set() {
+    let scope = RedirectScope::default();
+    scope.set(&["www.example.com".to_string()]);
+    assert!(scope.allows("https://www.example.com/page"));
+    assert!(scope.allows("http://WWW.example.com/"));
+    assert!(!scope.allows("https://example.com/page"));
+    assert!(!scope.allows("https://other.org/page"));
+}
+
+#[test]
+fn test_decide_follow_in_scope() {
+    let scope = RedirectScope::default();
+    scope.set(&["example.com".to_string()]);
+    let action = decide(&scope, 10, 0, "https://example.com/next");
+    assert_eq!(action, RedirectAction::Follow);
+}
+
+#[test]
+fn test_decide_stop_out_of_scope() {
+    let scope = RedirectScope::default();
+    scope.set(&["example.com".to_string()]);
+    let action = decide(&scope, 10, 0, "https://tracker.net/next");
+    assert_eq!(action, RedirectAction::Stop);
+}
+
+#[test]
+fn test_decide_too_many_redirects() {
+    let scope = RedirectScope::default();
+    assert_eq!(
+        decide(&scope, 3, 2, "https://example.com/"),
+        RedirectAction::Follow
+    );
+    assert_eq!(
+        decide(&scope, 3, 3, "https://example.com/"),
+        RedirectAction::TooMany
+    );
+    assert_eq!(
+        decide(&scope, 0, 0, "https://example.com/"),
+        RedirectAction::TooMany
+    );
+}
@@ -0,0 +1,172 @@
+#[cfg(test)]
+use super::redirect::RedirectScope;
+#[cfg(test)]
+use super::replay::{self, guess_content_type, MirrorReplay, Replay, WarcReplay};
+#[cfg(test)]
+use super::FetchError;
+#[cfg(test)]
+use crate::warc::{format_request, format_response, WarcWriter};
+#[cfg(test)]
+use std::path::Path;
+
+#[cfg(test)]
+fn mirror(name: &str) -> std::path::PathBuf {
+    let root = std::env::temp_dir().join(name);
+    let _ = std::fs::remove_dir_all(&root);
+    std::fs::create_dir_all(root.join("example.com/dir")).unwrap();
+    std::fs::write(root.join("example.com/index.html"), "<img src=\"a.png\">").unwrap();
+    std::fs::write(root.join("example.com/a.png"), [0x89, b'P', b'N', b'G']).unwrap();
+    std::fs::write(root.join("example.com/dir/index.html"), "dir").unwrap();
+    std::fs::write(root.join("example.com/page?id=2"), "page 2").unwrap();
+    std::fs::write(root.join("example.com/page"), "page").unwrap();
+    root
+}
+
+#[test]
+fn test_mirror_replay() {
+    let root = mirror("spider_test_mirror");
+    let replay = MirrorReplay::new(root.to_str().unwrap());
+    let index = replay.fetch("http://example.com/").unwrap();
+    assert_eq!(index.status, 200);
+    assert_eq!(index.content_type, "text/html");
+    assert_eq!(index.body, b"<img src=\"a.png\">");
+    assert_eq!(
+        replay
+            .fetch("http://example.com/a.png")
+            .unwrap()
+            .content_type,
+        "image/png"
+    );
+    assert_eq!(replay.fetch("http://example.com/dir").unwrap().body, b"dir");
+    assert_eq!(
+        replay.fetch("http://example.com/dir/").unwrap().body,
+        b"dir"
+    );
+    assert_eq!(
+        replay.fetch("http://example.com/page?id=2").unwrap().body,
+        b"page 2"
+    );
+    assert_eq!(
+        replay.fetch("http://example.com/page?id=3").unwrap().body,
+        b"page"
+    );
+    assert!(replay.fetch("http://example.com/missing.png").is_none());
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn test_mirror_replay_without_host() {
+    let root = mirror("spider_test_mirror_flat");
+    let replay = MirrorReplay::new(root.join("example.com").to_str().unwrap());
+    assert_eq!(
+        replay.fetch("http://example.com/dir/").unwrap().body,
+        b"dir"
+    );
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn test_guess_content_type() {
+    assert_eq!(guess_content_type(Path::new("a/b.JPG")), "image/jpeg");
+    assert_eq!(guess_content_type(Path::new("a/style.css")), "text/css");
+    assert_eq!(guess_content_type(Path::new("a/page")), "text/html");
+    assert_eq!(
+        guess_content_type(Path::new("a/file.bin")),
+        "application/octet-stream"
+    );
+}
+
+#[cfg(test)]
+fn warc_file(name: &str) -> String {
+    let responses = [
+        (
+            "http://example.com/",
+            "HTTP/1.1 301 Moved\r\nLocation: /home\r\n\r\n",
+        ),
+        (
+            "http://example.com/home",
+            "HTTP/1.1 200 OK\r\nContent-Type: text/html\r\n\r\nhome",
+        ),
+        ("http://example.com/home", "HTTP/1.1 200 OK\r\n\r\nlater"),
+        ("http://example.com/gone", "HTTP/1.1 404 Not Found\r\n\r\n"),
+        (
+            "http://example.com/out",
+            "HTTP/1.1 302 Found\r\nLocation: http://other.com/\r\n\r\n",
+        ),
+        (
+            "http://example.com/loop",
+            "HTTP/1.1 302 Found\r\nLocation: /loop\r\n\r\n",
+        ),
+    ];
+    let mut data = String::new();
+    for (uri, block) in responses {
+        data.push_str(&format!(
+            "WARC/1.1\r\nWARC-Type: response\r\nWARC-Target-URI: {uri}\r\nContent-Length: {}\r\n\r\n{block}\r\n\r\n",
+            block.len()
+        ));
+    }
+    let path = std::env::temp_dir().join(name);
+    std::fs::write(&path, data).unwrap();
+    path.to_str().unwrap().to_string()
+}
+
+#[test]
+fn test_warc_replay() {
+    let path = warc_file("spider_test_replay.warc");
+    let replay = WarcReplay::open(&path).unwrap();
+    let scope = RedirectScope::default();
+    scope.set(&["example.com".to_string()]);
+
+    let response = replay::get(&replay, &scope, 10, "http://example.com").unwrap();
+    assert_eq!(response.url, "http://example.com/home");
+    assert_eq!(response.content_type, "text/html");
+    assert_eq!(response.text, "home");
+    assert_eq!(
+        replay::get(&replay, &scope, 10, "http://example.com/gone").err(),
+        Some(FetchError::Status(404))
+    );
+    assert_eq!(
+        replay::get(&replay, &scope, 10, "http://example.com/missing").err(),
+        Some(FetchError::NotRecorded)
+    );
+    assert_eq!(
+        replay::get(&replay, &scope, 10, "http://example.com/out").err(),
+        Some(FetchError::Redirect("http://other.com/".to_string()))
+    );
+    assert_eq!(
+        replay::get(&replay, &scope, 10, "http://example.com/loop").err(),
+        Some(FetchError::TooManyRedirects)
+    );
+    assert!(replay::open(&path).is_ok());
+    assert!(replay::open("/nonexistent/spider").is_err());
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_warc_replay_ignores_head_responses() {
+    let path = std::env::temp_dir().join("spider_test_replay_head.warc.gz");
+    let path = path.to_str().unwrap();
+    let writer = WarcWriter::create(path).unwrap();
+    let url = "http://example.com/page";
+    let headers = [("Content-Type".to_string(), "text/html".to_string())];
+    writer
+        .write_exchange(
+            url,
+            &format_request("HEAD", url, &[]),
+            &format_response("HTTP/1.1", 200, "OK", &headers, b""),
+            None,
+        )
+        .unwrap();
+    writer
+        .write_exchange(
+            url,
+            &format_request("GET", url, &[]),
+            &format_response("HTTP/1.1", 200, "OK", &headers, b"page"),
+            None,
+        )
+        .unwrap();
+
+    let replay = WarcReplay::open(path).unwrap();
+    assert_eq!(replay.fetch(url).unwrap().body, b"page");
+    std::fs::remove_file(path).unwrap();
+}
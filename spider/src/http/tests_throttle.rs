@@ -0,0 +1,61 @@
+#[cfg(test)]
+use super::throttle::Throttle;
+#[cfg(test)]
+use super::{ClientOptions, SClient};
+#[cfg(test)]
+use std::sync::Arc;
+#[cfg(test)]
+use std::time::{Duration, Instant};
+
+#[test]
+fn test_first_request_is_not_delayed() {
+    let throttle = Throttle::new(Duration::from_secs(10));
+    let start = Instant::now();
+    throttle.wait();
+    assert!(start.elapsed() < Duration::from_secs(1));
+}
+
+#[test]
+fn test_requests_are_spaced() {
+    let throttle = Throttle::new(Duration::from_millis(50));
+    let start = Instant::now();
+    for _ in 0..4 {
+        throttle.wait();
+    }
+    assert!(start.elapsed() >= Duration::from_millis(150));
+}
+
+#[test]
+fn test_threads_share_the_delay() {
+    let throttle = Arc::new(Throttle::new(Duration::from_millis(50)));
+    let start = Instant::now();
+    let threads = (0..4)
+        .map(|_| {
+            let throttle = Arc::clone(&throttle);
+            std::thread::spawn(move || throttle.wait())
+        })
+        .collect::<Vec<_>>();
+    for thread in threads {
+        thread.join().unwrap();
+    }
+    assert!(start.elapsed() >= Duration::from_millis(150));
+}
+
+#[test]
+fn test_client_waits_before_sending() {
+    let client = SClient::new(&ClientOptions {
+        delay: Some(Duration::from_millis(50)),
+        ..ClientOptions::default()
+    })
+    .unwrap();
+    let start = Instant::now();
+    // Building requests does not wait, sending them does.
+    for _ in 0..4 {
+        let _ = client.get("http://example.com/");
+    }
+    assert!(start.elapsed() < Duration::from_millis(50));
+    for _ in 0..4 {
+        client.before_send();
+    }
+    assert!(start.elapsed() >= Duration::from_millis(150));
+}
@@ -0,0 +1,93 @@
+#[cfg(test)]
+use super::tls::{configure, TlsOptions};
+#[cfg(test)]
+use super::FetchError;
+#[cfg(test)]
+use reqwest::blocking::Client;
+
+#[cfg(test)]
+const TEST_CA_PEM: &str = "
+-----BEGIN CERTIFICATE-----
+MIIDJTCCAg2gAwIBAgIUP7elPxG6QVkcv2jS/BCR4Jm+62YwDQYJKoZIhvcNAQEL
+BQAwFDESMBAGA1UEAwwJbG9jYWxob3N0MB4XDTI2MTAxOTA1NDcyMVoXDTI2MTAy
+MDA1NDcyMVowFDESMBAGA1UEAwwJbG9jYWxob3N0MIIBIjANBgkqhkiG9w0BAQEF
+AAOCAQ8AMIIBCgKCAQEAnF95MCS2+wRSNWCKVB7Wwn7NSjb7iLF3pJZ+Rmy+E/iX
+2aUqKi2ObtpKZ31YjYpMTdKvJX6l+l6F9V2A9YA/JorhMOsEJhL+VE+TViIIXhK2
+T+N/mxdGHsirerjUjlpLlJxITLVd0NMTLe56mMJHTWsPy2a3+jFWczc8jGx6f+Z6
+KY5rbVePHwsemonSfuaV99lT4ggzJHOHYNKsk1gtLuidzqKo5Ug3fAfGVlctHGCp
+jPJ6mJQw6nFQFwPjYxRkGgcFEaunJNWFd0NY1D9H7mA6Kw2IMl17YVn89Ev5HKcp
+UA3u31BUHZEcgDhQdnAE0koN+YEtMPQSH8U7aesAewIDAQABo28wbTAdBgNVHQ4E
+FgQU7RSJZ0vCbwd8xmyZaoVxjoyOMVIwHwYDVR0jBBgwFoAU7RSJZ0vCbwd8xmyZ
+aoVxjoyOMVIwDwYDVR0TAQH/BAUwAwEB/zAaBgNVHREEEzARgglsb2NhbGhvc3SH
+BH8AAAEwDQYJKoZIhvcNAQELBQADggEBAFcArmBT1W50iWI0jUwA4xqsWa1JNXLf
+57a2IeCOgW+ZKNexfIL/SWSSTeGqfOtohLQo9JSpsLO6EBbc+FvuU87ZoCi0+iF4
+uOtwKE+2+rRg+uQnIcG63dOLBFKEjDwHIflK6CVESOB4ZehnDZ0UCUhK080+1MK1
+l7WtzSU6ymRsVCHi6GOXhbwjvqPWMzMPm9J87a4RQEFG9VjkNeIWO86c4TXJyh8a
+orYpLF0xp81SoopBVaK2Ks1tTmygp5yU4TLJQiYkNy8aC9srNnlBlaDljR/vo1S7
+asIXjOFW/mWeaPXzxS4+IUOdh1pvttSu2ys65TJw5IdRNc15hY5/U1A=
+-----END CERTIFICATE-----
+";
+
+#[cfg(test)]
+fn write_temp(name: &str, content: &str) -> String {
+    let path = std::env::temp_dir().join(name);
+    std::fs::write(&path, content).unwrap();
+    path.to_str().unwrap().to_string()
+}
+
+#[test]
+fn test_configure_without_options() {
+    assert!(configure(Client::builder(), &TlsOptions::default()).is_ok());
+}
+
+#[test]
+fn test_configure_with_ca_cert() {
+    let ca_cert = write_temp("spider_test_ca.pem", TEST_CA_PEM);
+    let options = TlsOptions {
+        ca_cert: Some(ca_cert.clone()),
+        ..Default::default()
+    };
+    let result = configure(Client::builder(), &options);
+    std::fs::remove_file(&ca_cert).unwrap();
+    assert!(result.is_ok());
+}
+
+#[test]
+fn test_configure_with_missing_ca_cert() {
+    let options = TlsOptions {
+        ca_cert: Some("/nonexistent/ca.pem".to_string()),
+        ..Default::default()
+    };
+    assert!(configure(Client::builder(), &options).is_err());
+}
+
+#[test]
+fn test_configure_with_invalid_ca_cert() {
+    let ca_cert = write_temp("spider_test_invalid_ca.pem", "not a certificate");
+    let options = TlsOptions {
+        ca_cert: Some(ca_cert.clone()),
+        ..Default::default()
+    };
+    let result = configure(Client::builder(), &options);
+    std::fs::remove_file(&ca_cert).unwrap();
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_configure_with_client_cert_without_key() {
+    let client_cert = write_temp("spider_test_client.pem", TEST_CA_PEM);
+    let options = TlsOptions {
+        client_cert: Some(client_cert.clone()),
+        ..Default::default()
+    };
+    let result = configure(Client::builder(), &options);
+    std::fs::remove_file(&client_cert).unwrap();
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_fetch_error_display() {
+    let err = FetchError::Tls("certificate verify failed".to_string());
+    assert_eq!(err.to_string(), "TLS error: certificate verify failed");
+    assert_eq!(FetchError::Status(404).to_string(), "HTTP status 404");
+}
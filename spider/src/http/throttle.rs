@@ -0,0 +1,32 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Minimum delay between two requests, shared by every thread of the crawl.
+pub struct Throttle {
+    pub delay: Duration,
+    next: Mutex<Option<Instant>>,
+}
+
+impl Throttle {
+    pub fn new(delay: Duration) -> Throttle {
+        Throttle {
+            delay,
+            next: Mutex::new(None),
+        }
+    }
+
+    /// Function that waits until the next request may be sent.
+    /// The lock is held while waiting, so the threads take turns.
+    pub fn wait(&self) {
+        let mut next = self.next.lock().unwrap();
+        let now = Instant::now();
+        let start = match *next {
+            Some(next) if next > now => {
+                std::thread::sleep(next - now);
+                next
+            }
+            _ => now,
+        };
+        *next = Some(start + self.delay);
+    }
+}
@@ -0,0 +1,89 @@
+use reqwest::blocking::ClientBuilder;
+use reqwest::tls::{Certificate, Identity};
+use std::error::Error;
+
+use crate::logging;
+
+/// TLS settings of the HTTP client.
+#[derive(Clone, Default)]
+pub struct TlsOptions {
+    pub ca_cert: Option<String>,
+    pub client_cert: Option<String>,
+    pub client_key: Option<String>,
+    pub insecure: bool,
+}
+
+/// Function that applies the TLS settings to the client builder.
+/// The CA bundle is trusted on top of the system roots.
+/// The client key must be a PKCS#8 PEM file, it defaults to the certificate
+/// file so a single PEM holding both the certificate and the key can be used.
+pub fn configure(mut builder: ClientBuilder, options: &TlsOptions) -> Result<ClientBuilder, ()> {
+    if let Some(ca_cert) = &options.ca_cert {
+        let pem = read_pem(ca_cert)?;
+        let certificates = Certificate::from_pem_bundle(&pem);
+        if certificates.is_err() || certificates.as_ref().unwrap().is_empty() {
+            logging::error(
+                &format!("No valid PEM certificate found in CA bundle: {ca_cert}"),
+                &[("path", ca_cert)],
+            );
+            return Err(());
+        }
+        for certificate in certificates.unwrap() {
+            builder = builder.add_root_certificate(certificate);
+        }
+    }
+    if let Some(client_cert) = &options.client_cert {
+        let cert = read_pem(client_cert)?;
+        let key = match &options.client_key {
+            Some(client_key) => read_pem(client_key)?,
+            None => cert.clone(),
+        };
+        let identity = Identity::from_pkcs8_pem(&cert, &key);
+        if identity.is_err() {
+            logging::error(
+                &format!(
+                    "Invalid client certificate or key (expected PEM, PKCS#8 key): {client_cert}"
+                ),
+                &[("path", client_cert)],
+            );
+            return Err(());
+        }
+        builder = builder.identity(identity.unwrap());
+    }
+    if options.insecure {
+        logging::warn(
+            "⚠️  WARNING: TLS certificate verification is DISABLED (--insecure).",
+            &[],
+        );
+        logging::warn("⚠️  WARNING: Any server can impersonate the crawled sites, do not use this on untrusted networks.", &[]);
+        builder = builder
+            .danger_accept_invalid_certs(true)
+            .danger_accept_invalid_hostnames(true);
+    }
+    Ok(builder)
+}
+
+fn read_pem(path: &str) -> Result<Vec<u8>, ()> {
+    let pem = std::fs::read(path);
+    if pem.is_err() {
+        logging::error(
+            &format!("Could not read PEM file: {path}"),
+            &[("path", path)],
+        );
+        return Err(());
+    }
+    Ok(pem.unwrap())
+}
+
+/// Function that check if a request failed during the TLS handshake.
+/// It returns the message of the underlying TLS error.
+pub fn tls_error_message(err: &reqwest::Error) -> Option<String> {
+    let mut source = err.source();
+    while let Some(err) = source {
+        if let Some(tls_err) = err.downcast_ref::<native_tls::Error>() {
+            return Some(tls_err.to_string());
+        }
+        source = err.source();
+    }
+    None
+}
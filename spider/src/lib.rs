@@ -0,0 +1,36 @@
+//! Spider downloads the images of a website.
+//!
+//! The crawler is configured with `Crawler::builder` and reports what it
+//! does through callbacks:
+//!
+//! ```no_run
+//! use spider::Crawler;
+//!
+//! let crawler = Crawler::builder("https://books.toscrape.com/")
+//!     .recursive(true)
+//!     .depth(2)
+//!     .output_dir("./data")
+//!     .concurrency(4)
+//!     .on_image_saved(|image| println!("{} -> {}", image.url, image.path))
+//!     .build()
+//!     .unwrap();
+//! let report = crawler.run().unwrap();
+//! println!("Found {} images", report.images.len());
+//! ```
+
+// The helpers report their failures themselves and return `Err(())`.
+#![allow(clippy::result_unit_err)]
+
+pub mod config;
+pub mod crawler;
+pub mod http;
+pub mod logging;
+pub mod parsing;
+#[cfg(feature = "render")]
+pub mod render;
+pub mod spider;
+pub mod storage;
+pub mod utils;
+pub mod warc;
+
+pub use crawler::{CrawlError, CrawlReport, Crawler, CrawlerBuilder};
@@ -0,0 +1,134 @@
+use colored::Colorize;
+use serde_json::{Map, Value};
+use std::io::IsTerminal;
+use std::sync::RwLock;
+
+pub mod progress;
+
+mod tests_logging;
+
+/// Importance of a message. Messages below the level of the logger are dropped.
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+pub enum Level {
+    Error,
+    Warn,
+    Info,
+    Debug,
+}
+
+impl Level {
+    /// Function that returns the level for a number of `-v` and a `-q` flag.
+    /// The default is `Warn`, so that only the progress bar and the problems are shown.
+    pub fn from_verbosity(verbose: u8, quiet: bool) -> Level {
+        match (quiet, verbose) {
+            (true, _) => Level::Error,
+            (false, 0) => Level::Warn,
+            (false, 1) => Level::Info,
+            (false, _) => Level::Debug,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Level::Error => "error",
+            Level::Warn => "warn",
+            Level::Info => "info",
+            Level::Debug => "debug",
+        }
+    }
+}
+
+/// How the messages are written to stderr.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Format {
+    /// One human readable line per message.
+    Text,
+    /// One JSON object per line, with the fields of the message.
+    Json,
+}
+
+struct Logger {
+    level: Level,
+    format: Format,
+}
+
+// Until `init` is called every message is written as text, as the crawler always did.
+static LOGGER: RwLock<Logger> = RwLock::new(Logger {
+    level: Level::Info,
+    format: Format::Text,
+});
+
+/// Function that sets the level and the format of the messages.
+pub fn init(level: Level, format: Format) {
+    let mut logger = LOGGER.write().unwrap();
+    logger.level = level;
+    logger.format = format;
+    if !std::io::stderr().is_terminal() {
+        colored::control::set_override(false);
+    }
+}
+
+pub fn enabled(level: Level) -> bool {
+    level <= LOGGER.read().unwrap().level
+}
+
+pub fn format() -> Format {
+    LOGGER.read().unwrap().format
+}
+
+pub fn error(message: &str, fields: &[(&str, &str)]) {
+    log(Level::Error, message, fields);
+}
+
+pub fn warn(message: &str, fields: &[(&str, &str)]) {
+    log(Level::Warn, message, fields);
+}
+
+pub fn info(message: &str, fields: &[(&str, &str)]) {
+    log(Level::Info, message, fields);
+}
+
+pub fn debug(message: &str, fields: &[(&str, &str)]) {
+    log(Level::Debug, message, fields);
+}
+
+/// Function that writes a message to stderr if its level is enabled.
+/// `fields` are only written in the JSON format, the text message already contains them.
+pub fn log(level: Level, message: &str, fields: &[(&str, &str)]) {
+    if !enabled(level) {
+        return;
+    }
+    let line = match format() {
+        Format::Text => format_text(level, message),
+        Format::Json => format_json(level, message, fields),
+    };
+    progress::println(&line);
+}
+
+/// Function that colors a text message after its level.
+pub fn format_text(level: Level, message: &str) -> String {
+    match level {
+        Level::Error => message.red().to_string(),
+        Level::Warn => message.yellow().to_string(),
+        Level::Info => message.to_string(),
+        Level::Debug => message.dimmed().to_string(),
+    }
+}
+
+/// Function that formats a message as a JSON line:
+/// `{"time":"...","level":"info","message":"...", ...fields}`.
+/// The emoji in front of the text message is left out.
+pub fn format_json(level: Level, message: &str, fields: &[(&str, &str)]) -> String {
+    let mut object = Map::new();
+    object.insert(
+        "time".to_string(),
+        Value::from(chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true)),
+    );
+    object.insert("level".to_string(), Value::from(level.name()));
+    let message = message.trim_start_matches(|c: char| !c.is_alphanumeric());
+    object.insert("message".to_string(), Value::from(message));
+    for (name, value) in fields {
+        object.insert(name.to_string(), Value::from(*value));
+    }
+    Value::Object(object).to_string()
+}
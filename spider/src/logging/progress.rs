@@ -0,0 +1,98 @@
+use indicatif::{HumanBytes, ProgressBar, ProgressDrawTarget, ProgressStyle};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// What the crawl has done so far, shown by the progress bar.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Snapshot {
+    pub pages_queued: usize,
+    pub pages_done: usize,
+    pub images_saved: usize,
+    pub bytes: u64,
+}
+
+static PAGES_QUEUED: AtomicUsize = AtomicUsize::new(0);
+static PAGES_DONE: AtomicUsize = AtomicUsize::new(0);
+static IMAGES_SAVED: AtomicUsize = AtomicUsize::new(0);
+static BYTES: AtomicU64 = AtomicU64::new(0);
+static BAR: Mutex<Option<(ProgressBar, Instant)>> = Mutex::new(None);
+
+/// Function that resets the counters and shows a progress bar on stderr
+/// until `finish` is called. Nothing is drawn when stderr is not a terminal.
+pub fn start() {
+    PAGES_QUEUED.store(0, Ordering::SeqCst);
+    PAGES_DONE.store(0, Ordering::SeqCst);
+    IMAGES_SAVED.store(0, Ordering::SeqCst);
+    BYTES.store(0, Ordering::SeqCst);
+    let bar = ProgressBar::with_draw_target(None, ProgressDrawTarget::stderr());
+    bar.set_style(ProgressStyle::with_template("{spinner} [{elapsed_precise}] {msg}").unwrap());
+    bar.enable_steady_tick(Duration::from_millis(100));
+    *BAR.lock().unwrap() = Some((bar, Instant::now()));
+    update();
+}
+
+/// Function that removes the progress bar.
+pub fn finish() {
+    if let Some((bar, _)) = BAR.lock().unwrap().take() {
+        bar.finish_and_clear();
+    }
+}
+
+pub fn snapshot() -> Snapshot {
+    Snapshot {
+        pages_queued: PAGES_QUEUED.load(Ordering::SeqCst),
+        pages_done: PAGES_DONE.load(Ordering::SeqCst),
+        images_saved: IMAGES_SAVED.load(Ordering::SeqCst),
+        bytes: BYTES.load(Ordering::SeqCst),
+    }
+}
+
+/// Function that counts pages about to be crawled.
+pub fn pages_queued(count: usize) {
+    PAGES_QUEUED.fetch_add(count, Ordering::SeqCst);
+    update();
+}
+
+/// Function that counts a queued page as done, crawled or skipped.
+pub fn page_done() {
+    PAGES_DONE.fetch_add(1, Ordering::SeqCst);
+    update();
+}
+
+pub fn image_saved() {
+    IMAGES_SAVED.fetch_add(1, Ordering::SeqCst);
+    update();
+}
+
+pub fn add_bytes(bytes: usize) {
+    BYTES.fetch_add(bytes as u64, Ordering::SeqCst);
+    update();
+}
+
+/// Function that writes a line to stderr above the progress bar.
+pub fn println(line: &str) {
+    match &*BAR.lock().unwrap() {
+        Some((bar, _)) if !bar.is_hidden() => bar.println(line),
+        _ => eprintln!("{line}"),
+    }
+}
+
+/// Function that formats the message of the progress bar.
+pub fn format_message(snapshot: &Snapshot, elapsed: Duration) -> String {
+    let rate = snapshot.bytes as f64 / elapsed.as_secs_f64().max(1.0);
+    format!(
+        "pages {}/{} · images {} · {} · {}/s",
+        snapshot.pages_done,
+        snapshot.pages_queued,
+        snapshot.images_saved,
+        HumanBytes(snapshot.bytes),
+        HumanBytes(rate as u64)
+    )
+}
+
+fn update() {
+    if let Some((bar, start)) = &*BAR.lock().unwrap() {
+        bar.set_message(format_message(&snapshot(), start.elapsed()));
+    }
+}
@@ -0,0 +1,62 @@
+#[cfg(test)]
+use super::progress::{format_message, Snapshot};
+#[cfg(test)]
+use super::{format_json, Level};
+#[cfg(test)]
+use std::time::Duration;
+
+#[test]
+fn test_level_from_verbosity() {
+    assert_eq!(Level::from_verbosity(0, false), Level::Warn);
+    assert_eq!(Level::from_verbosity(1, false), Level::Info);
+    assert_eq!(Level::from_verbosity(3, false), Level::Debug);
+    assert_eq!(Level::from_verbosity(0, true), Level::Error);
+    assert_eq!(Level::from_verbosity(2, true), Level::Error);
+    assert!(Level::Error < Level::Warn && Level::Info < Level::Debug);
+}
+
+#[test]
+fn test_format_json() {
+    let line = format_json(
+        Level::Info,
+        "🖼️ Downloaded image: https://example.com/a.png -> data/a.png",
+        &[("url", "https://example.com/a.png"), ("path", "data/a.png")],
+    );
+    assert!(!line.contains('\n'));
+    let value: serde_json::Value = serde_json::from_str(&line).unwrap();
+    assert_eq!(value["level"], "info");
+    assert_eq!(
+        value["message"],
+        "Downloaded image: https://example.com/a.png -> data/a.png"
+    );
+    assert_eq!(value["url"], "https://example.com/a.png");
+    assert_eq!(value["path"], "data/a.png");
+    assert!(value["time"].as_str().unwrap().ends_with('Z'));
+}
+
+#[test]
+fn test_format_json_escapes() {
+    let line = format_json(Level::Warn, "quote \" and\nnew line", &[]);
+    assert!(!line.contains('\n'));
+    let value: serde_json::Value = serde_json::from_str(&line).unwrap();
+    assert_eq!(value["level"], "warn");
+    assert_eq!(value["message"], "quote \" and\nnew line");
+}
+
+#[test]
+fn test_progress_message() {
+    let snapshot = Snapshot {
+        pages_queued: 12,
+        pages_done: 5,
+        images_saved: 40,
+        bytes: 3 * 1024 * 1024,
+    };
+    assert_eq!(
+        format_message(&snapshot, Duration::from_secs(3)),
+        "pages 5/12 · images 40 · 3.00 MiB · 1.00 MiB/s"
+    );
+    assert_eq!(
+        format_message(&Snapshot::default(), Duration::ZERO),
+        "pages 0/0 · images 0 · 0 B · 0 B/s"
+    );
+}
@@ -1,77 +1,138 @@
-use clap::{command, value_parser, Arg};
-use std::collections::HashSet;
+use clap::error::ErrorKind;
+use indicatif::HumanBytes;
+use spider::crawler::CrawlError;
+use spider::crawler::CrawlStats;
+use spider::http::FetchError;
+use spider::logging::{self, progress};
+use spider::CrawlReport;
+use std::process::ExitCode;
 
-mod utils;
-use utils::{
-    can_create_folder,
-    check_url,
-};
+mod cli;
 
-mod spider;
-use spider::SRequest;
+/// Exit code of a crawl where some pages or images failed, or that could not run.
+const EXIT_PARTIAL: u8 = 1;
+/// Exit code of a seed URL that could not be reached, or of a failed login.
+const EXIT_SEED: u8 = 3;
+/// Exit code of an output that could not be written.
+const EXIT_OUTPUT: u8 = 4;
 
-mod parsing;
+fn main() -> ExitCode {
+    let matches = cli::get_matches();
+    if matches.get_flag("print-config") {
+        cli::print_config(&matches);
+        return ExitCode::SUCCESS;
+    }
+    cli::init_logging(&matches);
+    let code = run(&matches);
+    progress::finish();
+    ExitCode::from(code)
+}
 
-fn main() {
-    let matches = command!()
-        .version("1.0")
-        .author("hboissel")
-        .about("Downloads images from a website")
-        .arg(
-            Arg::new("URL")
-                .required(true)
-                .index(1)
-                .help("The URL to download images from"),
-        )
-        .arg(
-            Arg::new("recursive")
-                .short('r')
-                .long("recursive")
-                .default_value("false")
-                .num_args(0..=1)
-                .require_equals(true)
-                .default_missing_value("true")
-                .value_parser(value_parser!(bool))
-                .help("Recursively download images"),
-        )
-        .arg(
-            Arg::new("level")
-                .short('l')
-                .long("level")
-                .default_value("5")
-                .value_parser(value_parser!(u16))
-                .help("Maximum depth level for recursive download"),
-        )
-        .arg(
-            Arg::new("path")
-                .short('p')
-                .long("path")
-                .default_value("./data")
-                .help("Path to save downloaded files"),
-        )
-        .get_matches();
+fn run(matches: &clap::ArgMatches) -> u8 {
+    // Settings that can not be used are reported like the other command line errors.
+    let builder = match cli::builder_from_matches(matches) {
+        Ok(builder) => builder,
+        Err(err) => usage_error(&err),
+    };
+    let crawler = match builder.build() {
+        Ok(crawler) => crawler,
+        Err(err) => usage_error(&err.to_string()),
+    };
+    if matches.get_flag("list") {
+        return match crawler.list() {
+            Ok(links) => {
+                cli::print_page_links(&links);
+                0
+            }
+            Err(err) => {
+                logging::error(&format!("❌ {err}"), &[]);
+                exit_code(&err)
+            }
+        };
+    }
+    match crawler.run() {
+        Ok(report) if matches.get_flag("dry-run") => {
+            progress::finish();
+            cli::print_found_images(&report.found_images);
+            if let Some(path) = matches.get_one::<String>("export") {
+                if let Err(err) = cli::export_found_images(path, &report.found_images) {
+                    logging::error(&format!("❌ {err}"), &[]);
+                    return EXIT_OUTPUT;
+                }
+            }
+            print_budget_hit(&report);
+            println!("📝 Would download {} images", report.images.len());
+            print_summary(matches, &report.stats);
+            exit_code_of_stats(&report.stats)
+        }
+        Ok(report) => {
+            progress::finish();
+            print_budget_hit(&report);
+            println!("🟢 Found {} images", report.images.len());
+            print_summary(matches, &report.stats);
+            exit_code_of_stats(&report.stats)
+        }
+        Err(err @ CrawlError::Seed(_, FetchError::Tls(_))) => {
+            logging::error(&format!("🔒 {err}"), &[]);
+            exit_code(&err)
+        }
+        Err(err) => {
+            logging::error(&format!("❌ {err}"), &[]);
+            exit_code(&err)
+        }
+    }
+}
 
-    let url: &String = matches.get_one::<String>("URL").unwrap();
-    let recu: &bool = matches.get_one::<bool>("recursive").unwrap();
-    let path: &String = matches.get_one::<String>("path").unwrap();
-    let mut level: &u16 = matches.get_one::<u16>("level").unwrap();
+/// Function that returns the exit code of a crawl that could not run.
+fn exit_code(err: &CrawlError) -> u8 {
+    match err {
+        CrawlError::Seed(_, _) | CrawlError::Login(_) => EXIT_SEED,
+        CrawlError::Output(_, _) => EXIT_OUTPUT,
+        _ => EXIT_PARTIAL,
+    }
+}
 
-    if !can_create_folder(path) {
-        return;
+/// Function that returns the exit code of a finished crawl.
+fn exit_code_of_stats(stats: &CrawlStats) -> u8 {
+    if stats.has_failures() {
+        return EXIT_PARTIAL;
     }
-    if !check_url(url) {
-        println!("❌ Cannot access URL: {url}");
+    0
+}
+
+/// Function that prints the counts of the crawl, as a JSON object with `--log-format json`.
+fn print_summary(matches: &clap::ArgMatches, stats: &CrawlStats) {
+    let json = matches
+        .get_one::<String>("log-format")
+        .is_some_and(|format| format == "json");
+    if json {
+        println!("{}", serde_json::to_string(stats).unwrap());
         return;
     }
+    println!(
+        "📄 Pages: {} fetched, {} failed",
+        stats.pages_fetched, stats.pages_failed
+    );
+    println!(
+        "🖼️ Images: {} saved, {} skipped, {} failed",
+        stats.images_saved, stats.images_skipped, stats.images_failed
+    );
+    println!("📦 Downloaded {}", HumanBytes(stats.bytes));
+}
 
-    if !recu {
-        level = &0;
-    }
+/// Function that prints a usage error the way clap does and exits with code 2.
+fn usage_error(message: &str) -> ! {
+    progress::finish();
+    cli::command()
+        .error(ErrorKind::ValueValidation, message)
+        .exit()
+}
 
-    let mut visited_urls: HashSet<String> = HashSet::new();
-    let mut collected_images: Vec<String> = Vec::new();
-    let mut spider = SRequest::new(url, *recu, *level, path);
-    spider.get_all_image_links(&mut visited_urls, &mut collected_images);
-    println!("🟢 Found {} images", collected_images.len());
-    // println!("{collected_images:?}");
+fn print_budget_hit(report: &CrawlReport) {
+    if let Some(limit) = report.budget_hit {
+        logging::warn(
+            &format!("⏹️ Stopped early: the {limit} was reached"),
+            &[("limit", &format!("{limit:?}").to_lowercase())],
+        );
+    }
 }
@@ -0,0 +1,100 @@
+use regex::Regex;
+use std::path::Path;
+use url::Url;
+
+/// URLs found in a stylesheet.
+#[derive(Debug, Default, PartialEq)]
+pub struct CssLinks {
+    /// Stylesheets pulled in with `@import`.
+    pub imports: Vec<String>,
+    /// Everything referenced with `url()` or `image-set()` that may be an image.
+    pub images: Vec<String>,
+}
+
+/// Extensions of the `url()` targets that are never images.
+const NOT_IMAGES: [&str; 7] = ["css", "woff", "woff2", "ttf", "otf", "eot", "js"];
+
+/// Function that extracts the imported stylesheets and the image URLs of a stylesheet.
+/// Comments are ignored, as well as `data:` URIs and references to SVG fragments (`url(#id)`).
+pub fn extract(css: &str) -> CssLinks {
+    let comment_re = Regex::new(r"(?s)/\*.*?\*/").unwrap();
+    let import_re =
+        Regex::new(r#"(?i)@import\s+(?:url\(\s*["']?([^"')]+)["']?\s*\)|["']([^"']+)["'])"#)
+            .unwrap();
+    let url_re = Regex::new(r#"(?i)url\(\s*["']?([^"')]+?)["']?\s*\)"#).unwrap();
+    let image_set_re = Regex::new(r"(?i)(?:-webkit-)?image-set\(").unwrap();
+    let type_re = Regex::new(r"(?i)type\([^)]*\)").unwrap();
+    let string_re = Regex::new(r#""([^"]+)"|'([^']+)'"#).unwrap();
+
+    let css = comment_re.replace_all(css, "");
+    let mut links = CssLinks::default();
+    for caps in import_re.captures_iter(&css) {
+        if let Some(link) = caps.get(1).or_else(|| caps.get(2)) {
+            links.imports.push(link.as_str().trim().to_string());
+        }
+    }
+    let without_imports = import_re.replace_all(&css, "");
+    for caps in url_re.captures_iter(&without_imports) {
+        links.images.push(caps[1].trim().to_string());
+    }
+    // `image-set()` also accepts plain strings instead of `url()`.
+    for start in image_set_re.find_iter(&without_imports) {
+        let arguments = &without_imports[start.end()..];
+        let mut depth = 1;
+        let end = arguments
+            .char_indices()
+            .find(|(_, c)| {
+                match c {
+                    '(' => depth += 1,
+                    ')' => depth -= 1,
+                    _ => {}
+                }
+                depth == 0
+            })
+            .map(|(index, _)| index)
+            .unwrap_or(arguments.len());
+        let arguments = url_re.replace_all(&arguments[..end], "");
+        let arguments = type_re.replace_all(&arguments, "");
+        for caps in string_re.captures_iter(&arguments) {
+            if let Some(link) = caps.get(1).or_else(|| caps.get(2)) {
+                links.images.push(link.as_str().trim().to_string());
+            }
+        }
+    }
+    links.images.retain(|link| {
+        !link.is_empty()
+            && !link.starts_with('#')
+            && !link.to_lowercase().starts_with("data:")
+            && !Path::new(link.split(['?', '#']).next().unwrap_or(""))
+                .extension()
+                .is_some_and(|ext| {
+                    NOT_IMAGES
+                        .iter()
+                        .any(|not_image| ext.eq_ignore_ascii_case(not_image))
+                })
+    });
+    links.imports.sort();
+    links.imports.dedup();
+    links.images.sort();
+    links.images.dedup();
+    links
+}
+
+/// Function that resolves links relative to the URL of the stylesheet they come from.
+/// Links that can not be resolved are dropped.
+pub fn resolve(base_url: &str, links: &[String]) -> Vec<String> {
+    let base_url = Url::parse(base_url);
+    if base_url.is_err() {
+        return Vec::new();
+    }
+    let base_url = base_url.unwrap();
+    links
+        .iter()
+        .filter_map(|link| base_url.join(link).ok())
+        .filter(|link| link.scheme() == "http" || link.scheme() == "https")
+        .map(|mut link| {
+            link.set_fragment(None);
+            link.to_string()
+        })
+        .collect()
+}
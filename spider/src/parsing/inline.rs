@@ -0,0 +1,118 @@
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use regex::Regex;
+use sha2::{Digest, Sha256};
+
+/// An image embedded in a page instead of being linked.
+#[derive(Clone, Debug, PartialEq)]
+pub struct InlineImage {
+    pub content_type: String,
+    pub data: Vec<u8>,
+}
+
+impl InlineImage {
+    /// Function that names the image after the hash of its content,
+    /// so the same image embedded on several pages is saved once.
+    pub fn filename(&self) -> String {
+        let hash = hex::encode(Sha256::digest(&self.data));
+        format!("{}.{}", &hash[..16], extension(&self.content_type))
+    }
+}
+
+/// Function that extracts the `data:image/...` URIs of a page or a stylesheet.
+pub fn extract_data_uris(text: &str) -> Vec<String> {
+    let data_uri_re =
+        Regex::new(r#"(?i)data:image/[a-z0-9.+-]+(?:;[a-z0-9=._-]+)*,[^"'()\s]+"#).unwrap();
+    let mut uris = data_uri_re
+        .find_iter(text)
+        .map(|uri| uri.as_str().to_string())
+        .collect::<Vec<String>>();
+    uris.sort();
+    uris.dedup();
+    uris
+}
+
+/// Function that decodes a `data:` URI, base64 or percent-encoded.
+/// Only images are decoded.
+pub fn decode_data_uri(uri: &str) -> Option<InlineImage> {
+    let (header, payload) = uri.trim().strip_prefix("data:")?.split_once(',')?;
+    let mut parameters = header.split(';');
+    let content_type = parameters.next()?.trim().to_lowercase();
+    if !content_type.starts_with("image/") {
+        return None;
+    }
+    let data = if parameters.any(|parameter| parameter.eq_ignore_ascii_case("base64")) {
+        let payload = payload
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .collect::<String>();
+        STANDARD.decode(percent_decode(&payload)).ok()?
+    } else {
+        percent_decode(payload)
+    };
+    if data.is_empty() {
+        return None;
+    }
+    Some(InlineImage { content_type, data })
+}
+
+/// Function that extracts the `<svg>` elements of a page as standalone SVG images.
+pub fn extract_svgs(html: &str) -> Vec<InlineImage> {
+    let svg_re = Regex::new(r"(?is)<svg\b.*?</svg\s*>").unwrap();
+    let open_tag_re = Regex::new(r"(?is)^(<svg)\b[^>]*>").unwrap();
+    svg_re
+        .find_iter(html)
+        .map(|svg| {
+            let svg = svg.as_str();
+            let open_tag = open_tag_re.captures(svg);
+            let has_namespace = open_tag
+                .as_ref()
+                .is_some_and(|tag| tag[0].to_lowercase().contains("xmlns="));
+            // Inline SVG does not need a namespace, a standalone file does.
+            // It goes right after the tag name, whatever its case.
+            let svg = match open_tag.and_then(|tag| tag.get(1)) {
+                Some(name) if !has_namespace => format!(
+                    "{} xmlns=\"http://www.w3.org/2000/svg\"{}",
+                    &svg[..name.end()],
+                    &svg[name.end()..]
+                ),
+                _ => svg.to_string(),
+            };
+            InlineImage {
+                content_type: "image/svg+xml".to_string(),
+                data: svg.into_bytes(),
+            }
+        })
+        .collect()
+}
+
+fn percent_decode(input: &str) -> Vec<u8> {
+    let bytes = input.as_bytes();
+    let mut output = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        if bytes[index] == b'%' && index + 2 < bytes.len() {
+            let hex = std::str::from_utf8(&bytes[index + 1..index + 3]).unwrap_or("");
+            if let Ok(byte) = u8::from_str_radix(hex, 16) {
+                output.push(byte);
+                index += 3;
+                continue;
+            }
+        }
+        output.push(bytes[index]);
+        index += 1;
+    }
+    output
+}
+
+fn extension(content_type: &str) -> &str {
+    match content_type {
+        "image/jpeg" | "image/jpg" => "jpg",
+        "image/svg+xml" => "svg",
+        "image/x-icon" | "image/vnd.microsoft.icon" => "ico",
+        _ => content_type
+            .strip_prefix("image/")
+            .filter(|subtype| subtype.chars().all(|c| c.is_ascii_alphanumeric()))
+            .unwrap_or("img"),
+    }
+}
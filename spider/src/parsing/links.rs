@@ -1,7 +1,9 @@
 use regex::Regex;
+use sha2::{Digest, Sha256};
 use std::path::Path;
 
 use super::domain;
+use crate::logging;
 
 pub fn extract_all(html: &str) -> Vec<String> {
     let mut links = Vec::new();
@@ -48,6 +50,8 @@ pub fn extract_all(html: &str) -> Vec<String> {
             links.push(link.as_str().to_string());
         }
     }
+    // Embedded images are handled by `inline`, they are not links.
+    links.retain(|link| !link.trim_start().to_lowercase().starts_with("data:"));
     links.sort();
     links.dedup();
     links
@@ -58,19 +62,19 @@ pub fn extract_image(html: &str) -> Vec<String> {
     links.retain(|link| {
         Path::new(&link)
             .extension()
-            .map_or(false, |ext| ext.eq_ignore_ascii_case("jpg"))
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("jpg"))
             || Path::new(&link)
                 .extension()
-                .map_or(false, |ext| ext.eq_ignore_ascii_case("jpeg"))
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("jpeg"))
             || Path::new(&link)
                 .extension()
-                .map_or(false, |ext| ext.eq_ignore_ascii_case("png"))
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("png"))
             || Path::new(&link)
                 .extension()
-                .map_or(false, |ext| ext.eq_ignore_ascii_case("gif"))
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("gif"))
             || Path::new(&link)
                 .extension()
-                .map_or(false, |ext| ext.eq_ignore_ascii_case("bmp"))
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("bmp"))
     });
     links.sort();
     links.dedup();
@@ -82,19 +86,19 @@ pub fn extract_not_image(html: &str) -> Vec<String> {
     links.retain(|link| {
         !Path::new(&link)
             .extension()
-            .map_or(false, |ext| ext.eq_ignore_ascii_case("jpg"))
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("jpg"))
             && !Path::new(&link)
                 .extension()
-                .map_or(false, |ext| ext.eq_ignore_ascii_case("jpeg"))
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("jpeg"))
             && !Path::new(&link)
                 .extension()
-                .map_or(false, |ext| ext.eq_ignore_ascii_case("png"))
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("png"))
             && !Path::new(&link)
                 .extension()
-                .map_or(false, |ext| ext.eq_ignore_ascii_case("gif"))
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("gif"))
             && !Path::new(&link)
                 .extension()
-                .map_or(false, |ext| ext.eq_ignore_ascii_case("bmp"))
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("bmp"))
     });
     links = links
         .iter()
@@ -105,25 +109,245 @@ pub fn extract_not_image(html: &str) -> Vec<String> {
     links
 }
 
-/// Function that clean a url by removing the fragment and query
-/// and remove index.[extention] from the url
+/// What a link points to, guessed from its scheme and its extension.
+#[derive(Debug, PartialEq)]
+pub enum LinkKind {
+    /// An HTML document, or a path without extension.
+    Page,
+    /// A script, stylesheet, font, media file, document or archive.
+    Asset,
+    /// An extension that may or may not be served as HTML (`.cgi`, `.do`...).
+    Unknown,
+    /// A link that is not fetched over HTTP (`mailto:`, `javascript:`...).
+    Ignored,
+}
+
+const PAGE_EXTENSIONS: [&str; 8] = ["html", "htm", "xhtml", "shtml", "php", "asp", "aspx", "jsp"];
+
+const ASSET_EXTENSIONS: [&str; 45] = [
+    "js", "mjs", "css", "map", "json", "xml", "rss", "atom", "txt", "csv", "woff", "woff2", "ttf",
+    "otf", "eot", "jpg", "jpeg", "png", "gif", "bmp", "webp", "svg", "ico", "avif", "mp3", "mp4",
+    "webm", "ogg", "wav", "avi", "mov", "pdf", "doc", "docx", "xls", "xlsx", "ppt", "pptx", "zip",
+    "gz", "tar", "rar", "7z", "exe", "dmg",
+];
+
+/// Function that classifies a link from its scheme and the extension of its path.
+pub fn classify(link: &str) -> LinkKind {
+    let link = link.trim();
+    let lowercase = link.to_lowercase();
+    if let Some((scheme, _)) = lowercase.split_once(':') {
+        let is_scheme = scheme
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '+' || c == '-' || c == '.');
+        if is_scheme && scheme != "http" && scheme != "https" {
+            return LinkKind::Ignored;
+        }
+    }
+    if link.is_empty() || link.starts_with('#') {
+        return LinkKind::Ignored;
+    }
+    let path = lowercase.split(['?', '#']).next().unwrap_or("");
+    // Only the path counts, not the host of an absolute link.
+    let path = match path.split_once("//") {
+        Some((_, rest)) => rest.split_once('/').map(|(_, path)| path).unwrap_or(""),
+        None => path,
+    };
+    let last_segment = path.rsplit('/').next().unwrap_or("");
+    match last_segment.rsplit_once('.') {
+        None => LinkKind::Page,
+        Some((_, extension)) if PAGE_EXTENSIONS.contains(&extension) => LinkKind::Page,
+        Some((_, extension)) if ASSET_EXTENSIONS.contains(&extension) => LinkKind::Asset,
+        Some(_) => LinkKind::Unknown,
+    }
+}
+
+/// Function that extracts the links that may lead to other pages:
+/// the targets of `<a>`, `<area>`, `<iframe>` and `<frame>` tags.
+/// Scripts, stylesheets and assets are left out, so are `mailto:` or `javascript:` links.
+pub fn extract_pages(html: &str) -> Vec<String> {
+    let mut links = Vec::new();
+    let a_tag_re = Regex::new(r#"(?i)<(?:a|area)\s[^>]*?\bhref\s*=\s*["']([^"']*)["']"#).unwrap();
+    let frame_tag_re =
+        Regex::new(r#"(?i)<(?:iframe|frame)\s[^>]*?\bsrc\s*=\s*["']([^"']*)["']"#).unwrap();
+    for caps in a_tag_re.captures_iter(html) {
+        if let Some(link) = caps.get(1) {
+            links.push(link.as_str().trim().to_string());
+        }
+    }
+    for caps in frame_tag_re.captures_iter(html) {
+        if let Some(link) = caps.get(1) {
+            links.push(link.as_str().trim().to_string());
+        }
+    }
+    links.retain(|link| matches!(classify(link), LinkKind::Page | LinkKind::Unknown));
+    links = links
+        .iter()
+        .map(|link| clean_url(link))
+        .collect::<Vec<String>>();
+    links.sort();
+    links.dedup();
+    links
+}
+
+/// Function that returns the href of the `<link rel="canonical">` tag of a page.
+pub fn extract_canonical(html: &str) -> Option<String> {
+    let link_tag_re = Regex::new(r#"(?i)<link\s[^>]*>"#).unwrap();
+    let rel_re = Regex::new(r#"(?i)\srel\s*=\s*["']?canonical["'\s/>]"#).unwrap();
+    let href_re = Regex::new(r#"(?i)\shref\s*=\s*["']([^"']*)["']"#).unwrap();
+    for tag in link_tag_re.find_iter(html) {
+        let tag = tag.as_str();
+        if !rel_re.is_match(tag) {
+            continue;
+        }
+        if let Some(caps) = href_re.captures(tag) {
+            let href = caps[1].trim();
+            if !href.is_empty() {
+                return Some(href.to_string());
+            }
+        }
+    }
+    None
+}
+
+/// Function that returns the href of the `<link rel="stylesheet">` tags of a page.
+pub fn extract_stylesheets(html: &str) -> Vec<String> {
+    let link_tag_re = Regex::new(r#"(?i)<link\s[^>]*>"#).unwrap();
+    let rel_re = Regex::new(r#"(?i)\srel\s*=\s*["']?([^"'>]*)"#).unwrap();
+    let href_re = Regex::new(r#"(?i)\shref\s*=\s*["']([^"']*)["']"#).unwrap();
+    let mut stylesheets = Vec::new();
+    for tag in link_tag_re.find_iter(html) {
+        let tag = tag.as_str();
+        let is_stylesheet = rel_re.captures(tag).is_some_and(|caps| {
+            caps[1]
+                .split_whitespace()
+                .any(|rel| rel.eq_ignore_ascii_case("stylesheet"))
+        });
+        if !is_stylesheet {
+            continue;
+        }
+        if let Some(caps) = href_re.captures(tag) {
+            let href = caps[1].trim();
+            if !href.is_empty() {
+                stylesheets.push(clean_url(href));
+            }
+        }
+    }
+    stylesheets.sort();
+    stylesheets.dedup();
+    stylesheets
+}
+
+/// Links of a page sorted by kind, as shown by `--list`.
+/// Every link is resolved against the page URL, except the ignored ones.
+#[derive(Debug, Default, PartialEq)]
+pub struct PageLinks {
+    pub url: String,
+    pub canonical: Option<String>,
+    /// Links that are followed when crawling recursively.
+    pub pages: Vec<String>,
+    /// Links checked with a `HEAD` request before they are followed.
+    pub unknown: Vec<String>,
+    pub images: Vec<String>,
+    pub stylesheets: Vec<String>,
+    /// Scripts, fonts, documents... which are never downloaded.
+    pub assets: Vec<String>,
+    /// `mailto:`, `javascript:`, fragments, images with `..` in their path...
+    pub ignored: Vec<String>,
+    /// Number of images embedded as `data:` URIs.
+    pub inline_images: usize,
+}
+
+/// Function that extracts the links of a page the way the crawler sees them,
+/// sorted by kind.
+pub fn list(page_url: &str, html: &str) -> PageLinks {
+    let resolve = |links: Vec<String>| {
+        let mut links = add_start_url(page_url, &links);
+        links.sort();
+        links.dedup();
+        links
+    };
+    let stylesheets = resolve(extract_stylesheets(html));
+    let raw_images = extract_image(html);
+    let images = resolve(remove_double_dots(&raw_images));
+    let mut pages = Vec::new();
+    let mut unknown = Vec::new();
+    for link in resolve(extract_pages(html)) {
+        if stylesheets.contains(&link) {
+            continue;
+        }
+        match classify(&link) {
+            LinkKind::Unknown => unknown.push(link),
+            _ => pages.push(link),
+        }
+    }
+    let href_re = Regex::new(r#"(?i)<(?:a|area)\s[^>]*?\bhref\s*=\s*["']([^"']*)["']"#).unwrap();
+    let mut raw_links = extract_all(html);
+    raw_links.extend(
+        href_re
+            .captures_iter(html)
+            .map(|caps| caps[1].trim().to_string()),
+    );
+    let mut assets = Vec::new();
+    let mut ignored = Vec::new();
+    for link in raw_links {
+        if raw_images.contains(&link) {
+            // Images with `..` in their path are never downloaded.
+            if link.contains("..") {
+                ignored.push(link);
+            }
+            continue;
+        }
+        match classify(&link) {
+            LinkKind::Ignored => ignored.push(link),
+            LinkKind::Asset => assets.push(clean_url(&link)),
+            _ => {}
+        }
+    }
+    let mut assets = resolve(assets);
+    assets.retain(|link| !stylesheets.contains(link));
+    ignored.sort();
+    ignored.dedup();
+    PageLinks {
+        url: page_url.to_string(),
+        canonical: extract_canonical(html)
+            .map(|canonical| add_start_url(page_url, &vec![canonical]).remove(0)),
+        pages,
+        unknown,
+        images,
+        stylesheets,
+        assets,
+        ignored,
+        inline_images: super::inline::extract_data_uris(html).len(),
+    }
+}
+
+/// Function that clean a url by removing the fragment
+/// and remove index.[extention] from the url.
+/// The query is kept since it can identify a different page (`?page=2`),
+/// duplicates are handled by `normalize::normalize` instead.
 pub fn clean_url(url: &str) -> String {
-    let url = url.to_string();
     let url = url.split('#').collect::<Vec<&str>>()[0].to_string();
-    let url = url.split('?').collect::<Vec<&str>>()[0].to_string();
+    let (url, query) = match url.split_once('?') {
+        Some((url, query)) => (url.to_string(), Some(query.to_string())),
+        None => (url, None),
+    };
     let url = if url.ends_with("index.html") {
         url.split('/').collect::<Vec<&str>>()[0..url.split('/').collect::<Vec<&str>>().len() - 1]
             .join("/")
     } else {
         url
     };
-    url
+    match query {
+        Some(query) if !query.is_empty() => format!("{url}?{query}"),
+        _ => url,
+    }
 }
 
 /// Function that add the domain to the links using the `base_url`
 pub fn add_start_url(base_url: &str, links: &Vec<String>) -> Vec<String> {
     let mut links_full = Vec::new();
     let base_url = clean_url(base_url);
+    let base_url = base_url.split('?').collect::<Vec<&str>>()[0].to_string();
     let base_url = if base_url.ends_with('/') {
         base_url
     } else {
@@ -131,7 +355,10 @@ pub fn add_start_url(base_url: &str, links: &Vec<String>) -> Vec<String> {
     };
     for link in links {
         let mut link = link.to_string();
-        if link.starts_with("http://") || link.starts_with("https://") {
+        if link.starts_with("http://")
+            || link.starts_with("https://")
+            || link.to_lowercase().starts_with("data:")
+        {
             links_full.push(link);
         } else if link.starts_with("//") {
             link = format!("https:{link}");
@@ -163,6 +390,18 @@ pub fn filter_links_by_domain(domain: &str, links: &Vec<String>) -> Vec<String>
     links_from_domain
 }
 
+/// Function that remove all the links that are not from one of the domains of the scope
+pub fn filter_links_by_scope(scope: &[String], links: &Vec<String>) -> Vec<String> {
+    let mut links_in_scope = Vec::new();
+    for link in links {
+        let link_domain = domain::get(link).to_lowercase();
+        if !link_domain.is_empty() && scope.contains(&link_domain) {
+            links_in_scope.push(link.to_string());
+        }
+    }
+    links_in_scope
+}
+
 /// Function which takes a URL and returns the filename.
 /// The filename is the url path without the query string and fragment
 /// and / replaced with _
@@ -172,6 +411,10 @@ pub fn get_filename(url: &str) -> Result<String, ()> {
         return Err(());
     }
     let parsed_url = parsed_url.unwrap();
+    // `data:` and other non HTTP URLs have no file name.
+    if parsed_url.scheme() != "http" && parsed_url.scheme() != "https" {
+        return Err(());
+    }
     let mut path = parsed_url.path();
     if path.starts_with('/') {
         path = &path[1..];
@@ -180,6 +423,44 @@ pub fn get_filename(url: &str) -> Result<String, ()> {
     Ok(path)
 }
 
+/// Function which takes a URL and returns its path inside an archive:
+/// the host (and port) followed by the path of the URL.
+/// Empty, `.` and `..` segments are dropped so the entry can not escape
+/// the archive, and a path ending with `/` gets an `index` file name.
+/// A query is kept as a short hash before the extension (`a-1b2c3d4e.jpg`),
+/// so `a.jpg?w=100` and `a.jpg?w=800` are two entries.
+pub fn get_archive_path(url: &str) -> Result<String, ()> {
+    let parsed_url = url::Url::parse(url);
+    if parsed_url.is_err() {
+        return Err(());
+    }
+    let parsed_url = parsed_url.unwrap();
+    let host = parsed_url.host_str().ok_or(())?;
+    let mut segments = vec![match parsed_url.port() {
+        Some(port) => format!("{host}_{port}"),
+        None => host.to_string(),
+    }];
+    for segment in parsed_url.path().split('/') {
+        if !segment.is_empty() && segment != "." && segment != ".." {
+            segments.push(segment.to_string());
+        }
+    }
+    if segments.len() == 1 || parsed_url.path().ends_with('/') {
+        segments.push("index".to_string());
+    }
+    if let Some(query) = parsed_url.query().filter(|query| !query.is_empty()) {
+        let hash = hex::encode(Sha256::digest(query.as_bytes()));
+        let name = segments.pop().unwrap();
+        segments.push(match name.rsplit_once('.') {
+            Some((stem, extension)) if !stem.is_empty() => {
+                format!("{stem}-{}.{extension}", &hash[..8])
+            }
+            _ => format!("{name}-{}", &hash[..8]),
+        });
+    }
+    Ok(segments.join("/"))
+}
+
 /// Function that removes all the links with .. in the path
 pub fn remove_double_dots(links: &Vec<String>) -> Vec<String> {
     let mut links_filtered = Vec::new();
@@ -203,8 +484,8 @@ pub fn print_diff(links: &Vec<String>, links_domain: &[String]) {
     if diff.is_empty() {
         return;
     }
-    println!("🖾 Images found from other domain:");
+    logging::debug("🖾 Images found from other domain:", &[]);
     for link in diff {
-        println!("{link}");
+        logging::debug(link, &[("url", link)]);
     }
 }
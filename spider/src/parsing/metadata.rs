@@ -0,0 +1,96 @@
+use regex::Regex;
+use serde::Serialize;
+
+/// Context of an image, taken from the `<img>` element it was found in and its page.
+/// Images linked from elsewhere (CSS, `<source>`, links) only have the page title.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct ImageMetadata {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alt: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub width: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub height: Option<String>,
+    /// Text of the `<figcaption>` of the `<figure>` holding the image.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub figcaption: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page_title: Option<String>,
+}
+
+/// Function that returns the text of the `<title>` of a page.
+pub fn extract_title(html: &str) -> Option<String> {
+    let title_re = Regex::new(r#"(?is)<title(?:\s[^>]*)?>(.*?)</title>"#).unwrap();
+    title_re.captures(html).and_then(|caps| text(&caps[1]))
+}
+
+/// Function that returns the `src` of every `<img>` of a page with its metadata,
+/// in the order of the page. The `src` is as written, like `links::extract_image` returns it.
+pub fn extract(html: &str) -> Vec<(String, ImageMetadata)> {
+    let img_tag_re = Regex::new(r#"(?i)<img\s[^>]*>"#).unwrap();
+    let src_re = Regex::new(r#"(?i)\ssrc=["']([^"']*)["']"#).unwrap();
+    let figure_re = Regex::new(r#"(?is)<figure[\s>].*?</figure\s*>"#).unwrap();
+    let figcaption_re =
+        Regex::new(r#"(?is)<figcaption(?:\s[^>]*)?>(.*?)</figcaption\s*>"#).unwrap();
+    let page_title = extract_title(html);
+    let figures: Vec<_> = figure_re.find_iter(html).collect();
+    let mut images = Vec::new();
+    for tag in img_tag_re.find_iter(html) {
+        let src = match src_re.captures(tag.as_str()) {
+            Some(caps) => caps[1].to_string(),
+            None => continue,
+        };
+        let figcaption = figures
+            .iter()
+            .find(|figure| figure.start() < tag.start() && tag.end() <= figure.end())
+            .and_then(|figure| figcaption_re.captures(figure.as_str()))
+            .and_then(|caps| text(&caps[1]));
+        images.push((
+            src,
+            ImageMetadata {
+                alt: attribute(tag.as_str(), "alt"),
+                title: attribute(tag.as_str(), "title"),
+                width: attribute(tag.as_str(), "width"),
+                height: attribute(tag.as_str(), "height"),
+                figcaption,
+                page_title: page_title.clone(),
+            },
+        ));
+    }
+    images
+}
+
+/// Function that returns the value of an attribute of a tag, quoted or not.
+/// An empty value is `None`.
+pub fn attribute(tag: &str, name: &str) -> Option<String> {
+    let attribute_re = Regex::new(&format!(
+        r#"(?i)\s{}\s*=\s*(?:"([^"]*)"|'([^']*)'|([^\s"'>]+))"#,
+        regex::escape(name)
+    ))
+    .unwrap();
+    let caps = attribute_re.captures(tag)?;
+    let value = caps.get(1).or(caps.get(2)).or(caps.get(3))?;
+    text(value.as_str())
+}
+
+/// Function that turns an HTML fragment into plain text: the tags are removed,
+/// the common entities decoded and the whitespace collapsed.
+fn text(html: &str) -> Option<String> {
+    let tag_re = Regex::new(r#"<[^>]*>"#).unwrap();
+    let text = tag_re
+        .replace_all(html, " ")
+        .replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&");
+    let text = text.split_whitespace().collect::<Vec<&str>>().join(" ");
+    if text.is_empty() {
+        return None;
+    }
+    Some(text)
+}
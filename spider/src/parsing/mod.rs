@@ -1,5 +1,13 @@
+pub mod css;
 pub mod domain;
+pub mod inline;
 pub mod links;
+pub mod metadata;
+pub mod normalize;
 
+mod tests_css;
 mod tests_domain;
+mod tests_inline;
 mod tests_links;
+mod tests_metadata;
+mod tests_normalize;
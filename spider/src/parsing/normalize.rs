@@ -0,0 +1,115 @@
+use url::Url;
+
+/// Query parameters stripped by default because they only track visitors.
+/// A trailing `*` matches every parameter starting with the prefix.
+pub const DEFAULT_STRIP_PARAMS: &str =
+    "utm_*,fbclid,gclid,dclid,msclkid,mc_cid,mc_eid,_ga,_gl,yclid,igshid";
+
+/// Function that parses a comma separated list of query parameters to strip.
+pub fn parse_strip_params(params: &str) -> Vec<String> {
+    params
+        .split(',')
+        .map(|param| param.trim().to_lowercase())
+        .filter(|param| !param.is_empty())
+        .collect()
+}
+
+/// Function that check if a query parameter is in the strip list.
+pub fn is_stripped(name: &str, strip_params: &[String]) -> bool {
+    let name = name.to_lowercase();
+    strip_params
+        .iter()
+        .any(|param| match param.strip_suffix('*') {
+            Some(prefix) => name.starts_with(prefix),
+            None => name == *param,
+        })
+}
+
+/// Function that returns the normalized form of a URL, used as the key of the visited set.
+/// - scheme and host are lowercased and the default port is removed
+/// - `.` and `..` segments are resolved and the trailing `/` is removed
+/// - percent-encoded unreserved characters are decoded, other escapes are uppercased
+/// - the fragment is removed
+/// - the query parameters in `strip_params` are removed and the others are sorted
+///
+/// A URL that cannot be parsed is returned unchanged.
+pub fn normalize(url: &str, strip_params: &[String]) -> String {
+    let parsed_url = Url::parse(url.trim());
+    if parsed_url.is_err() {
+        return url.to_string();
+    }
+    let parsed_url = parsed_url.unwrap();
+    let host = match parsed_url.host_str() {
+        Some(host) => host,
+        None => return url.to_string(),
+    };
+
+    let mut normalized = format!("{}://", parsed_url.scheme());
+    if !parsed_url.username().is_empty() {
+        normalized.push_str(parsed_url.username());
+        if let Some(password) = parsed_url.password() {
+            normalized.push(':');
+            normalized.push_str(password);
+        }
+        normalized.push('@');
+    }
+    normalized.push_str(host);
+    if let Some(port) = parsed_url.port() {
+        normalized.push_str(&format!(":{port}"));
+    }
+
+    let path = normalize_percent_encoding(parsed_url.path());
+    let path = match path.strip_suffix('/') {
+        Some(path) if !path.is_empty() => path.to_string(),
+        _ => path,
+    };
+    normalized.push_str(&path);
+
+    if let Some(query) = parsed_url.query() {
+        let mut params = query
+            .split('&')
+            .filter(|param| !param.is_empty())
+            .map(normalize_percent_encoding)
+            .filter(|param| {
+                let name = param.split('=').next().unwrap_or("");
+                !is_stripped(name, strip_params)
+            })
+            .collect::<Vec<String>>();
+        params.sort();
+        if !params.is_empty() {
+            normalized.push('?');
+            normalized.push_str(&params.join("&"));
+        }
+    }
+    normalized
+}
+
+/// Function that decodes the percent-encoded unreserved characters
+/// and writes the remaining escapes with uppercase hexadecimal digits.
+pub fn normalize_percent_encoding(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut output: Vec<u8> = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        // from_str_radix accepts a sign, so both digits are checked first.
+        let is_escape = bytes[i] == b'%'
+            && i + 2 < bytes.len()
+            && bytes[i + 1].is_ascii_hexdigit()
+            && bytes[i + 2].is_ascii_hexdigit();
+        if is_escape {
+            let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).unwrap_or("");
+            if let Ok(byte) = u8::from_str_radix(hex, 16) {
+                if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) {
+                    output.push(byte);
+                } else {
+                    output.extend_from_slice(format!("%{byte:02X}").as_bytes());
+                }
+                i += 3;
+                continue;
+            }
+        }
+        output.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&output).to_string()
+}
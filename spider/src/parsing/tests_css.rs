@@ -0,0 +1,80 @@
+#[cfg(test)]
+use super::css::{self, CssLinks};
+
+#[test]
+fn test_extract_urls() {
+    let stylesheet = r#"
+        body { background: url(img/bg.png) no-repeat; }
+        .logo { background-image: url( "/img/logo.jpg" ); }
+        .icon { background: url('icons/icon.gif?v=2'); }
+        /* .old { background: url(img/old.png); } */
+        @font-face { src: url(fonts/font.woff2) format("woff2"), url(fonts/font.ttf); }
+        .inline { background: url("data:image/png;base64,iVBORw0KGgo="); }
+        .masked { filter: url(#blur); }
+    "#;
+    assert_eq!(
+        css::extract(stylesheet),
+        CssLinks {
+            imports: vec![],
+            images: vec![
+                "/img/logo.jpg".to_string(),
+                "icons/icon.gif?v=2".to_string(),
+                "img/bg.png".to_string(),
+            ],
+        }
+    );
+}
+
+#[test]
+fn test_extract_imports() {
+    let stylesheet = r#"
+        @import "base.css";
+        @import url(theme.css) screen;
+        @IMPORT url('/print.css') print;
+        .a { background: url(a.png); }
+    "#;
+    let links = css::extract(stylesheet);
+    assert_eq!(links.imports, vec!["/print.css", "base.css", "theme.css"]);
+    assert_eq!(links.images, vec!["a.png"]);
+}
+
+#[test]
+fn test_extract_image_set() {
+    let stylesheet = r#"
+        .hero { background-image: image-set("hero.png" 1x, "hero@2x.png" 2x); }
+        .logo { background-image: -webkit-image-set(url(logo.png) 1x, url('logo@2x.png') 2x); }
+        .type { background-image: image-set('photo.jpg' type("image/jpeg")); }
+    "#;
+    let links = css::extract(stylesheet);
+    assert_eq!(
+        links.images,
+        vec![
+            "hero.png",
+            "hero@2x.png",
+            "logo.png",
+            "logo@2x.png",
+            "photo.jpg",
+        ]
+    );
+}
+
+#[test]
+fn test_resolve() {
+    let links = vec![
+        "../img/bg.png".to_string(),
+        "icons/icon.gif#top".to_string(),
+        "/logo.jpg".to_string(),
+        "//cdn.example.com/a.png".to_string(),
+        "mailto:someone@example.com".to_string(),
+    ];
+    assert_eq!(
+        css::resolve("https://example.com/static/css/style.css?v=1", &links),
+        vec![
+            "https://example.com/static/img/bg.png",
+            "https://example.com/static/css/icons/icon.gif",
+            "https://example.com/logo.jpg",
+            "https://cdn.example.com/a.png",
+        ]
+    );
+    assert!(css::resolve("not a url", &links).is_empty());
+}
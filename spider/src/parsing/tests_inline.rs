@@ -0,0 +1,87 @@
+#[cfg(test)]
+use super::inline::{self, InlineImage};
+
+#[test]
+fn test_extract_data_uris() {
+    let html = r#"
+        <img src="data:image/png;base64,iVBORw0KGgo=" alt="dot">
+        <img src='data:image/svg+xml;charset=utf-8,%3Csvg%3E%3C/svg%3E'>
+        <div style="background: url(data:image/gif;base64,R0lGODlhAQABAAAAACw=)"></div>
+        <a href="data:text/plain,hello">text</a>
+        <img src="/image.png">
+    "#;
+    assert_eq!(
+        inline::extract_data_uris(html),
+        vec![
+            "data:image/gif;base64,R0lGODlhAQABAAAAACw=",
+            "data:image/png;base64,iVBORw0KGgo=",
+            "data:image/svg+xml;charset=utf-8,%3Csvg%3E%3C/svg%3E",
+        ]
+    );
+}
+
+#[test]
+fn test_decode_data_uri() {
+    assert_eq!(
+        inline::decode_data_uri("data:image/png;base64,iVBORw0KGgo="),
+        Some(InlineImage {
+            content_type: "image/png".to_string(),
+            data: vec![0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'],
+        })
+    );
+    assert_eq!(
+        inline::decode_data_uri("data:image/svg+xml;charset=utf-8,%3Csvg%3E%3C/svg%3E")
+            .unwrap()
+            .data,
+        b"<svg></svg>"
+    );
+    assert_eq!(inline::decode_data_uri("data:text/plain,hello"), None);
+    assert_eq!(inline::decode_data_uri("data:image/png;base64,***"), None);
+    assert_eq!(inline::decode_data_uri("data:image/png;base64,"), None);
+    assert_eq!(inline::decode_data_uri("https://example.com/a.png"), None);
+}
+
+#[test]
+fn test_inline_image_filename() {
+    let image = inline::decode_data_uri("data:image/jpeg;base64,/9j/4AAQ").unwrap();
+    let filename = image.filename();
+    assert!(filename.ends_with(".jpg"));
+    assert_eq!(filename.len(), 16 + 4);
+    assert_eq!(
+        inline::decode_data_uri("data:image/jpeg;base64,/9j/4AAQ")
+            .unwrap()
+            .filename(),
+        filename
+    );
+    let svg = InlineImage {
+        content_type: "image/svg+xml".to_string(),
+        data: b"<svg/>".to_vec(),
+    };
+    assert!(svg.filename().ends_with(".svg"));
+}
+
+#[test]
+fn test_extract_svgs() {
+    let html = r#"
+        <p>icons</p>
+        <svg width="10" height="10"><circle r="5"/></svg>
+        <SVG xmlns="http://www.w3.org/2000/svg" viewBox="0 0 1 1"><rect/></SVG>
+        <SVG viewBox="0 0 2 2"><rect/></SVG>
+    "#;
+    let svgs = inline::extract_svgs(html);
+    assert_eq!(svgs.len(), 3);
+    assert_eq!(
+        String::from_utf8(svgs[0].data.clone()).unwrap(),
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="10" height="10"><circle r="5"/></svg>"#
+    );
+    assert_eq!(
+        String::from_utf8(svgs[1].data.clone()).unwrap(),
+        r#"<SVG xmlns="http://www.w3.org/2000/svg" viewBox="0 0 1 1"><rect/></SVG>"#
+    );
+    // Without a namespace, it is added to an upper case tag too.
+    assert_eq!(
+        String::from_utf8(svgs[2].data.clone()).unwrap(),
+        r#"<SVG xmlns="http://www.w3.org/2000/svg" viewBox="0 0 2 2"><rect/></SVG>"#
+    );
+    assert_eq!(svgs[0].content_type, "image/svg+xml");
+}
@@ -1,5 +1,5 @@
 #[cfg(test)]
-use super::links;
+use super::links::{self, LinkKind};
 
 #[test]
 fn test_extract_image_links_with_no_images() {
@@ -152,6 +152,13 @@ fn test_extract_all_links() {
 fn test_clean_url() {
     let url = "https://example.com/page?query=1#fragment";
     let cleaned_url = links::clean_url(url);
+    assert_eq!(cleaned_url, "https://example.com/page?query=1");
+}
+
+#[test]
+fn test_clean_url_with_empty_query() {
+    let url = "https://example.com/page?#fragment";
+    let cleaned_url = links::clean_url(url);
     assert_eq!(cleaned_url, "https://example.com/page");
 }
 
@@ -166,7 +173,7 @@ fn test_clean_url_with_index() {
 fn test_clean_url_with_index_and_query_fragment() {
     let url = "https://example.com/index.html?query=1#fragment";
     let cleaned_url = links::clean_url(url);
-    assert_eq!(cleaned_url, "https://example.com");
+    assert_eq!(cleaned_url, "https://example.com?query=1");
 }
 
 #[test]
@@ -190,6 +197,21 @@ fn test_filter_links_by_domain() {
     assert!(filtered_links.contains(&"http://example.com/page3".to_string()));
 }
 
+#[test]
+fn test_filter_links_by_scope() {
+    let scope = vec!["example.com".to_string(), "cdn.example.com".to_string()];
+    let links = vec![
+        "https://example.com/page1".to_string(),
+        "https://cdn.example.com/image.jpg".to_string(),
+        "https://www.example.com/page2".to_string(),
+        "not a url".to_string(),
+    ];
+    let filtered_links = links::filter_links_by_scope(&scope, &links);
+    assert_eq!(filtered_links.len(), 2);
+    assert!(filtered_links.contains(&"https://example.com/page1".to_string()));
+    assert!(filtered_links.contains(&"https://cdn.example.com/image.jpg".to_string()));
+}
+
 #[test]
 fn test_add_start_url_to_links() {
     let start_url = "https://example.com";
@@ -227,13 +249,20 @@ fn test_add_start_url_to_links_with_path_in_url() {
     assert!(links.contains(&"https://example.com/page5".to_string()));
 }
 
+#[test]
+fn test_add_start_url_with_query_in_url() {
+    let start_url = "https://example.com/list?page=2";
+    let links = vec!["item".to_string(), "/other?page=3".to_string()];
+    let links = links::add_start_url(start_url, &links);
+    assert_eq!(links[0], "https://example.com/list/item");
+    assert_eq!(links[1], "https://example.com/other?page=3");
+}
+
 #[test]
 fn test_get_filename() {
     let url = "https://example.com/image.jpg".to_string();
     let filename = links::get_filename(&url);
-    if filename.is_err() {
-        assert!(false);
-    }
+    assert!(filename.is_ok());
     let filename = filename.unwrap();
     assert_eq!(filename, "image.jpg".to_string());
 }
@@ -242,9 +271,7 @@ fn test_get_filename() {
 fn test_get_filename_with_query() {
     let url = "https://example.com/image.jpg?query=1".to_string();
     let filename = links::get_filename(&url);
-    if filename.is_err() {
-        assert!(false);
-    }
+    assert!(filename.is_ok());
     let filename = filename.unwrap();
     assert_eq!(filename, "image.jpg".to_string());
 }
@@ -253,9 +280,198 @@ fn test_get_filename_with_query() {
 fn test_get_filename_long_path() {
     let url = "https://example.com/path/to/image.jpg".to_string();
     let filename = links::get_filename(&url);
-    if filename.is_err() {
-        assert!(false);
-    }
+    assert!(filename.is_ok());
     let filename = filename.unwrap();
     assert_eq!(filename, "path_to_image.jpg".to_string());
 }
+
+#[test]
+fn test_extract_canonical() {
+    let html_content = r#"
+        <head>
+            <link rel="stylesheet" href="styles.css">
+            <link rel="canonical" href="https://example.com/page">
+        </head>
+    "#;
+    let canonical = links::extract_canonical(html_content);
+    assert_eq!(canonical, Some("https://example.com/page".to_string()));
+}
+
+#[test]
+fn test_extract_canonical_href_before_rel() {
+    let html_content = r#"<LINK href='/page' REL=canonical />"#;
+    let canonical = links::extract_canonical(html_content);
+    assert_eq!(canonical, Some("/page".to_string()));
+}
+
+#[test]
+fn test_extract_canonical_missing() {
+    let html_content = r#"
+        <link rel="stylesheet" href="styles.css">
+        <link rel="canonical-ish" href="https://example.com/other">
+        <a rel="canonical" href="https://example.com/page">Link</a>
+    "#;
+    assert_eq!(links::extract_canonical(html_content), None);
+}
+
+#[test]
+fn test_get_archive_path() {
+    let path = links::get_archive_path("https://example.com/path/to/image.jpg");
+    assert_eq!(path, Ok("example.com/path/to/image.jpg".to_string()));
+}
+
+#[test]
+fn test_get_archive_path_with_query() {
+    let small = links::get_archive_path("https://example.com/a.jpg?w=100");
+    let large = links::get_archive_path("https://example.com/a.jpg?w=800");
+    assert_eq!(small, Ok("example.com/a-4af38b55.jpg".to_string()));
+    assert_eq!(large, Ok("example.com/a-51a9cc0c.jpg".to_string()));
+    let path = links::get_archive_path("https://example.com/images/?v=1");
+    assert_eq!(path, Ok("example.com/images/index-a798de8e".to_string()));
+    let path = links::get_archive_path("https://example.com/a.jpg?");
+    assert_eq!(path, Ok("example.com/a.jpg".to_string()));
+}
+
+#[test]
+fn test_get_archive_path_with_port_and_directory() {
+    let path = links::get_archive_path("http://localhost:8080/images/");
+    assert_eq!(path, Ok("localhost_8080/images/index".to_string()));
+    let path = links::get_archive_path("http://localhost:8080");
+    assert_eq!(path, Ok("localhost_8080/index".to_string()));
+}
+
+#[test]
+fn test_get_archive_path_cannot_escape() {
+    let path = links::get_archive_path("http://example.com/a/%2e%2e/..//b.jpg");
+    assert_eq!(path, Ok("example.com/b.jpg".to_string()));
+    assert!(links::get_archive_path("not a url").is_err());
+}
+
+#[test]
+fn test_extract_stylesheets() {
+    let html = r#"
+        <link rel="stylesheet" href="/css/main.css">
+        <link href="print.css#x" rel="alternate stylesheet" media="print">
+        <LINK REL=stylesheet HREF="theme.css">
+        <link rel="icon" href="/favicon.ico">
+        <link rel="canonical" href="https://example.com/">
+    "#;
+    assert_eq!(
+        links::extract_stylesheets(html),
+        vec!["/css/main.css", "print.css", "theme.css"]
+    );
+}
+
+#[test]
+fn test_classify() {
+    assert_eq!(links::classify("/about"), LinkKind::Page);
+    assert_eq!(links::classify("/about/"), LinkKind::Page);
+    assert_eq!(links::classify("https://example.com"), LinkKind::Page);
+    assert_eq!(links::classify("page.HTML?x=1.js"), LinkKind::Page);
+    assert_eq!(links::classify("/index.php#a.pdf"), LinkKind::Page);
+    assert_eq!(
+        links::classify("https://example.com/app.js"),
+        LinkKind::Asset
+    );
+    assert_eq!(links::classify("/static/main.css?v=3"), LinkKind::Asset);
+    assert_eq!(links::classify("report.PDF"), LinkKind::Asset);
+    assert_eq!(links::classify("/fonts/font.woff2"), LinkKind::Asset);
+    assert_eq!(links::classify("/archive.zip"), LinkKind::Asset);
+    assert_eq!(links::classify("/cgi-bin/search.cgi"), LinkKind::Unknown);
+    assert_eq!(
+        links::classify("mailto:someone@example.com"),
+        LinkKind::Ignored
+    );
+    assert_eq!(links::classify("javascript:void(0)"), LinkKind::Ignored);
+    assert_eq!(links::classify("tel:+33100000000"), LinkKind::Ignored);
+    assert_eq!(links::classify("#top"), LinkKind::Ignored);
+}
+
+#[test]
+fn test_extract_pages() {
+    let html = r#"
+        <a href="/about">about</a>
+        <a class="x" href='page.html#section'>page</a>
+        <area shape="rect" href="/map.php">
+        <iframe src="/embed/"></iframe>
+        <a href="/app.js">script</a>
+        <a href="mailto:someone@example.com">mail</a>
+        <link rel="stylesheet" href="/main.css">
+        <script src="/main.js"></script>
+        <img src="/image.png">
+        <a href="/search.cgi?q=1">search</a>
+    "#;
+    assert_eq!(
+        links::extract_pages(html),
+        vec![
+            "/about",
+            "/embed/",
+            "/map.php",
+            "/search.cgi?q=1",
+            "page.html"
+        ]
+    );
+}
+
+#[test]
+fn test_data_uris_are_not_links() {
+    let html = r#"<img src="data:image/png;base64,iVBORw0KGgo="><img src="/a.png">"#;
+    assert_eq!(links::extract_all(html), vec!["/a.png"]);
+    assert!(links::get_filename("data:image/png;base64,iVBORw0KGgo=").is_err());
+    assert_eq!(
+        links::add_start_url(
+            "https://example.com/",
+            &vec!["data:image/gif;base64,R0lG".to_string()]
+        ),
+        vec!["data:image/gif;base64,R0lG"]
+    );
+}
+
+#[test]
+fn test_list() {
+    let html = r##"
+        <link rel="canonical" href="/gallery">
+        <link rel="stylesheet" href="css/site.css">
+        <script src="/app.js"></script>
+        <a href="/about">about</a> <a href="page.html#top">page</a>
+        <a href="/form.do">form</a> <a href="#top">top</a>
+        <a href="javascript:void(0)">js</a> <a href="/files/report.pdf">report</a>
+        <img src="/img/a.png"> <img src="b.jpg"> <img src="../up.png">
+    "##;
+    let links = links::list("https://example.com/gallery/", html);
+    assert_eq!(
+        links.canonical,
+        Some("https://example.com/gallery".to_string())
+    );
+    assert_eq!(
+        links.pages,
+        vec![
+            "https://example.com/about",
+            "https://example.com/gallery/page.html"
+        ]
+    );
+    assert_eq!(links.unknown, vec!["https://example.com/form.do"]);
+    assert_eq!(
+        links.images,
+        vec![
+            "https://example.com/gallery/b.jpg",
+            "https://example.com/img/a.png"
+        ]
+    );
+    assert_eq!(
+        links.stylesheets,
+        vec!["https://example.com/gallery/css/site.css"]
+    );
+    assert_eq!(
+        links.assets,
+        vec![
+            "https://example.com/app.js",
+            "https://example.com/files/report.pdf"
+        ]
+    );
+    assert_eq!(
+        links.ignored,
+        vec!["#top", "../up.png", "javascript:void(0)"]
+    );
+    assert_eq!(links.inline_images, 0);
+}
@@ -0,0 +1,83 @@
+#[cfg(test)]
+use super::metadata::{self, ImageMetadata};
+
+#[test]
+fn test_extract_title() {
+    let html = "<html><head><TITLE>\n  Cats &amp; Dogs\n</TITLE></head></html>";
+    assert_eq!(
+        metadata::extract_title(html),
+        Some("Cats & Dogs".to_string())
+    );
+    assert_eq!(metadata::extract_title("<title> </title>"), None);
+    assert_eq!(metadata::extract_title("<body>no title</body>"), None);
+}
+
+#[test]
+fn test_extract() {
+    let html = r#"<html><head><title>Gallery</title></head><body>
+        <img src="/a.png" alt="A cat" title='Sleeping' width=640 height="480">
+        <figure class="photo">
+            <img src="b.jpg" alt="">
+            <figcaption>A <em>dog</em>
+                in the park</figcaption>
+        </figure>
+        <figure><img src="c.gif"></figure>
+        <img alt="no source">
+    </body></html>"#;
+    let page_title = Some("Gallery".to_string());
+    assert_eq!(
+        metadata::extract(html),
+        vec![
+            (
+                "/a.png".to_string(),
+                ImageMetadata {
+                    alt: Some("A cat".to_string()),
+                    title: Some("Sleeping".to_string()),
+                    width: Some("640".to_string()),
+                    height: Some("480".to_string()),
+                    figcaption: None,
+                    page_title: page_title.clone(),
+                }
+            ),
+            (
+                "b.jpg".to_string(),
+                ImageMetadata {
+                    figcaption: Some("A dog in the park".to_string()),
+                    page_title: page_title.clone(),
+                    ..ImageMetadata::default()
+                }
+            ),
+            (
+                "c.gif".to_string(),
+                ImageMetadata {
+                    page_title,
+                    ..ImageMetadata::default()
+                }
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_attribute() {
+    let tag = r#"<img data-alt="wrong" alt="Tom &quot;the cat&quot;" width=100>"#;
+    assert_eq!(
+        metadata::attribute(tag, "alt"),
+        Some("Tom \"the cat\"".to_string())
+    );
+    assert_eq!(metadata::attribute(tag, "width"), Some("100".to_string()));
+    assert_eq!(metadata::attribute(tag, "height"), None);
+}
+
+#[test]
+fn test_metadata_json() {
+    let metadata = ImageMetadata {
+        alt: Some("A cat".to_string()),
+        page_title: Some("Gallery".to_string()),
+        ..ImageMetadata::default()
+    };
+    assert_eq!(
+        serde_json::to_string(&metadata).unwrap(),
+        r#"{"alt":"A cat","page_title":"Gallery"}"#
+    );
+}
@@ -0,0 +1,129 @@
+#[cfg(test)]
+use super::normalize::{
+    is_stripped, normalize, normalize_percent_encoding, parse_strip_params, DEFAULT_STRIP_PARAMS,
+};
+
+#[cfg(test)]
+fn default_strip_params() -> Vec<String> {
+    parse_strip_params(DEFAULT_STRIP_PARAMS)
+}
+
+#[test]
+fn test_normalize_scheme_host_and_default_port() {
+    let strip = default_strip_params();
+    assert_eq!(
+        normalize("HTTP://Example.com:80/a/", &strip),
+        normalize("http://example.com/a", &strip)
+    );
+    assert_eq!(
+        normalize("HTTPS://WWW.Example.COM:443/a", &strip),
+        "https://www.example.com/a"
+    );
+}
+
+#[test]
+fn test_normalize_keeps_other_ports() {
+    let strip = default_strip_params();
+    assert_eq!(
+        normalize("http://example.com:8080/a", &strip),
+        "http://example.com:8080/a"
+    );
+}
+
+#[test]
+fn test_normalize_root() {
+    let strip = default_strip_params();
+    assert_eq!(
+        normalize("http://example.com", &strip),
+        "http://example.com/"
+    );
+    assert_eq!(
+        normalize("http://example.com/", &strip),
+        "http://example.com/"
+    );
+}
+
+#[test]
+fn test_normalize_dot_segments_and_fragment() {
+    let strip = default_strip_params();
+    assert_eq!(
+        normalize("http://example.com/a/./b/../c#section", &strip),
+        "http://example.com/a/c"
+    );
+}
+
+#[test]
+fn test_normalize_keeps_and_sorts_query() {
+    let strip = default_strip_params();
+    assert_eq!(
+        normalize("http://example.com/list?page=2&sort=asc", &strip),
+        normalize("http://example.com/list?sort=asc&page=2", &strip)
+    );
+    assert_ne!(
+        normalize("http://example.com/list?page=2", &strip),
+        normalize("http://example.com/list?page=3", &strip)
+    );
+}
+
+#[test]
+fn test_normalize_strips_tracking_params() {
+    let strip = default_strip_params();
+    assert_eq!(
+        normalize(
+            "http://example.com/a?utm_source=news&page=2&fbclid=abc&UTM_Medium=x",
+            &strip
+        ),
+        "http://example.com/a?page=2"
+    );
+    assert_eq!(
+        normalize("http://example.com/a?utm_source=news", &strip),
+        "http://example.com/a"
+    );
+}
+
+#[test]
+fn test_normalize_with_custom_strip_params() {
+    let strip = parse_strip_params("sessionid, ref");
+    assert_eq!(
+        normalize(
+            "http://example.com/a?ref=home&sessionid=1&utm_source=x",
+            &strip
+        ),
+        "http://example.com/a?utm_source=x"
+    );
+}
+
+#[test]
+fn test_normalize_percent_encoding() {
+    let strip = default_strip_params();
+    assert_eq!(
+        normalize("http://example.com/%7euser/a%2fb?q=%c3%a9", &strip),
+        "http://example.com/~user/a%2Fb?q=%C3%A9"
+    );
+    assert_eq!(
+        normalize("http://example.com/%41%42c", &strip),
+        normalize("http://example.com/ABc", &strip)
+    );
+}
+
+#[test]
+fn test_normalize_percent_encoding_invalid_escape() {
+    assert_eq!(normalize_percent_encoding("a%zzb%4"), "a%zzb%4");
+    assert_eq!(normalize_percent_encoding("%2d%2E"), "-.");
+    assert_eq!(normalize_percent_encoding("%+F%-1a"), "%+F%-1a");
+}
+
+#[test]
+fn test_normalize_invalid_url() {
+    let strip = default_strip_params();
+    assert_eq!(normalize("not a url", &strip), "not a url");
+}
+
+#[test]
+fn test_is_stripped() {
+    let strip = default_strip_params();
+    assert!(is_stripped("utm_campaign", &strip));
+    assert!(is_stripped("GCLID", &strip));
+    assert!(!is_stripped("page", &strip));
+    assert!(!is_stripped("utm", &strip));
+}
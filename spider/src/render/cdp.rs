@@ -0,0 +1,150 @@
+use serde_json::{json, Value};
+use std::collections::{HashSet, VecDeque};
+use std::io::ErrorKind;
+use std::net::TcpStream;
+use std::time::{Duration, Instant};
+use tungstenite::stream::MaybeTlsStream;
+use tungstenite::{Message, WebSocket};
+
+/// Connection to one browser target over the DevTools protocol.
+/// Events received while waiting for a response are kept for `next_event`.
+pub struct CdpSession {
+    socket: WebSocket<MaybeTlsStream<TcpStream>>,
+    next_id: u64,
+    events: VecDeque<Value>,
+}
+
+impl CdpSession {
+    pub fn connect(ws_url: &str) -> Result<CdpSession, String> {
+        let (mut socket, _) = tungstenite::connect(ws_url)
+            .map_err(|err| format!("cannot connect to {ws_url}: {err}"))?;
+        if let MaybeTlsStream::Plain(stream) = socket.get_mut() {
+            stream
+                .set_read_timeout(Some(Duration::from_millis(100)))
+                .map_err(|err| err.to_string())?;
+        }
+        Ok(CdpSession {
+            socket,
+            next_id: 1,
+            events: VecDeque::new(),
+        })
+    }
+
+    /// Function that sends a command and waits for its result.
+    pub fn call(
+        &mut self,
+        method: &str,
+        params: Value,
+        timeout: Duration,
+    ) -> Result<Value, String> {
+        let id = self.next_id;
+        self.next_id += 1;
+        let command = json!({ "id": id, "method": method, "params": params });
+        self.socket
+            .send(Message::Text(command.to_string().into()))
+            .map_err(|err| format!("{method}: {err}"))?;
+        let start = Instant::now();
+        while start.elapsed() < timeout {
+            let message = match self.read()? {
+                Some(message) => message,
+                None => continue,
+            };
+            if message["id"].as_u64() != Some(id) {
+                if message.get("method").is_some() {
+                    self.events.push_back(message);
+                }
+                continue;
+            }
+            if let Some(error) = message.get("error") {
+                return Err(format!("{method}: {}", error["message"]));
+            }
+            return Ok(message["result"].clone());
+        }
+        Err(format!("{method}: no response from the browser"))
+    }
+
+    /// Function that returns the next event, or `None` if none arrived for a moment.
+    pub fn next_event(&mut self) -> Result<Option<Value>, String> {
+        if let Some(event) = self.events.pop_front() {
+            return Ok(Some(event));
+        }
+        Ok(self
+            .read()?
+            .filter(|message| message.get("method").is_some()))
+    }
+
+    fn read(&mut self) -> Result<Option<Value>, String> {
+        match self.socket.read() {
+            Ok(Message::Text(text)) => Ok(serde_json::from_str(text.as_str()).ok()),
+            Ok(Message::Close(_)) => Err("the browser closed the connection".to_string()),
+            Ok(_) => Ok(None),
+            Err(tungstenite::Error::Io(err))
+                if err.kind() == ErrorKind::WouldBlock || err.kind() == ErrorKind::TimedOut =>
+            {
+                Ok(None)
+            }
+            Err(err) => Err(err.to_string()),
+        }
+    }
+}
+
+/// State of a page load, built from the `Page` and `Network` events.
+/// The page is idle once it has fired `load` and no request was in flight for a while.
+pub struct PageTracker {
+    in_flight: HashSet<String>,
+    pub images: Vec<String>,
+    pub loaded: bool,
+    last_activity: Instant,
+}
+
+impl PageTracker {
+    pub fn new(now: Instant) -> PageTracker {
+        PageTracker {
+            in_flight: HashSet::new(),
+            images: Vec::new(),
+            loaded: false,
+            last_activity: now,
+        }
+    }
+
+    pub fn handle(&mut self, event: &Value, now: Instant) {
+        let params = &event["params"];
+        let request_id = params["requestId"].as_str().unwrap_or("").to_string();
+        match event["method"].as_str().unwrap_or("") {
+            "Network.requestWillBeSent" => {
+                self.in_flight.insert(request_id);
+                if params["type"] == "Image" {
+                    self.add_image(params["request"]["url"].as_str());
+                }
+            }
+            "Network.responseReceived" => {
+                let is_image = params["type"] == "Image"
+                    || params["response"]["mimeType"]
+                        .as_str()
+                        .is_some_and(|mime_type| mime_type.starts_with("image/"));
+                if is_image {
+                    self.add_image(params["response"]["url"].as_str());
+                }
+            }
+            "Network.loadingFinished" | "Network.loadingFailed" => {
+                self.in_flight.remove(&request_id);
+            }
+            "Page.loadEventFired" => self.loaded = true,
+            _ => return,
+        }
+        self.last_activity = now;
+    }
+
+    pub fn is_idle(&self, now: Instant, idle: Duration) -> bool {
+        self.loaded && self.in_flight.is_empty() && now.duration_since(self.last_activity) >= idle
+    }
+
+    fn add_image(&mut self, url: Option<&str>) {
+        if let Some(url) = url {
+            let is_http = url.starts_with("http://") || url.starts_with("https://");
+            if is_http && !self.images.iter().any(|image| image == url) {
+                self.images.push(url.to_string());
+            }
+        }
+    }
+}
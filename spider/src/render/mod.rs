@@ -0,0 +1,197 @@
+use serde_json::{json, Value};
+use std::io::{BufRead, BufReader};
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+use std::sync::{mpsc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+pub mod cdp;
+
+mod tests_cdp;
+
+use crate::logging;
+use cdp::{CdpSession, PageTracker};
+
+/// Settings of the headless browser used to render the pages.
+#[derive(Clone, Debug)]
+pub struct RenderOptions {
+    /// Chromium or Chrome executable (default is `$SPIDER_BROWSER`, then `chromium`).
+    pub browser: String,
+    /// How long the network must stay quiet after `load` for the page to be done.
+    pub idle: Duration,
+    /// Maximum time spent on one page.
+    pub timeout: Duration,
+}
+
+impl Default for RenderOptions {
+    fn default() -> RenderOptions {
+        RenderOptions {
+            browser: std::env::var("SPIDER_BROWSER").unwrap_or_else(|_| "chromium".to_string()),
+            idle: Duration::from_millis(500),
+            timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+/// A page as the browser built it.
+pub struct Rendered {
+    /// The DOM serialized after the scripts ran.
+    pub html: String,
+    /// Images the page loaded, including the ones added by scripts.
+    pub images: Vec<String>,
+}
+
+/// Headless Chromium driven over the DevTools protocol.
+/// Every page is loaded in a new tab, closed once rendered.
+pub struct Renderer {
+    pub options: RenderOptions,
+    endpoint: String,
+    process: Mutex<Child>,
+    profile: PathBuf,
+    client: reqwest::blocking::Client,
+}
+
+impl Renderer {
+    /// Function that starts the browser and waits for its DevTools endpoint.
+    pub fn launch(options: RenderOptions) -> Result<Renderer, String> {
+        let client = reqwest::blocking::Client::builder()
+            .no_proxy()
+            .build()
+            .map_err(|err| err.to_string())?;
+        let profile = std::env::temp_dir().join(format!("spider-render-{}", std::process::id()));
+        let child = Command::new(&options.browser)
+            .args([
+                "--headless=new",
+                "--disable-gpu",
+                "--no-first-run",
+                "--no-default-browser-check",
+                "--remote-debugging-port=0",
+                &format!("--user-data-dir={}", profile.display()),
+                "about:blank",
+            ])
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn();
+        if let Err(err) = child {
+            return Err(format!("cannot start {}: {err}", options.browser));
+        }
+        let mut child = child.unwrap();
+
+        // The browser prints its endpoint on stderr; the pipe is drained until it exits.
+        let stderr = child.stderr.take().unwrap();
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+                if let Some(ws_url) = line.split("DevTools listening on ").nth(1) {
+                    let _ = sender.send(ws_url.trim().to_string());
+                }
+            }
+        });
+        let endpoint = receiver
+            .recv_timeout(Duration::from_secs(20))
+            .ok()
+            .and_then(|ws_url| http_endpoint(&ws_url));
+        if endpoint.is_none() {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(format!(
+                "{} did not open a DevTools endpoint",
+                options.browser
+            ));
+        }
+        logging::info(
+            &format!("🖥️ Rendering pages with {}", options.browser),
+            &[("browser", &options.browser)],
+        );
+        Ok(Renderer {
+            options,
+            endpoint: endpoint.unwrap(),
+            process: Mutex::new(child),
+            profile,
+            client,
+        })
+    }
+
+    /// Function that loads a page in a new tab and returns its rendered DOM.
+    pub fn render(&self, url: &str) -> Result<Rendered, String> {
+        let target = self
+            .client
+            .put(format!("{}/json/new?about:blank", self.endpoint))
+            .send()
+            .and_then(|response| response.text())
+            .map_err(|err| format!("cannot open a tab: {err}"))?;
+        let target: Value = serde_json::from_str(&target).map_err(|err| err.to_string())?;
+        let ws_url = target["webSocketDebuggerUrl"]
+            .as_str()
+            .ok_or("the browser did not return a tab")?;
+        let rendered = CdpSession::connect(ws_url)
+            .and_then(|mut session| load_page(&mut session, url, &self.options));
+        if let Some(id) = target["id"].as_str() {
+            let _ = self
+                .client
+                .get(format!("{}/json/close/{id}", self.endpoint))
+                .send();
+        }
+        rendered
+    }
+}
+
+impl Drop for Renderer {
+    fn drop(&mut self) {
+        let mut process = self.process.lock().unwrap();
+        let _ = process.kill();
+        let _ = process.wait();
+        let _ = std::fs::remove_dir_all(&self.profile);
+    }
+}
+
+/// Function that navigates a tab to the URL, waits for the network to be idle
+/// and returns the DOM with the images the page requested.
+pub fn load_page(
+    session: &mut CdpSession,
+    url: &str,
+    options: &RenderOptions,
+) -> Result<Rendered, String> {
+    let command_timeout = Duration::from_secs(10);
+    session.call("Network.enable", json!({}), command_timeout)?;
+    session.call("Page.enable", json!({}), command_timeout)?;
+    let start = Instant::now();
+    let mut tracker = PageTracker::new(start);
+    session.call("Page.navigate", json!({ "url": url }), command_timeout)?;
+    while !tracker.is_idle(Instant::now(), options.idle) {
+        if start.elapsed() >= options.timeout {
+            logging::warn(
+                &format!("⏱️ Rendering timed out, using the page as it is: {url}"),
+                &[("url", url)],
+            );
+            break;
+        }
+        if let Some(event) = session.next_event()? {
+            tracker.handle(&event, Instant::now());
+        }
+    }
+    let result = session.call(
+        "Runtime.evaluate",
+        json!({ "expression": "document.documentElement.outerHTML", "returnByValue": true }),
+        command_timeout,
+    )?;
+    let html = result["result"]["value"]
+        .as_str()
+        .ok_or("the page has no DOM")?
+        .to_string();
+    Ok(Rendered {
+        html,
+        images: tracker.images,
+    })
+}
+
+/// Function that turns the browser WebSocket URL printed at startup
+/// (`ws://127.0.0.1:9222/devtools/browser/<id>`) into its HTTP endpoint.
+pub fn http_endpoint(ws_url: &str) -> Option<String> {
+    let address = ws_url.strip_prefix("ws://")?.split('/').next()?;
+    if address.is_empty() {
+        return None;
+    }
+    Some(format!("http://{address}"))
+}
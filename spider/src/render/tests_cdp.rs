@@ -0,0 +1,142 @@
+#[cfg(test)]
+use super::cdp::{CdpSession, PageTracker};
+#[cfg(test)]
+use super::{http_endpoint, load_page, RenderOptions};
+#[cfg(test)]
+use serde_json::{json, Value};
+#[cfg(test)]
+use std::net::TcpListener;
+#[cfg(test)]
+use std::time::{Duration, Instant};
+#[cfg(test)]
+use tungstenite::Message;
+
+#[test]
+fn test_http_endpoint() {
+    assert_eq!(
+        http_endpoint("ws://127.0.0.1:40123/devtools/browser/0b1c-22"),
+        Some("http://127.0.0.1:40123".to_string())
+    );
+    assert_eq!(http_endpoint("http://127.0.0.1:40123/"), None);
+    assert_eq!(http_endpoint("ws:///devtools"), None);
+}
+
+#[test]
+fn test_page_tracker() {
+    let start = Instant::now();
+    let idle = Duration::from_millis(500);
+    let mut tracker = PageTracker::new(start);
+    let event = |method: &str, params: Value| json!({ "method": method, "params": params });
+
+    tracker.handle(
+        &event(
+            "Network.requestWillBeSent",
+            json!({ "requestId": "1", "type": "Document", "request": { "url": "https://example.com/" } }),
+        ),
+        start,
+    );
+    tracker.handle(
+        &event(
+            "Network.requestWillBeSent",
+            json!({ "requestId": "2", "type": "Image", "request": { "url": "https://example.com/a.png" } }),
+        ),
+        start,
+    );
+    tracker.handle(
+        &event(
+            "Network.responseReceived",
+            json!({ "requestId": "3", "type": "Fetch", "response": { "url": "https://example.com/b", "mimeType": "image/webp" } }),
+        ),
+        start,
+    );
+    tracker.handle(
+        &event(
+            "Network.requestWillBeSent",
+            json!({ "requestId": "4", "type": "Image", "request": { "url": "data:image/png;base64,AAAA" } }),
+        ),
+        start,
+    );
+    tracker.handle(&event("Page.loadEventFired", json!({})), start);
+    assert!(!tracker.is_idle(start + idle, idle));
+    for request_id in ["1", "2"] {
+        tracker.handle(
+            &event(
+                "Network.loadingFinished",
+                json!({ "requestId": request_id }),
+            ),
+            start,
+        );
+    }
+    tracker.handle(
+        &event("Network.loadingFailed", json!({ "requestId": "4" })),
+        start,
+    );
+    assert!(!tracker.is_idle(start + Duration::from_millis(100), idle));
+    assert!(tracker.is_idle(start + idle, idle));
+    assert_eq!(
+        tracker.images,
+        vec!["https://example.com/a.png", "https://example.com/b"]
+    );
+}
+
+/// A DevTools endpoint answering like a tab loading a page with one script-added image.
+#[cfg(test)]
+fn mock_tab() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    std::thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut socket = tungstenite::accept(stream).unwrap();
+        loop {
+            let message = match socket.read() {
+                Ok(Message::Text(text)) => text,
+                _ => return,
+            };
+            let command: Value = serde_json::from_str(message.as_str()).unwrap();
+            let id = command["id"].clone();
+            let mut replies = vec![];
+            match command["method"].as_str().unwrap() {
+                "Page.navigate" => {
+                    replies.push(json!({ "method": "Network.requestWillBeSent", "params": { "requestId": "1", "type": "Document", "request": { "url": "http://example.com/" } } }));
+                    replies.push(json!({ "id": id, "result": { "frameId": "F" } }));
+                    replies.push(json!({ "method": "Network.loadingFinished", "params": { "requestId": "1" } }));
+                    replies.push(json!({ "method": "Network.requestWillBeSent", "params": { "requestId": "2", "type": "Image", "request": { "url": "http://example.com/js.png" } } }));
+                    replies.push(json!({ "method": "Page.loadEventFired", "params": {} }));
+                    replies.push(json!({ "method": "Network.loadingFinished", "params": { "requestId": "2" } }));
+                }
+                "Runtime.evaluate" => replies.push(json!({ "id": id, "result": { "result": { "type": "string", "value": "<html><body><img src=\"js.png\"></body></html>" } } })),
+                "Browser.crash" => replies.push(json!({ "id": id, "error": { "code": -1, "message": "not allowed" } })),
+                _ => replies.push(json!({ "id": id, "result": {} })),
+            }
+            for reply in replies {
+                socket
+                    .send(Message::Text(reply.to_string().into()))
+                    .unwrap();
+            }
+        }
+    });
+    format!("ws://{address}/devtools/page/1")
+}
+
+#[test]
+fn test_cdp_session_error() {
+    let mut session = CdpSession::connect(&mock_tab()).unwrap();
+    let result = session.call("Browser.crash", json!({}), Duration::from_secs(5));
+    assert_eq!(result, Err("Browser.crash: \"not allowed\"".to_string()));
+}
+
+#[test]
+fn test_load_page() {
+    let mut session = CdpSession::connect(&mock_tab()).unwrap();
+    let options = RenderOptions {
+        browser: String::new(),
+        idle: Duration::from_millis(50),
+        timeout: Duration::from_secs(5),
+    };
+    let rendered = load_page(&mut session, "http://example.com/", &options).unwrap();
+    assert_eq!(
+        rendered.html,
+        "<html><body><img src=\"js.png\"></body></html>"
+    );
+    assert_eq!(rendered.images, vec!["http://example.com/js.png"]);
+}
@@ -1,10 +1,22 @@
+use super::crawler::{CrawlConfig, ImageFound, PageFetched};
+use super::http::FetchError;
+use super::logging::{self, progress};
+use super::parsing::css;
 use super::parsing::domain;
+use super::parsing::inline::{self, InlineImage};
 use super::parsing::links;
-use super::utils::{download_images, get_request_url};
+use super::parsing::metadata::{self, ImageMetadata};
+use super::parsing::normalize::normalize;
+use super::utils::{
+    download_images, get_content_type_of, get_request_url, is_css, is_html, is_html_response,
+    save_inline_images,
+};
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
 
 pub struct ResponseContent {
+    pub url: String,
     pub content_type: String,
     pub text: String,
     pub bytes: Vec<u8>,
@@ -12,60 +24,180 @@ pub struct ResponseContent {
 
 pub struct SRequest {
     pub url: String,
-    pub recursive: bool,
     pub level: u16,
-    pub max_level: u16,
-    pub domain: String,
-    pub path: String,
+    pub config: Arc<CrawlConfig>,
+    /// Page that linked this URL. The images of a stylesheet are attributed to it.
+    pub linked_from: Option<String>,
+    /// Response already fetched for this URL, used instead of a new request.
+    pub response: Option<ResponseContent>,
 }
 
 pub struct SResult {
+    pub url: String,
+    pub canonical: Option<String>,
     pub links: Vec<String>,
     pub image_links: Vec<String>,
+    /// Stylesheets linked by the page, or imported by the stylesheet.
+    pub stylesheets: Vec<String>,
+    pub is_stylesheet: bool,
+    /// Images embedded as `data:` URIs, and inline `<svg>` if enabled.
+    pub inline_images: Vec<InlineImage>,
+    /// Title of the page.
+    pub title: Option<String>,
+    /// Metadata of the images of the page's `<img>` elements, by URL.
+    pub image_metadata: HashMap<String, ImageMetadata>,
+}
+
+impl SResult {
+    /// Function that returns the metadata of an image of the page.
+    /// An image without `<img>` element only gets the page title.
+    pub fn metadata_of(&self, image_url: &str) -> ImageMetadata {
+        match self.image_metadata.get(image_url) {
+            Some(image_metadata) => image_metadata.clone(),
+            None => self.page_metadata(),
+        }
+    }
+
+    /// Function that returns the metadata shared by every image of the page.
+    pub fn page_metadata(&self) -> ImageMetadata {
+        ImageMetadata {
+            page_title: self.title.clone(),
+            ..ImageMetadata::default()
+        }
+    }
 }
 
 impl SRequest {
-    pub fn new(url: &str, recursive: bool, max_level: u16, path: &str) -> SRequest {
+    pub fn new(url: &str, config: &Arc<CrawlConfig>) -> SRequest {
         SRequest {
             url: url.to_owned(),
-            recursive,
             level: 0,
-            max_level,
-            domain: domain::get(url),
-            path: path.to_owned(),
+            config: Arc::clone(config),
+            linked_from: None,
+            response: None,
         }
     }
 
     pub fn request(&mut self) -> Result<SResult, ()> {
         let mut result = SResult {
+            url: self.url.clone(),
+            canonical: None,
             links: vec![],
             image_links: vec![],
+            stylesheets: vec![],
+            is_stylesheet: false,
+            inline_images: vec![],
+            title: None,
+            image_metadata: HashMap::new(),
+        };
+        let mut response = match self.response.take() {
+            Some(response) => Ok(response),
+            None => Err(FetchError::Network),
         };
-        let mut response = Err(());
         for _ in 0..3 {
-            response = get_request_url(&self.url);
-            if response.is_ok() {
+            if !matches!(response, Err(FetchError::Network | FetchError::Body)) {
                 break;
             }
+            response = get_request_url(&self.config, &self.url);
         }
-        if response.is_err() {
-            return Err(());
+        let url = self.url.as_str();
+        match &response {
+            Err(FetchError::Tls(message)) => logging::warn(
+                &format!("🔒 TLS error on {url}: {message}"),
+                &[("url", url), ("error", message)],
+            ),
+            Err(FetchError::TooManyRedirects) => {
+                logging::warn(&format!("🔴 Too many redirects: {url}"), &[("url", url)])
+            }
+            Err(FetchError::NotRecorded) => logging::warn(
+                &format!("📼 Not in the offline source: {url}"),
+                &[("url", url)],
+            ),
+            Err(err) => logging::debug(
+                &format!("🔴 Could not fetch {url}: {err}"),
+                &[("url", url), ("error", &err.to_string())],
+            ),
+            _ => {}
+        }
+        match &response {
+            // A redirect out of the scope is not followed on purpose,
+            // and a page past the download size limit is left for the end of the crawl.
+            Err(FetchError::Redirect(_) | FetchError::OverBudget) => return Err(()),
+            Err(_) => {
+                self.config.stats.page_failed();
+                return Err(());
+            }
+            Ok(_) => self.config.stats.page_fetched(),
         }
-        println!("🟢 Successfully fetched URL: {}", self.url);
+        logging::info(
+            &format!("🟢 Successfully fetched URL: {}", self.url),
+            &[("url", &self.url)],
+        );
         let response = response.unwrap();
-        let links = links::extract_not_image(&response.text);
-        let links = links::add_start_url(&self.url, &links);
-        let links = links::filter_links_by_domain(&self.domain, &links);
-        let links = links::remove_double_dots(&links);
-        result.links = links;
-
-        let image_links = links::extract_image(&response.text);
-        let image_links = links::remove_double_dots(&image_links);
-        let image_links = links::add_start_url(&self.url, &image_links);
-        let image_links_domain = links::filter_links_by_domain(&self.domain, &image_links);
-        links::print_diff(&image_links, &image_links_domain);
-        result.image_links = image_links_domain;
+        // Links are resolved against the URL reached after the redirects.
+        result.url = response.url.clone();
+        if is_css(&response.content_type) {
+            // A stylesheet has no page links, its URLs are relative to the stylesheet itself.
+            let css_links = css::extract(&response.text);
+            let imports = css::resolve(&result.url, &css_links.imports);
+            result.stylesheets = links::filter_links_by_scope(&self.config.scope, &imports);
+            let image_links = css::resolve(&result.url, &css_links.images);
+            let image_links_domain = links::filter_links_by_scope(&self.config.scope, &image_links);
+            links::print_diff(&image_links, &image_links_domain);
+            result.image_links = image_links_domain;
+            result.is_stylesheet = true;
+            result.inline_images = decode_data_uris(&response.text);
+        } else if !is_html_response(&response) {
+            logging::info(
+                &format!(
+                    "⏭️ Not an HTML page: {} ({})",
+                    result.url, response.content_type
+                ),
+                &[
+                    ("url", &result.url),
+                    ("content_type", &response.content_type),
+                ],
+            );
+        } else {
+            let (html, rendered_images) = self.page_content(&result.url, response.text);
+            result.canonical = links::extract_canonical(&html)
+                .map(|canonical| links::add_start_url(&result.url, &vec![canonical]).remove(0))
+                .filter(|canonical| self.config.scope.contains(&domain::get(canonical)));
+            let stylesheets = links::extract_stylesheets(&html);
+            let stylesheets = links::add_start_url(&result.url, &stylesheets);
+            let stylesheets = links::filter_links_by_scope(&self.config.scope, &stylesheets);
+            let links = links::extract_pages(&html);
+            let links = links::add_start_url(&result.url, &links);
+            let links = links::filter_links_by_scope(&self.config.scope, &links);
+            let mut links = links::remove_double_dots(&links);
+            links.retain(|link| !stylesheets.contains(link));
+            result.links = links;
+            result.stylesheets = stylesheets;
 
+            result.title = metadata::extract_title(&html);
+            for (src, image_metadata) in metadata::extract(&html) {
+                let image_url = links::add_start_url(&result.url, &vec![src]).remove(0);
+                result
+                    .image_metadata
+                    .entry(image_url)
+                    .or_insert(image_metadata);
+            }
+            let image_links = links::extract_image(&html);
+            let image_links = links::remove_double_dots(&image_links);
+            result.inline_images = decode_data_uris(&html);
+            if self.config.inline_svg {
+                result.inline_images.extend(inline::extract_svgs(&html));
+            }
+            let mut image_links = links::add_start_url(&result.url, &image_links);
+            for image_link in rendered_images {
+                if !image_links.contains(&image_link) {
+                    image_links.push(image_link);
+                }
+            }
+            let image_links_domain = links::filter_links_by_scope(&self.config.scope, &image_links);
+            links::print_diff(&image_links, &image_links_domain);
+            result.image_links = image_links_domain;
+        }
         Ok(result)
     }
 
@@ -74,46 +206,213 @@ impl SRequest {
         visited_urls: &mut HashSet<String>,
         collected_images: &mut Vec<String>,
     ) {
-        if visited_urls.contains(&self.url) {
+        let visited_key = normalize(&self.url, &self.config.strip_params);
+        if visited_urls.contains(&visited_key) || self.config.budget.exhausted().is_some() {
             return;
         }
-        visited_urls.insert(self.url.clone());
+        visited_urls.insert(visited_key.clone());
         let request_result = self.request();
         if let Ok(spider_result) = request_result {
-            let mut new_image_links: Vec<String> = Vec::new();
-            for image_link in spider_result.image_links {
-                if !collected_images.contains(&image_link) {
-                    new_image_links.push(image_link.clone());
-                    collected_images.push(image_link);
+            if is_duplicate(
+                &visited_key,
+                &spider_result,
+                &self.config.strip_params,
+                visited_urls,
+            ) {
+                return;
+            }
+            self.config.events.emit_page_fetched(&PageFetched {
+                url: self.url.clone(),
+                final_url: spider_result.url.clone(),
+                level: self.level,
+                links: spider_result.links.len(),
+                images: spider_result.image_links.len(),
+            });
+            // Stylesheets are part of their page and do not count as pages.
+            if !spider_result.is_stylesheet {
+                self.config.budget.add_page();
+            }
+            // The images of a stylesheet belong to the page that links it.
+            let page_url = match (spider_result.is_stylesheet, &self.linked_from) {
+                (true, Some(linked_from)) => linked_from.clone(),
+                _ => spider_result.url.clone(),
+            };
+            let mut new_images: Vec<ImageFound> = Vec::new();
+            for image_link in &spider_result.image_links {
+                if !collected_images.contains(image_link) {
+                    let image_found = ImageFound {
+                        url: image_link.clone(),
+                        page_url: page_url.clone(),
+                        metadata: spider_result.metadata_of(image_link),
+                    };
+                    self.config.events.emit_image_found(&image_found);
+                    self.config
+                        .found_images
+                        .lock()
+                        .unwrap()
+                        .push(image_found.clone());
+                    new_images.push(image_found);
+                    collected_images.push(image_link.clone());
                 }
             }
-            if !new_image_links.is_empty() {
-                download_images(&new_image_links, &self.path);
+            // A dry run only collects the links.
+            if !self.config.dry_run {
+                if !new_images.is_empty() {
+                    download_images(&self.config, &new_images);
+                }
+                save_inline_images(
+                    &self.config,
+                    &spider_result.inline_images,
+                    &page_url,
+                    &spider_result.page_metadata(),
+                );
+            }
+            // Stylesheets are part of the page: they are crawled at the same level,
+            // even when the crawl is not recursive.
+            for stylesheet in spider_result.stylesheets {
+                let mut new_request = SRequest {
+                    url: stylesheet,
+                    level: self.level,
+                    config: Arc::clone(&self.config),
+                    linked_from: Some(page_url.clone()),
+                    response: None,
+                };
+                new_request.get_all_image_links(visited_urls, collected_images);
             }
-            if self.recursive && self.level <= self.max_level {
+            if self.config.recursive && self.level <= self.config.max_level {
+                progress::pages_queued(spider_result.links.len());
                 for link in spider_result.links {
+                    if !self.config.budget.can_fetch_page() {
+                        break;
+                    }
+                    if !self.is_page(&link, visited_urls) {
+                        progress::page_done();
+                        continue;
+                    }
                     let mut new_request = SRequest {
                         url: link.clone(),
-                        recursive: self.recursive,
                         level: self.level + 1,
-                        max_level: self.max_level,
-                        domain: self.domain.clone(),
-                        path: self.path.clone(),
+                        config: Arc::clone(&self.config),
+                        linked_from: Some(page_url.clone()),
+                        response: None,
                     };
                     new_request.get_all_image_links(visited_urls, collected_images);
+                    progress::page_done();
                 }
             }
-        } else {
-            // println!("🔴 Failed to fetch or process URL: {}", self.url);
         }
     }
 
+    /// Function that returns the HTML to extract the links from, and the images
+    /// the page loaded: the DOM rendered by the browser when there is one.
+    #[cfg(feature = "render")]
+    fn page_content(&self, url: &str, html: String) -> (String, Vec<String>) {
+        let renderer = match &self.config.renderer {
+            Some(renderer) => renderer,
+            None => return (html, Vec::new()),
+        };
+        match renderer.render(url) {
+            Ok(rendered) => {
+                logging::info(&format!("🖥️ Rendered page: {url}"), &[("url", url)]);
+                (rendered.html, rendered.images)
+            }
+            Err(err) => {
+                logging::warn(
+                    &format!("Could not render {url}: {err}"),
+                    &[("url", url), ("error", &err)],
+                );
+                (html, Vec::new())
+            }
+        }
+    }
+
+    #[cfg(not(feature = "render"))]
+    fn page_content(&self, _url: &str, html: String) -> (String, Vec<String>) {
+        (html, Vec::new())
+    }
+
+    /// Function that check with a HEAD request if a link with an unusual extension
+    /// is an HTML page before downloading it. Links that are not are marked as visited.
+    fn is_page(&self, link: &str, visited_urls: &mut HashSet<String>) -> bool {
+        if links::classify(link) != links::LinkKind::Unknown {
+            return true;
+        }
+        let visited_key = normalize(link, &self.config.strip_params);
+        if visited_urls.contains(&visited_key) {
+            return false;
+        }
+        let is_page = match get_content_type_of(&self.config.client, link) {
+            // Without content type the link is not worth downloading to find out.
+            Ok(content_type) if content_type.trim().is_empty() => {
+                logging::info(
+                    &format!("⏭️ Unknown content type: {link}"),
+                    &[("url", link)],
+                );
+                false
+            }
+            Ok(content_type) => {
+                let is_page = is_html(&content_type);
+                if !is_page {
+                    logging::info(
+                        &format!("⏭️ Not an HTML page: {link} ({content_type})"),
+                        &[("url", link), ("content_type", &content_type)],
+                    );
+                }
+                is_page
+            }
+            // Some servers do not implement HEAD, the GET request decides.
+            Err(FetchError::Status(405 | 501)) => true,
+            Err(_) => false,
+        };
+        if !is_page {
+            visited_urls.insert(visited_key);
+        }
+        is_page
+    }
+
     #[allow(dead_code)]
     pub fn print(&self) {
         println!("URL: {}", self.url);
-        println!("Recursive: {}", self.recursive);
+        println!("Recursive: {}", self.config.recursive);
         println!("Level: {}", self.level);
-        println!("Max Level: {}", self.max_level);
-        println!("Domain: {}", self.domain);
+        println!("Max Level: {}", self.config.max_level);
+        println!("Scope: {}", self.config.scope.join(", "));
+    }
+}
+
+/// Function that decodes the images embedded as `data:` URIs in a page or a stylesheet.
+fn decode_data_uris(text: &str) -> Vec<InlineImage> {
+    inline::extract_data_uris(text)
+        .iter()
+        .filter_map(|uri| inline::decode_data_uri(uri))
+        .collect()
+}
+
+/// Function that records the final URL and the canonical URL of a page as visited.
+/// `visited_key` is the normalized URL the page was requested with.
+/// It returns true if one of them was already visited under another URL,
+/// meaning the page has already been crawled.
+pub fn is_duplicate(
+    visited_key: &str,
+    result: &SResult,
+    strip_params: &[String],
+    visited_urls: &mut HashSet<String>,
+) -> bool {
+    let mut aliases = vec![normalize(&result.url, strip_params)];
+    if let Some(canonical) = &result.canonical {
+        aliases.push(normalize(canonical, strip_params));
+    }
+    let mut duplicate = false;
+    for alias in aliases {
+        if alias != visited_key && !visited_urls.insert(alias) {
+            duplicate = true;
+        }
+    }
+    if duplicate {
+        logging::info(
+            &format!("🔁 Already crawled: {visited_key} -> {}", result.url),
+            &[("url", visited_key), ("final_url", &result.url)],
+        );
     }
+    duplicate
 }
@@ -0,0 +1,147 @@
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use zip::write::SimpleFileOptions;
+use zip::ZipWriter;
+
+use super::Storage;
+use crate::parsing::links::get_archive_path;
+
+/// Kind of archive, chosen from the extension of the archive path.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ArchiveFormat {
+    Zip,
+    Tar,
+    TarGz,
+}
+
+impl ArchiveFormat {
+    /// Function that returns the format matching `.zip`, `.tar`, `.tar.gz` or `.tgz`.
+    pub fn from_path(path: &str) -> Option<ArchiveFormat> {
+        let path = path.to_lowercase();
+        if path.ends_with(".zip") {
+            Some(ArchiveFormat::Zip)
+        } else if path.ends_with(".tar.gz") || path.ends_with(".tgz") {
+            Some(ArchiveFormat::TarGz)
+        } else if path.ends_with(".tar") {
+            Some(ArchiveFormat::Tar)
+        } else {
+            None
+        }
+    }
+}
+
+enum ArchiveWriter {
+    Zip(ZipWriter<File>),
+    Tar(tar::Builder<File>),
+    TarGz(tar::Builder<GzEncoder<File>>),
+}
+
+/// Images streamed into a single ZIP or tar archive as they are downloaded.
+/// The archive is only complete once `finish` has been called.
+pub struct ArchiveStorage {
+    pub path: String,
+    pub format: ArchiveFormat,
+    writer: Mutex<Option<ArchiveWriter>>,
+}
+
+impl ArchiveStorage {
+    pub fn new(path: &str) -> io::Result<ArchiveStorage> {
+        let format = ArchiveFormat::from_path(path).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("unknown archive format (expected .zip, .tar or .tar.gz): {path}"),
+            )
+        })?;
+        Ok(ArchiveStorage {
+            path: path.to_string(),
+            format,
+            writer: Mutex::new(None),
+        })
+    }
+}
+
+impl Storage for ArchiveStorage {
+    fn prepare(&self) -> io::Result<()> {
+        if let Some(parent) = Path::new(&self.path).parent() {
+            if !parent.as_os_str().is_empty() {
+                std::fs::create_dir_all(parent)?;
+            }
+        }
+        let file = File::create(&self.path)?;
+        let writer = match self.format {
+            ArchiveFormat::Zip => ArchiveWriter::Zip(ZipWriter::new(file)),
+            ArchiveFormat::Tar => ArchiveWriter::Tar(tar::Builder::new(file)),
+            ArchiveFormat::TarGz => ArchiveWriter::TarGz(tar::Builder::new(GzEncoder::new(
+                file,
+                Compression::default(),
+            ))),
+        };
+        *self.writer.lock().unwrap() = Some(writer);
+        Ok(())
+    }
+
+    /// Entries keep the layout of the site: `host/path/to/image.jpg`.
+    fn entry_name(&self, url: &str) -> Result<String, ()> {
+        get_archive_path(url)
+    }
+
+    fn put(&self, name: &str, data: &[u8], _content_type: &str) -> io::Result<String> {
+        let mut writer = self.writer.lock().unwrap();
+        match writer.as_mut() {
+            Some(ArchiveWriter::Zip(zip)) => {
+                zip.start_file(name, SimpleFileOptions::default())
+                    .map_err(io::Error::other)?;
+                zip.write_all(data)?;
+            }
+            Some(ArchiveWriter::Tar(tar)) => append_tar_entry(tar, name, data)?,
+            Some(ArchiveWriter::TarGz(tar)) => append_tar_entry(tar, name, data)?,
+            None => {
+                return Err(io::Error::other(format!(
+                    "archive is not open: {}",
+                    self.path
+                )))
+            }
+        }
+        Ok(format!("{}:{name}", self.path))
+    }
+
+    fn finish(&self) -> io::Result<()> {
+        match self.writer.lock().unwrap().take() {
+            Some(ArchiveWriter::Zip(zip)) => {
+                zip.finish().map_err(io::Error::other)?.flush()?;
+            }
+            Some(ArchiveWriter::Tar(tar)) => tar.into_inner()?.flush()?,
+            Some(ArchiveWriter::TarGz(tar)) => {
+                tar.into_inner()?.finish()?.flush()?;
+            }
+            None => {}
+        }
+        Ok(())
+    }
+
+    fn describe(&self) -> String {
+        self.path.clone()
+    }
+}
+
+fn append_tar_entry<W: Write>(
+    tar: &mut tar::Builder<W>,
+    name: &str,
+    data: &[u8],
+) -> io::Result<()> {
+    let mtime = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0);
+    let mut header = tar::Header::new_gnu();
+    header.set_size(data.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(mtime);
+    header.set_cksum();
+    tar.append_data(&mut header, name, data)
+}
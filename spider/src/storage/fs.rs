@@ -0,0 +1,182 @@
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use super::Storage;
+use crate::logging;
+use crate::utils::can_create_folder;
+
+/// Suffix of the temporary files an image is written to before it gets its name.
+const TEMP_SUFFIX: &str = ".spider-tmp";
+
+/// Age after which a temporary file is left over by an interrupted crawl.
+/// A running crawl moves its temporary files to their name as soon as they are written,
+/// so another crawl writing to the same directory keeps its own.
+const STALE_TEMP_AGE: Duration = Duration::from_secs(60 * 60);
+
+/// What to do with an image whose file already exists in the directory.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Existing {
+    /// Replace the file.
+    Overwrite,
+    /// Keep the file and do not download the image again.
+    #[default]
+    Skip,
+    /// Save the image next to it, as `name-1.ext`, `name-2.ext`...
+    Rename,
+}
+
+/// Images written as files of a local directory.
+/// Every file is written to a temporary file first and then moved to its name,
+/// so an interrupted crawl never leaves a truncated image.
+pub struct FsStorage {
+    pub path: String,
+    pub existing: Existing,
+}
+
+impl FsStorage {
+    pub fn new(path: &str) -> FsStorage {
+        FsStorage {
+            path: path.to_string(),
+            existing: Existing::default(),
+        }
+    }
+
+    /// What to do with the images already in the directory (default is to skip them).
+    pub fn existing(mut self, existing: Existing) -> FsStorage {
+        self.existing = existing;
+        self
+    }
+
+    /// Function that writes the data to a new temporary file of the directory.
+    fn write_temp(&self, name: &str, data: &[u8]) -> io::Result<PathBuf> {
+        let temp_path = Path::new(&self.path).join(format!(
+            ".{name}.{}{TEMP_SUFFIX}",
+            uuid::Uuid::new_v4().simple()
+        ));
+        let result = File::create_new(&temp_path).and_then(|mut file| {
+            file.write_all(data)?;
+            file.sync_all()
+        });
+        if let Err(err) = result {
+            let _ = fs::remove_file(&temp_path);
+            return Err(err);
+        }
+        Ok(temp_path)
+    }
+
+    /// Function that removes the temporary files left by an interrupted crawl,
+    /// the ones not modified for `STALE_TEMP_AGE`.
+    fn remove_temp_files(&self) {
+        let entries = match fs::read_dir(&self.path) {
+            Ok(entries) => entries,
+            Err(_) => return,
+        };
+        for entry in entries.flatten() {
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            if !file_name.starts_with('.') || !file_name.ends_with(TEMP_SUFFIX) {
+                continue;
+            }
+            let is_stale = entry
+                .metadata()
+                .and_then(|metadata| metadata.modified())
+                .ok()
+                .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+                .is_some_and(|age| age >= STALE_TEMP_AGE);
+            if is_stale {
+                let _ = fs::remove_file(entry.path());
+            }
+        }
+    }
+}
+
+impl Storage for FsStorage {
+    fn prepare(&self) -> io::Result<()> {
+        if !can_create_folder(&self.path) {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                format!("cannot write to {}", self.path),
+            ));
+        }
+        self.remove_temp_files();
+        Ok(())
+    }
+
+    fn put(&self, name: &str, data: &[u8], _content_type: &str) -> io::Result<String> {
+        let file_path = Path::new(&self.path).join(name);
+        let temp_path = self.write_temp(name, data)?;
+        if let Err(err) = fs::rename(&temp_path, &file_path) {
+            let _ = fs::remove_file(&temp_path);
+            return Err(err);
+        }
+        Ok(file_path.to_string_lossy().to_string())
+    }
+
+    /// The image is written with the `existing` policy.
+    /// With `Existing::Skip`, an image already there gives an `AlreadyExists` error.
+    fn put_image(&self, name: &str, data: &[u8], content_type: &str) -> io::Result<String> {
+        if self.existing == Existing::Overwrite {
+            return self.put(name, data, content_type);
+        }
+        let temp_path = self.write_temp(name, data)?;
+        let mut number = 0;
+        let result = loop {
+            let file_name = match number {
+                0 => name.to_string(),
+                _ => numbered_name(name, number),
+            };
+            let file_path = Path::new(&self.path).join(&file_name);
+            match move_new(&temp_path, &file_path) {
+                Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {
+                    if self.existing == Existing::Skip {
+                        break Err(err);
+                    }
+                    number += 1;
+                }
+                Err(err) => break Err(err),
+                Ok(()) => {
+                    if number > 0 {
+                        logging::debug(
+                            &format!("{name} already exists, saved as {file_name}"),
+                            &[("name", name), ("path", &file_path.to_string_lossy())],
+                        );
+                    }
+                    break Ok(file_path.to_string_lossy().to_string());
+                }
+            }
+        };
+        let _ = fs::remove_file(&temp_path);
+        result
+    }
+
+    fn keeps_existing(&self, name: &str) -> bool {
+        self.existing == Existing::Skip && Path::new(&self.path).join(name).exists()
+    }
+
+    fn describe(&self) -> String {
+        self.path.clone()
+    }
+}
+
+/// Function that gives the temporary file its name, without replacing an existing file.
+/// On file systems without hard links it is renamed after a check, which is not atomic.
+fn move_new(temp_path: &Path, file_path: &Path) -> io::Result<()> {
+    match fs::hard_link(temp_path, file_path) {
+        Err(err) if err.kind() != io::ErrorKind::AlreadyExists => {
+            if file_path.exists() {
+                return Err(io::Error::from(io::ErrorKind::AlreadyExists));
+            }
+            fs::rename(temp_path, file_path)
+        }
+        result => result,
+    }
+}
+
+/// Function that numbers a file name before its extension: `a.png` gives `a-1.png`.
+pub fn numbered_name(name: &str, number: usize) -> String {
+    match name.rsplit_once('.') {
+        Some((stem, extension)) if !stem.is_empty() => format!("{stem}-{number}.{extension}"),
+        _ => format!("{name}-{number}"),
+    }
+}
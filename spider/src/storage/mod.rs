@@ -0,0 +1,54 @@
+use std::io;
+
+use crate::parsing::links::get_filename;
+
+pub mod archive;
+pub mod fs;
+pub mod s3;
+
+mod tests_archive;
+mod tests_fs;
+mod tests_s3;
+
+pub use archive::{ArchiveFormat, ArchiveStorage};
+pub use fs::{Existing, FsStorage};
+pub use s3::{S3Config, S3Storage};
+
+/// Destination of the downloaded images.
+/// `put` may be called from several download threads at the same time.
+pub trait Storage: Send + Sync {
+    /// Function that check the destination can be written, before the crawl starts.
+    fn prepare(&self) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Function that returns the name an image is stored under.
+    /// By default it is the path of the URL with `/` replaced by `_`.
+    fn entry_name(&self, url: &str) -> Result<String, ()> {
+        get_filename(url)
+    }
+
+    /// Function that stores an image under the given name.
+    /// It returns where the image ended up (a path, an archive entry or a URL).
+    fn put(&self, name: &str, data: &[u8], content_type: &str) -> io::Result<String>;
+
+    /// Function that stores a downloaded image under the given name.
+    /// By default it is `put`, a storage can handle the images already there its own way.
+    fn put_image(&self, name: &str, data: &[u8], content_type: &str) -> io::Result<String> {
+        self.put(name, data, content_type)
+    }
+
+    /// Function that tells if the image stored under the name is kept as it is,
+    /// so it does not need to be downloaded again.
+    fn keeps_existing(&self, _name: &str) -> bool {
+        false
+    }
+
+    /// Function that flushes the destination once the crawl is over.
+    fn finish(&self) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Human readable destination, used in the messages.
+    fn describe(&self) -> String;
+}
@@ -0,0 +1,279 @@
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use reqwest::blocking::{Client, Response};
+use reqwest::Method;
+use sha2::{Digest, Sha256};
+use std::io;
+
+use super::Storage;
+use crate::http::{self, ClientOptions};
+use crate::logging;
+
+/// Settings of an S3-compatible object store (AWS S3, MinIO, Ceph...).
+#[derive(Clone, Debug)]
+pub struct S3Config {
+    pub endpoint: String,
+    pub bucket: String,
+    pub prefix: String,
+    pub region: String,
+    pub access_key: String,
+    pub secret_key: String,
+    /// Token of temporary credentials, sent as `x-amz-security-token`.
+    pub session_token: Option<String>,
+}
+
+impl S3Config {
+    /// Function that reads the credentials and the region from the usual
+    /// `AWS_ACCESS_KEY_ID`, `AWS_SECRET_ACCESS_KEY`, `AWS_SESSION_TOKEN` and `AWS_REGION` variables.
+    /// `target` is `bucket` or `bucket/prefix`.
+    pub fn from_env(
+        endpoint: &str,
+        target: &str,
+        region: Option<&str>,
+    ) -> Result<S3Config, String> {
+        let (bucket, prefix) = match target.trim_start_matches("s3://").split_once('/') {
+            Some((bucket, prefix)) => {
+                (bucket.to_string(), prefix.trim_end_matches('/').to_string())
+            }
+            None => (
+                target.trim_start_matches("s3://").to_string(),
+                String::new(),
+            ),
+        };
+        if bucket.is_empty() {
+            return Err(format!("invalid S3 bucket: {target}"));
+        }
+        let access_key = std::env::var("AWS_ACCESS_KEY_ID")
+            .map_err(|_| "AWS_ACCESS_KEY_ID is not set".to_string())?;
+        let secret_key = std::env::var("AWS_SECRET_ACCESS_KEY")
+            .map_err(|_| "AWS_SECRET_ACCESS_KEY is not set".to_string())?;
+        let region = match region {
+            Some(region) => region.to_string(),
+            None => std::env::var("AWS_REGION").unwrap_or_else(|_| "us-east-1".to_string()),
+        };
+        Ok(S3Config {
+            endpoint: endpoint.trim_end_matches('/').to_string(),
+            bucket,
+            prefix,
+            region,
+            access_key,
+            secret_key,
+            session_token: std::env::var("AWS_SESSION_TOKEN")
+                .ok()
+                .filter(|token| !token.is_empty()),
+        })
+    }
+
+    /// Function that returns the key of an object, with the prefix.
+    pub fn key(&self, name: &str) -> String {
+        if self.prefix.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}/{name}", self.prefix)
+        }
+    }
+}
+
+/// Images uploaded to a bucket with path-style requests signed with AWS Signature V4.
+pub struct S3Storage {
+    pub config: S3Config,
+    client: Client,
+}
+
+impl S3Storage {
+    pub fn new(config: S3Config) -> S3Storage {
+        S3Storage {
+            config,
+            client: Client::new(),
+        }
+    }
+
+    /// Function that uploads through the proxy and with the TLS settings of the crawl,
+    /// for a bucket behind a proxy or served with a private CA.
+    pub fn with_options(config: S3Config, options: &ClientOptions) -> Result<S3Storage, ()> {
+        let client = http::client_builder(options)?.build();
+        if client.is_err() {
+            logging::error("Could not build the S3 client", &[]);
+            return Err(());
+        }
+        Ok(S3Storage {
+            config,
+            client: client.unwrap(),
+        })
+    }
+}
+
+impl S3Storage {
+    /// Function that sends a request signed with AWS Signature V4 to the endpoint.
+    /// `path` is the encoded path of the bucket or of an object.
+    fn send(
+        &self,
+        method: Method,
+        path: &str,
+        data: &[u8],
+        content_type: Option<&str>,
+    ) -> io::Result<Response> {
+        let url = format!("{}{path}", self.config.endpoint);
+        let host = url::Url::parse(&url)
+            .ok()
+            .and_then(|url| {
+                let host = url.host_str()?.to_string();
+                Some(match url.port() {
+                    Some(port) => format!("{host}:{port}"),
+                    None => host,
+                })
+            })
+            .ok_or_else(|| {
+                io::Error::other(format!("invalid S3 endpoint: {}", self.config.endpoint))
+            })?;
+
+        let payload_hash = hex::encode(Sha256::digest(data));
+        let amz_date = format_amz_date(Utc::now());
+        let mut headers = vec![
+            ("host".to_string(), host),
+            ("x-amz-content-sha256".to_string(), payload_hash.clone()),
+            ("x-amz-date".to_string(), amz_date.clone()),
+        ];
+        if let Some(content_type) = content_type {
+            headers.push(("content-type".to_string(), content_type.to_string()));
+        }
+        if let Some(token) = &self.config.session_token {
+            headers.push(("x-amz-security-token".to_string(), token.clone()));
+        }
+        let authorization = sign(
+            &self.config,
+            method.as_str(),
+            path,
+            "",
+            &mut headers,
+            &payload_hash,
+            &amz_date,
+        );
+
+        let mut request = self.client.request(method, &url).body(data.to_vec());
+        for (name, value) in headers.iter().filter(|(name, _)| name != "host") {
+            request = request.header(name, value);
+        }
+        request
+            .header("authorization", authorization)
+            .send()
+            .map_err(io::Error::other)
+    }
+}
+
+impl Storage for S3Storage {
+    /// A signed `HEAD` on the bucket checks the endpoint, the bucket and the credentials
+    /// before the crawl, instead of failing on every image.
+    fn prepare(&self) -> io::Result<()> {
+        let path = format!("/{}", uri_encode(&self.config.bucket, true));
+        let status = self
+            .send(Method::HEAD, &path, &[], None)
+            .map(|response| response.status())
+            .map_err(|err| {
+                io::Error::new(
+                    io::ErrorKind::PermissionDenied,
+                    format!("cannot reach {}: {err}", self.describe()),
+                )
+            })?;
+        if !status.is_success() {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                format!("cannot write to {}: {status}", self.describe()),
+            ));
+        }
+        Ok(())
+    }
+
+    fn put(&self, name: &str, data: &[u8], content_type: &str) -> io::Result<String> {
+        let key = self.config.key(name);
+        let path = format!(
+            "/{}/{}",
+            uri_encode(&self.config.bucket, true),
+            uri_encode(&key, false)
+        );
+        let response = self.send(Method::PUT, &path, data, Some(content_type))?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().unwrap_or_default();
+            return Err(io::Error::other(format!(
+                "S3 upload of {key} failed: {status} {body}"
+            )));
+        }
+        Ok(format!("s3://{}/{key}", self.config.bucket))
+    }
+
+    fn describe(&self) -> String {
+        format!("s3://{}/{}", self.config.bucket, self.config.prefix)
+    }
+}
+
+/// Function that formats a date the way `x-amz-date` expects it.
+pub fn format_amz_date(date: DateTime<Utc>) -> String {
+    date.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// Function that percent-encodes everything but the unreserved characters,
+/// and `/` unless `encode_slash` is set.
+pub fn uri_encode(input: &str, encode_slash: bool) -> String {
+    let mut encoded = String::new();
+    for byte in input.bytes() {
+        if byte.is_ascii_alphanumeric()
+            || b"-._~".contains(&byte)
+            || (byte == b'/' && !encode_slash)
+        {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    encoded
+}
+
+fn hmac_sha256(key: &[u8], data: &str) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any size");
+    mac.update(data.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// Function that computes the `Authorization` header of an AWS Signature V4 request.
+/// `headers` are sorted in place and must contain `host` and `x-amz-date`.
+pub fn sign(
+    config: &S3Config,
+    method: &str,
+    path: &str,
+    query: &str,
+    headers: &mut [(String, String)],
+    payload_hash: &str,
+    amz_date: &str,
+) -> String {
+    headers.sort();
+    let canonical_headers = headers
+        .iter()
+        .map(|(name, value)| format!("{}:{}\n", name.to_lowercase(), value.trim()))
+        .collect::<String>();
+    let signed_headers = headers
+        .iter()
+        .map(|(name, _)| name.to_lowercase())
+        .collect::<Vec<String>>()
+        .join(";");
+    let canonical_request =
+        format!("{method}\n{path}\n{query}\n{canonical_headers}\n{signed_headers}\n{payload_hash}");
+
+    let date = &amz_date[..8];
+    let credential_scope = format!("{date}/{}/s3/aws4_request", config.region);
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{amz_date}\n{credential_scope}\n{}",
+        hex::encode(Sha256::digest(canonical_request.as_bytes()))
+    );
+
+    let key = hmac_sha256(format!("AWS4{}", config.secret_key).as_bytes(), date);
+    let key = hmac_sha256(&key, &config.region);
+    let key = hmac_sha256(&key, "s3");
+    let key = hmac_sha256(&key, "aws4_request");
+    let signature = hex::encode(hmac_sha256(&key, &string_to_sign));
+
+    format!(
+        "AWS4-HMAC-SHA256 Credential={}/{credential_scope}, SignedHeaders={signed_headers}, Signature={signature}",
+        config.access_key
+    )
+}
@@ -0,0 +1,113 @@
+#[cfg(test)]
+use super::{ArchiveFormat, ArchiveStorage, Storage};
+#[cfg(test)]
+use std::io::Read;
+
+#[test]
+fn test_archive_format_from_path() {
+    assert_eq!(
+        ArchiveFormat::from_path("out.zip"),
+        Some(ArchiveFormat::Zip)
+    );
+    assert_eq!(
+        ArchiveFormat::from_path("out.TAR"),
+        Some(ArchiveFormat::Tar)
+    );
+    assert_eq!(
+        ArchiveFormat::from_path("out.tar.gz"),
+        Some(ArchiveFormat::TarGz)
+    );
+    assert_eq!(
+        ArchiveFormat::from_path("out.tgz"),
+        Some(ArchiveFormat::TarGz)
+    );
+    assert_eq!(ArchiveFormat::from_path("out.rar"), None);
+    assert!(ArchiveStorage::new("out.rar").is_err());
+}
+
+#[test]
+fn test_archive_storage_zip() {
+    let path = std::env::temp_dir().join("spider_test_archive.zip");
+    let storage = ArchiveStorage::new(path.to_str().unwrap()).unwrap();
+    storage.prepare().unwrap();
+    let location = storage.put("a.jpg", b"first", "image/jpeg").unwrap();
+    assert_eq!(location, format!("{}:a.jpg", path.to_str().unwrap()));
+    storage.put("b.png", b"second", "image/png").unwrap();
+    storage.finish().unwrap();
+
+    let mut archive = zip::ZipArchive::new(std::fs::File::open(&path).unwrap()).unwrap();
+    assert_eq!(archive.len(), 2);
+    let mut content = String::new();
+    archive
+        .by_name("b.png")
+        .unwrap()
+        .read_to_string(&mut content)
+        .unwrap();
+    assert_eq!(content, "second");
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_archive_storage_tar_gz() {
+    let path = std::env::temp_dir().join("spider_test_archive.tar.gz");
+    let storage = ArchiveStorage::new(path.to_str().unwrap()).unwrap();
+    storage.prepare().unwrap();
+    storage.put("a.jpg", b"first", "image/jpeg").unwrap();
+    storage.put("b.png", b"second", "image/png").unwrap();
+    storage.finish().unwrap();
+
+    let file = std::fs::File::open(&path).unwrap();
+    let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(file));
+    let mut entries = Vec::new();
+    for entry in archive.entries().unwrap() {
+        let mut entry = entry.unwrap();
+        let mut content = String::new();
+        entry.read_to_string(&mut content).unwrap();
+        entries.push((entry.path().unwrap().to_str().unwrap().to_string(), content));
+    }
+    assert_eq!(
+        entries,
+        vec![
+            ("a.jpg".to_string(), "first".to_string()),
+            ("b.png".to_string(), "second".to_string()),
+        ]
+    );
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_archive_storage_put_before_prepare() {
+    let storage = ArchiveStorage::new("never_opened.tar").unwrap();
+    assert!(storage.put("a.jpg", b"first", "image/jpeg").is_err());
+}
+
+#[test]
+fn test_archive_storage_entry_name_keeps_layout() {
+    let storage = ArchiveStorage::new("out.zip").unwrap();
+    let name = storage.entry_name("https://example.com/img/a.jpg").unwrap();
+    assert_eq!(name, "example.com/img/a.jpg");
+}
+
+#[test]
+fn test_archive_storage_urls_with_queries() {
+    let path = std::env::temp_dir().join("spider_test_archive_queries.zip");
+    let storage = ArchiveStorage::new(path.to_str().unwrap()).unwrap();
+    storage.prepare().unwrap();
+    for (url, data) in [
+        ("https://example.com/a.jpg?w=100", b"small"),
+        ("https://example.com/a.jpg?w=800", b"large"),
+    ] {
+        let name = storage.entry_name(url).unwrap();
+        storage.put(&name, data, "image/jpeg").unwrap();
+    }
+    storage.finish().unwrap();
+
+    let archive = zip::ZipArchive::new(std::fs::File::open(&path).unwrap()).unwrap();
+    let mut names: Vec<&str> = archive.file_names().collect();
+    names.sort();
+    assert_eq!(
+        names,
+        vec!["example.com/a-4af38b55.jpg", "example.com/a-51a9cc0c.jpg"]
+    );
+    std::fs::remove_file(&path).unwrap();
+}
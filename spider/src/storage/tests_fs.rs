@@ -0,0 +1,103 @@
+#[cfg(test)]
+use super::fs::numbered_name;
+#[cfg(test)]
+use super::{Existing, FsStorage, Storage};
+#[cfg(test)]
+use std::time::{Duration, SystemTime};
+
+#[test]
+fn test_fs_storage_put() {
+    let dir = std::env::temp_dir().join("spider_test_fs_storage");
+    let storage = FsStorage::new(dir.to_str().unwrap());
+    assert!(storage.prepare().is_ok());
+    let location = storage
+        .put("path_to_image.jpg", b"jpeg data", "image/jpeg")
+        .unwrap();
+    assert_eq!(location, dir.join("path_to_image.jpg").to_str().unwrap());
+    assert_eq!(std::fs::read(&location).unwrap(), b"jpeg data");
+    assert!(storage.finish().is_ok());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_fs_storage_prepare_on_file() {
+    let file = std::env::temp_dir().join("spider_test_fs_storage_file");
+    std::fs::write(&file, b"not a directory").unwrap();
+    let storage = FsStorage::new(file.to_str().unwrap());
+    assert!(storage.prepare().is_err());
+    std::fs::remove_file(&file).unwrap();
+}
+
+#[test]
+fn test_fs_storage_entry_name_is_flat() {
+    let storage = FsStorage::new("./data");
+    let name = storage.entry_name("https://example.com/img/a.jpg").unwrap();
+    assert_eq!(name, "img_a.jpg");
+}
+
+#[test]
+fn test_fs_storage_prepare_keeps_files() {
+    let dir = std::env::temp_dir().join("spider_test_fs_storage_keeps");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("test"), b"my file").unwrap();
+    std::fs::write(dir.join(".a.png.0123.spider-tmp"), b"trunc").unwrap();
+    std::fs::File::options()
+        .write(true)
+        .open(dir.join(".a.png.0123.spider-tmp"))
+        .unwrap()
+        .set_modified(SystemTime::now() - Duration::from_secs(2 * 60 * 60))
+        .unwrap();
+    std::fs::write(dir.join(".b.png.4567.spider-tmp"), b"writing").unwrap();
+    let storage = FsStorage::new(dir.to_str().unwrap());
+    assert!(storage.prepare().is_ok());
+    assert_eq!(std::fs::read(dir.join("test")).unwrap(), b"my file");
+    // The temporary files of an interrupted crawl are removed,
+    // the ones another crawl is writing are kept.
+    assert!(!dir.join(".a.png.0123.spider-tmp").exists());
+    assert!(dir.join(".b.png.4567.spider-tmp").exists());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_fs_storage_existing() {
+    let dir = std::env::temp_dir().join("spider_test_fs_storage_existing");
+    let _ = std::fs::remove_dir_all(&dir);
+    let storage = FsStorage::new(dir.to_str().unwrap());
+    assert!(storage.prepare().is_ok());
+    assert!(!storage.keeps_existing("a.png"));
+    storage.put_image("a.png", b"first", "image/png").unwrap();
+    assert!(storage.keeps_existing("a.png"));
+    let err = storage
+        .put_image("a.png", b"second", "image/png")
+        .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::AlreadyExists);
+    assert_eq!(std::fs::read(dir.join("a.png")).unwrap(), b"first");
+
+    let storage = storage.existing(Existing::Rename);
+    assert!(!storage.keeps_existing("a.png"));
+    let location = storage.put_image("a.png", b"second", "image/png").unwrap();
+    assert_eq!(location, dir.join("a-1.png").to_str().unwrap());
+    storage.put_image("a.png", b"third", "image/png").unwrap();
+    assert_eq!(std::fs::read(dir.join("a-2.png")).unwrap(), b"third");
+
+    let storage = storage.existing(Existing::Overwrite);
+    storage.put_image("a.png", b"fourth", "image/png").unwrap();
+    assert_eq!(std::fs::read(dir.join("a.png")).unwrap(), b"fourth");
+    // Only the images are left, no temporary file.
+    let mut files: Vec<String> = std::fs::read_dir(&dir)
+        .unwrap()
+        .flatten()
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .collect();
+    files.sort();
+    assert_eq!(files, ["a-1.png", "a-2.png", "a.png"]);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_numbered_name() {
+    assert_eq!(numbered_name("img_a.png", 1), "img_a-1.png");
+    assert_eq!(numbered_name("archive.tar.gz", 2), "archive.tar-2.gz");
+    assert_eq!(numbered_name("image", 3), "image-3");
+    assert_eq!(numbered_name(".hidden", 1), ".hidden-1");
+}
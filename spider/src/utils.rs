@@ -1,10 +1,18 @@
-use reqwest::blocking::Client;
 use std::fs::File;
-use std::io::{self, Write};
+use std::io::{self, Read};
 use std::path::Path;
 
-use super::parsing::links::get_filename;
+use reqwest::blocking::Response;
+use reqwest::Method;
+
+use super::crawler::{CrawlConfig, ImageFound, ImageSaved, SavedImage};
+use super::http::replay::{self, RecordedResponse};
+use super::http::{FetchError, SClient};
+use super::logging::{self, progress};
+use super::parsing::inline::InlineImage;
+use super::parsing::metadata::ImageMetadata;
 use super::spider::ResponseContent;
+use super::warc;
 
 /// Function that takes a Response object and returns the status code.
 pub fn get_status_code(response: &reqwest::blocking::Response) -> u16 {
@@ -22,53 +30,193 @@ pub fn get_content_type(response: &reqwest::blocking::Response) -> String {
 }
 
 /// Function that is doing a get request against the given URL and returns the response.
-pub fn get_request_url(url: &String) -> Result<ResponseContent, ()> {
-    let http_client = Client::new();
-    let http_result = http_client.get(url).send();
-    if http_result.is_err() {
-        //println!("Error sending request");
-        return Err(());
+/// With an offline source the response is read from it instead.
+/// With a WARC recorder every response, redirects included, is recorded.
+/// The body is counted against the download size limit of the crawl.
+pub fn get_request_url(config: &CrawlConfig, url: &str) -> Result<ResponseContent, FetchError> {
+    let client = &config.client;
+    if let Some(replay) = &client.replay {
+        let response = replay::get(replay.as_ref(), &client.scope, client.max_redirects, url)?;
+        if !config.take_bytes(response.bytes.len()) {
+            return Err(FetchError::OverBudget);
+        }
+        return Ok(response);
+    }
+    if client.recorder.is_some() {
+        return replay::follow(&client.scope, client.max_redirects, url, |current_url| {
+            send_request(config, current_url).map(|(response, _)| response)
+        });
+    }
+    let (response, final_url) = send_request(config, url)?;
+    if (300..400).contains(&response.status) {
+        return Err(FetchError::Redirect(response.location.unwrap_or_default()));
+    }
+    if response.status == 200 {
+        Ok(ResponseContent {
+            url: final_url,
+            content_type: response.content_type,
+            text: String::from_utf8(response.body.clone()).unwrap_or_default(),
+            bytes: response.body,
+        })
+    } else {
+        Err(FetchError::Status(response.status))
+    }
+}
+
+/// Function that sends one GET request and reads the whole response,
+/// returning it with the URL reached after the redirects followed by the client.
+/// The exchange is written to the WARC file if there is one.
+pub fn send_request(
+    config: &CrawlConfig,
+    url: &str,
+) -> Result<(RecordedResponse, String), FetchError> {
+    send(&config.client, Method::GET, url, Some(config))
+}
+
+/// Function that sends one request of the given method, like `send_request`.
+/// With a crawl configuration, the body is read within its download size limit.
+fn send(
+    client: &SClient,
+    method: Method,
+    url: &str,
+    config: Option<&CrawlConfig>,
+) -> Result<(RecordedResponse, String), FetchError> {
+    let request = match method {
+        Method::HEAD => client.head(url),
+        _ => client.get(url),
+    };
+    let request = request.build();
+    if request.is_err() {
+        return Err(FetchError::Network);
+    }
+    let request = request.unwrap();
+    // Credentials are not written to the archive.
+    let request_headers = client
+        .sent_headers(&request)
+        .iter()
+        .filter(|(name, _)| {
+            *name != reqwest::header::AUTHORIZATION && *name != reqwest::header::PROXY_AUTHORIZATION
+        })
+        .map(|(name, value)| {
+            (
+                name.to_string(),
+                value.to_str().unwrap_or_default().to_string(),
+            )
+        })
+        .collect::<Vec<(String, String)>>();
+    client.before_send();
+    let http_result = client.client.execute(request);
+    if let Err(err) = http_result {
+        return Err(FetchError::from_reqwest(&err));
     }
     let http_result = http_result.unwrap();
 
     let status_code = get_status_code(&http_result);
-    if status_code == 200 {
-        let content = {
-            let content_type = get_content_type(&http_result);
-            let content_bytes = {
-                let response = http_result.bytes();
-                if response.is_err() {
-                    //println!("Error getting response bytes");
-                    return Err(());
-                }
-                response.unwrap().to_vec()
-            };
-            let content_string = {
-                let tmp = String::from_utf8(content_bytes.clone());
-                if let Ok(tmp) = tmp {
-                    tmp
-                } else {
-                    String::new()
-                }
-            };
-            ResponseContent {
-                content_type,
-                text: content_string,
-                bytes: content_bytes,
-            }
+    let final_url = http_result.url().to_string();
+    let content_type = get_content_type(&http_result);
+    let location = http_result
+        .headers()
+        .get("Location")
+        .and_then(|location| location.to_str().ok())
+        .map(|location| location.to_string());
+    let response_headers = http_result
+        .headers()
+        .iter()
+        .map(|(name, value)| {
+            (
+                name.to_string(),
+                String::from_utf8_lossy(value.as_bytes()).to_string(),
+            )
+        })
+        .collect::<Vec<(String, String)>>();
+    let reason = http_result
+        .status()
+        .canonical_reason()
+        .unwrap_or("")
+        .to_string();
+    let version = format!("{:?}", http_result.version());
+    let ip_address = http_result.remote_addr().map(|addr| addr.ip().to_string());
+    let content_bytes = match config {
+        Some(config) => read_body(config, http_result)?,
+        None => match http_result.bytes() {
+            Ok(bytes) => bytes.to_vec(),
+            Err(_) => return Err(FetchError::Body),
+        },
+    };
+
+    if let Some(recorder) = &client.recorder {
+        let request = warc::format_request(method.as_str(), &final_url, &request_headers);
+        let response = warc::format_response(
+            &version,
+            status_code,
+            &reason,
+            &response_headers,
+            &content_bytes,
+        );
+        if let Err(err) = recorder.write_exchange(&final_url, &request, &response, ip_address) {
+            logging::error(
+                &format!("Could not write to the WARC file {}: {err}", recorder.path),
+                &[("path", &recorder.path)],
+            );
+        }
+    }
+    let response = RecordedResponse {
+        status: status_code,
+        content_type,
+        location,
+        body: content_bytes,
+    };
+    Ok((response, final_url))
+}
+
+/// Function that reads the body of a response within the download size limit.
+/// A body announced bigger than what is left is not read at all, and the read
+/// stops as soon as the bytes go past the limit.
+fn read_body(config: &CrawlConfig, mut response: Response) -> Result<Vec<u8>, FetchError> {
+    if let (Some(length), Some(remaining)) =
+        (response.content_length(), config.budget.remaining_bytes())
+    {
+        if length > remaining {
+            // The bytes do not fit: the budget runs out without counting them.
+            config.take_bytes(length as usize);
+            return Err(FetchError::OverBudget);
+        }
+    }
+    let mut body = Vec::new();
+    let mut buffer = vec![0; 64 * 1024];
+    loop {
+        // One byte more than what is left is enough to see a body going past the limit.
+        let size = match config.budget.remaining_bytes() {
+            Some(remaining) => (remaining + 1).min(buffer.len() as u64) as usize,
+            None => buffer.len(),
         };
-        Ok(content)
-    } else {
-        //println!("Error getting response: {}", status_code);
-        Err(())
+        let read = match response.read(&mut buffer[..size]) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(_) => return Err(FetchError::Body),
+        };
+        if !config.take_bytes(read) {
+            return Err(FetchError::OverBudget);
+        }
+        body.extend_from_slice(&buffer[..read]);
     }
+    Ok(body)
 }
 
-pub fn write_vec_to_file(data: &[u8], file_path: &str) -> io::Result<()> {
-    let mut file = File::create(Path::new(file_path))?;
-    file.write_all(data)?;
-    file.flush()?;
-    Ok(())
+/// Function that asks for the content type of a URL without downloading it.
+/// Offline, the recorded response is used instead.
+/// The HEAD request is written to the WARC file if there is one.
+pub fn get_content_type_of(client: &SClient, url: &str) -> Result<String, FetchError> {
+    if let Some(replay) = &client.replay {
+        return replay::get(replay.as_ref(), &client.scope, client.max_redirects, url)
+            .map(|response| response.content_type);
+    }
+    let (response, _) = send(client, Method::HEAD, url, None)?;
+    if response.status >= 400 {
+        return Err(FetchError::Status(response.status));
+    }
+    Ok(response.content_type)
 }
 
 /// Function that check if the content type is an image jpg jpeg png gif or bmp.
@@ -81,72 +229,261 @@ pub fn is_image(content_type: &str) -> bool {
         || content_type.contains("image/bmp")
 }
 
-/// Function that takes the url of an image and downloads it to the given path.
-pub fn download_image(url: &String, path: &String) -> Result<(), ()> {
-    let response = get_request_url(url);
-    if response.is_err() {
-        println!("Could not download image {url}");
+/// Function that check if the content type is an HTML document.
+/// A missing content type is unknown, not HTML.
+pub fn is_html(content_type: &str) -> bool {
+    let content_type = content_type.to_lowercase();
+    content_type.contains("text/html") || content_type.contains("application/xhtml+xml")
+}
+
+/// Function that check if a response is an HTML document.
+/// Without content type, the start of the body decides.
+pub fn is_html_response(response: &ResponseContent) -> bool {
+    if response.content_type.trim().is_empty() {
+        return looks_like_html(&response.bytes);
+    }
+    is_html(&response.content_type)
+}
+
+/// Function that check if a body starts like an HTML document,
+/// after an optional byte order mark and whitespace.
+pub fn looks_like_html(body: &[u8]) -> bool {
+    let start = String::from_utf8_lossy(&body[..body.len().min(512)]).to_lowercase();
+    let start = start.trim_start_matches(|c: char| c == '\u{feff}' || c.is_whitespace());
+    [
+        "<!doctype html",
+        "<html",
+        "<head",
+        "<body",
+        "<title",
+        "<!--",
+    ]
+    .iter()
+    .any(|tag| start.starts_with(tag))
+}
+
+/// Function that check if the content type is a stylesheet.
+pub fn is_css(content_type: &str) -> bool {
+    content_type.to_lowercase().starts_with("text/css")
+}
+
+/// Function that downloads an image found on a page to the output directory.
+pub fn download_image(config: &CrawlConfig, image: &ImageFound) -> Result<(), ()> {
+    let url = image.url.as_str();
+    let filename = config.storage.entry_name(url);
+    if filename.is_err() {
+        config.stats.image_failed();
+        return Err(());
+    }
+    let filename = filename.unwrap();
+    if config.storage.keeps_existing(&filename) {
+        logging::info(
+            &format!("⏭️ Already saved: {url} -> {filename}"),
+            &[("url", url), ("name", &filename)],
+        );
+        config.stats.image_skipped();
+        return Err(());
+    }
+    // The image is not downloaded once the image budget is spent.
+    if !config.budget.take_image() {
+        config.stats.image_skipped();
+        return Err(());
+    }
+    let response = get_request_url(config, url);
+    if let Err(FetchError::OverBudget) = response {
+        // The crawl stops on the download size limit, the image is not an error.
+        config.budget.release_image();
+        config.stats.image_skipped();
+        return Err(());
+    }
+    if let Err(err) = response {
+        logging::warn(
+            &format!("Could not download image {url}: {err}"),
+            &[("url", url), ("error", &err.to_string())],
+        );
+        config.budget.release_image();
+        config.stats.image_failed();
         return Err(());
     }
     let response = response.unwrap();
     if !is_image(&response.content_type) {
-        println!("Not an image: {} -> {}", url, response.content_type);
+        logging::warn(
+            &format!("Not an image: {} -> {}", url, response.content_type),
+            &[("url", url), ("content_type", &response.content_type)],
+        );
+        config.budget.release_image();
+        config.stats.image_skipped();
         return Err(());
     }
-    let filename = get_filename(url)?;
-    let file_path = format!("{path}/{filename}");
-    let result = write_vec_to_file(&response.bytes, &file_path);
-    if result.is_err() {
-        println!("Could not write image to file: {file_path}");
+    store_image(
+        config,
+        url,
+        &filename,
+        &response.bytes,
+        &response.content_type,
+        &image.page_url,
+        &image.metadata,
+    )
+}
+
+/// Function that writes an image to the storage and reports it.
+/// The image must have been reserved with `Budget::take_image`,
+/// the reservation is given back if the image is not written.
+pub fn store_image(
+    config: &CrawlConfig,
+    url: &str,
+    filename: &str,
+    bytes: &[u8],
+    content_type: &str,
+    page_url: &str,
+    metadata: &ImageMetadata,
+) -> Result<(), ()> {
+    let result = config.storage.put_image(filename, bytes, content_type);
+    if let Err(err) = result {
+        config.budget.release_image();
+        if err.kind() == io::ErrorKind::AlreadyExists {
+            logging::info(
+                &format!("⏭️ Already saved: {url} -> {filename}"),
+                &[("url", url), ("name", filename)],
+            );
+            config.stats.image_skipped();
+            return Err(());
+        }
+        logging::error(
+            &format!(
+                "Could not write image {filename} to {}: {err}",
+                config.storage.describe()
+            ),
+            &[("url", url), ("name", filename)],
+        );
+        config.stats.image_failed();
         return Err(());
     }
-    println!("🖼️ Downloaded image: {url} -> {file_path}");
+    let file_path = result.unwrap();
+    logging::info(
+        &format!("🖼️ Downloaded image: {url} -> {file_path}"),
+        &[("url", url), ("path", &file_path), ("page_url", page_url)],
+    );
+    progress::image_saved();
+    config.stats.image_saved();
+    config.saved_images.lock().unwrap().push(SavedImage {
+        url: url.to_string(),
+        page_url: page_url.to_string(),
+        name: filename.to_string(),
+        location: file_path.clone(),
+        content_type: content_type.to_string(),
+        bytes: bytes.len(),
+        metadata: metadata.clone(),
+    });
+    config.events.emit_image_saved(&ImageSaved {
+        url: url.to_string(),
+        page_url: page_url.to_string(),
+        path: file_path,
+        bytes: bytes.len(),
+    });
     Ok(())
 }
 
-/// Functiont that takes a Vec of links and downloads all the images to the given path.
-pub fn download_images(links: &Vec<String>, path: &String) {
-    for link in links {
-        let result = download_image(link, path);
-        if result.is_err() {
+/// Function that saves the images embedded in a page, named after their content.
+/// An image already saved from another page is skipped.
+pub fn save_inline_images(
+    config: &CrawlConfig,
+    images: &[InlineImage],
+    page_url: &str,
+    metadata: &ImageMetadata,
+) {
+    for image in images {
+        if config.budget.exhausted().is_some() {
+            break;
+        }
+        let filename = image.filename();
+        let already_saved = config
+            .saved_images
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|saved| saved.name == filename);
+        if already_saved {
             continue;
         }
+        if !config.budget.take_image() {
+            config.stats.image_skipped();
+            break;
+        }
+        let url = format!("data:{}", image.content_type);
+        let _ = store_image(
+            config,
+            &url,
+            &filename,
+            &image.data,
+            &image.content_type,
+            page_url,
+            metadata,
+        );
     }
 }
 
+/// Functiont that takes a Vec of images and downloads them all to the output directory.
+/// The images are split between `config.concurrency` threads.
+pub fn download_images(config: &CrawlConfig, images: &[ImageFound]) {
+    let chunk_size = images.len().div_ceil(config.concurrency.max(1)).max(1);
+    std::thread::scope(|scope| {
+        for chunk in images.chunks(chunk_size) {
+            scope.spawn(move || {
+                for image in chunk {
+                    if config.budget.exhausted().is_some() {
+                        break;
+                    }
+                    let result = download_image(config, image);
+                    if result.is_err() {
+                        continue;
+                    }
+                }
+            });
+        }
+    });
+}
+
 /// Function that check if we can create a folder at the given path.
+/// An existing directory is probed with a new file of a unique name,
+/// so no file of the directory is touched.
 pub fn can_create_folder(path: &String) -> bool {
     let path = Path::new(path);
     if path.exists() {
         if path.is_dir() {
-            let test_file = path.join("test");
-            let result = File::create(&test_file);
-            if result.is_err() {
-                println!("Could not write to directory: {}", path.display());
+            let test_file = path.join(format!(
+                ".spider-write-test-{}",
+                uuid::Uuid::new_v4().simple()
+            ));
+            if File::create_new(&test_file).is_err() {
+                logging::error(
+                    &format!("Could not write to directory: {}", path.display()),
+                    &[],
+                );
                 return false;
             }
             let result = std::fs::remove_file(&test_file);
             if result.is_err() {
-                println!("Could not remove test file from directory: {}", path.display());
+                logging::error(
+                    &format!(
+                        "Could not remove test file from directory: {}",
+                        path.display()
+                    ),
+                    &[],
+                );
                 return false;
             }
             return true;
         }
-        println!("Path is not a directory: {}", path.display());
+        logging::error(&format!("Path is not a directory: {}", path.display()), &[]);
         return false;
     }
     let result = std::fs::create_dir_all(path);
     if result.is_err() {
-        println!("Could not create directory: {}", path.display());
-        return false;
-    }
-    true
-}
-
-/// Function that is making a get request to the given URL to check if we can access it.
-pub fn check_url(url: &String) -> bool {
-    let response = get_request_url(url);
-    if response.is_err() {
+        logging::error(
+            &format!("Could not create directory: {}", path.display()),
+            &[],
+        );
         return false;
     }
     true
@@ -0,0 +1,137 @@
+use flate2::read::GzDecoder;
+use std::io::Read;
+
+/// An HTTP response as stored in a WARC `response` record.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl HttpResponse {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Function that parses a raw HTTP response: status line, headers and body.
+/// Chunked and gzip encoded bodies are decoded.
+pub fn parse_http_response(raw: &[u8]) -> Option<HttpResponse> {
+    let header_end = find(raw, b"\r\n\r\n")
+        .map(|index| (index, index + 4))
+        .or_else(|| find(raw, b"\n\n").map(|index| (index, index + 2)))?;
+    let head = String::from_utf8_lossy(&raw[..header_end.0]);
+    let mut lines = head.lines();
+    let status_line = lines.next()?;
+    if !status_line.starts_with("HTTP/") {
+        return None;
+    }
+    let status = status_line.split_whitespace().nth(1)?.parse::<u16>().ok()?;
+    let headers = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+        .collect::<Vec<(String, String)>>();
+    let mut response = HttpResponse {
+        status,
+        headers,
+        body: raw[header_end.1..].to_vec(),
+    };
+    let chunked = response
+        .header("Transfer-Encoding")
+        .is_some_and(|encoding| encoding.eq_ignore_ascii_case("chunked"));
+    if chunked {
+        response.body = decode_chunked(&response.body).unwrap_or(response.body);
+    }
+    let gzip = response
+        .header("Content-Encoding")
+        .is_some_and(|encoding| encoding.eq_ignore_ascii_case("gzip"));
+    if gzip {
+        let mut body = Vec::new();
+        if GzDecoder::new(&response.body[..])
+            .read_to_end(&mut body)
+            .is_ok()
+        {
+            response.body = body;
+        }
+    }
+    Some(response)
+}
+
+/// Function that formats the raw request sent for a URL, `method` being `GET` or `HEAD`.
+pub fn format_request(method: &str, url: &str, headers: &[(String, String)]) -> Vec<u8> {
+    let parsed_url = url::Url::parse(url);
+    let (target, host) = match &parsed_url {
+        Ok(parsed_url) => {
+            let target = match parsed_url.query() {
+                Some(query) => format!("{}?{query}", parsed_url.path()),
+                None => parsed_url.path().to_string(),
+            };
+            let host = match parsed_url.port() {
+                Some(port) => format!("{}:{port}", parsed_url.host_str().unwrap_or("")),
+                None => parsed_url.host_str().unwrap_or("").to_string(),
+            };
+            (target, host)
+        }
+        Err(_) => (url.to_string(), String::new()),
+    };
+    let mut request = format!("{method} {target} HTTP/1.1\r\nHost: {host}\r\n");
+    for (name, value) in headers {
+        request.push_str(&format!("{name}: {value}\r\n"));
+    }
+    request.push_str("\r\n");
+    request.into_bytes()
+}
+
+/// Function that formats a raw HTTP response from its parts.
+/// The body is the one returned by the client, already de-chunked, so
+/// `Transfer-Encoding` is dropped and `Content-Length` is set to its length.
+pub fn format_response(
+    version: &str,
+    status: u16,
+    reason: &str,
+    headers: &[(String, String)],
+    body: &[u8],
+) -> Vec<u8> {
+    let mut response = format!("{version} {status} {reason}\r\n");
+    for (name, value) in headers {
+        if name.eq_ignore_ascii_case("Transfer-Encoding")
+            || name.eq_ignore_ascii_case("Content-Length")
+        {
+            continue;
+        }
+        response.push_str(&format!("{name}: {value}\r\n"));
+    }
+    response.push_str(&format!("Content-Length: {}\r\n\r\n", body.len()));
+    let mut response = response.into_bytes();
+    response.extend_from_slice(body);
+    response
+}
+
+/// Function that decodes a body sent with `Transfer-Encoding: chunked`.
+pub fn decode_chunked(mut raw: &[u8]) -> Option<Vec<u8>> {
+    let mut body = Vec::new();
+    loop {
+        let line_end = find(raw, b"\r\n")?;
+        let size_line = String::from_utf8_lossy(&raw[..line_end]);
+        let size = usize::from_str_radix(size_line.split(';').next()?.trim(), 16).ok()?;
+        raw = &raw[line_end + 2..];
+        if size == 0 {
+            return Some(body);
+        }
+        if raw.len() < size {
+            return None;
+        }
+        body.extend_from_slice(&raw[..size]);
+        raw = raw.get(size + 2..)?;
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
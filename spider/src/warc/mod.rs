@@ -0,0 +1,39 @@
+pub mod http;
+pub mod reader;
+pub mod writer;
+
+mod tests_http;
+mod tests_reader;
+mod tests_writer;
+
+pub use http::{format_request, format_response, parse_http_response, HttpResponse};
+pub use reader::{read_file, read_records};
+pub use writer::WarcWriter;
+
+/// A WARC record: its named fields and its content block.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WarcRecord {
+    pub version: String,
+    pub headers: Vec<(String, String)>,
+    pub block: Vec<u8>,
+}
+
+impl WarcRecord {
+    /// Function that returns the value of a named field, the name is not case sensitive.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn record_type(&self) -> Option<&str> {
+        self.header("WARC-Type")
+    }
+
+    /// Function that returns the target URI, without the `<>` some writers put around it.
+    pub fn target_uri(&self) -> Option<&str> {
+        self.header("WARC-Target-URI")
+            .map(|uri| uri.trim_start_matches('<').trim_end_matches('>'))
+    }
+}
@@ -0,0 +1,88 @@
+use flate2::read::MultiGzDecoder;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read};
+
+use super::WarcRecord;
+
+/// Function that reads all the records of a WARC file.
+/// Plain files and gzip files (one member per record or for the whole file) are supported.
+pub fn read_file(path: &str) -> io::Result<Vec<WarcRecord>> {
+    let mut file = BufReader::new(File::open(path)?);
+    let is_gzip = file.fill_buf()?.starts_with(&[0x1f, 0x8b]);
+    if is_gzip {
+        read_records(BufReader::new(MultiGzDecoder::new(file)))
+    } else {
+        read_records(file)
+    }
+}
+
+/// Function that reads the records of an uncompressed WARC stream.
+pub fn read_records(mut reader: impl BufRead) -> io::Result<Vec<WarcRecord>> {
+    let mut records = Vec::new();
+    loop {
+        let mut line = String::new();
+        // Records are separated by blank lines.
+        loop {
+            line.clear();
+            if reader.read_line(&mut line)? == 0 {
+                return Ok(records);
+            }
+            if !line.trim().is_empty() {
+                break;
+            }
+        }
+        let version = line.trim().to_string();
+        if !version.starts_with("WARC/") {
+            return Err(invalid_data(format!(
+                "expected a WARC record, got '{version}'"
+            )));
+        }
+
+        let mut headers: Vec<(String, String)> = Vec::new();
+        loop {
+            line.clear();
+            if reader.read_line(&mut line)? == 0 {
+                return Err(invalid_data("truncated WARC record header".to_string()));
+            }
+            let trimmed = line.trim_end_matches(['\r', '\n']);
+            if trimmed.is_empty() {
+                break;
+            }
+            // Folded lines continue the previous field.
+            if trimmed.starts_with([' ', '\t']) {
+                if let Some((_, value)) = headers.last_mut() {
+                    value.push(' ');
+                    value.push_str(trimmed.trim());
+                }
+                continue;
+            }
+            if let Some((name, value)) = trimmed.split_once(':') {
+                headers.push((name.trim().to_string(), value.trim().to_string()));
+            }
+        }
+
+        let length = headers
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case("Content-Length"))
+            .and_then(|(_, value)| value.parse::<usize>().ok())
+            .ok_or_else(|| invalid_data("WARC record without Content-Length".to_string()))?;
+        // The length is not trusted: the block grows with what is actually read.
+        let mut block = Vec::new();
+        (&mut reader).take(length as u64).read_to_end(&mut block)?;
+        if block.len() < length {
+            return Err(invalid_data(format!(
+                "truncated WARC record: {} of {length} bytes",
+                block.len()
+            )));
+        }
+        records.push(WarcRecord {
+            version,
+            headers,
+            block,
+        });
+    }
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
@@ -0,0 +1,45 @@
+#[cfg(test)]
+use super::http::decode_chunked;
+#[cfg(test)]
+use super::parse_http_response;
+#[cfg(test)]
+use flate2::write::GzEncoder;
+#[cfg(test)]
+use std::io::Write;
+
+#[test]
+fn test_parse_http_response() {
+    let response = parse_http_response(
+        b"HTTP/1.1 301 Moved Permanently\r\nLocation: /new\r\nContent-Type: text/html\r\n\r\nmoved",
+    )
+    .unwrap();
+    assert_eq!(response.status, 301);
+    assert_eq!(response.header("location"), Some("/new"));
+    assert_eq!(response.header("Content-Type"), Some("text/html"));
+    assert_eq!(response.body, b"moved");
+    assert!(parse_http_response(b"GET / HTTP/1.1\r\n\r\n").is_none());
+    assert!(parse_http_response(b"HTTP/1.1 200 OK").is_none());
+}
+
+#[test]
+fn test_parse_http_response_encoded() {
+    let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(b"<html></html>").unwrap();
+    let body = encoder.finish().unwrap();
+    let mut raw = b"HTTP/1.1 200 OK\r\nContent-Encoding: gzip\r\n\r\n".to_vec();
+    raw.extend(&body);
+    assert_eq!(parse_http_response(&raw).unwrap().body, b"<html></html>");
+
+    let raw = b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nhello\r\n6\r\n world\r\n0\r\n\r\n";
+    assert_eq!(parse_http_response(raw).unwrap().body, b"hello world");
+}
+
+#[test]
+fn test_decode_chunked() {
+    assert_eq!(
+        decode_chunked(b"3;ext=1\r\nabc\r\n0\r\n\r\n"),
+        Some(b"abc".to_vec())
+    );
+    assert_eq!(decode_chunked(b"a\r\nabc\r\n0\r\n\r\n"), None);
+    assert_eq!(decode_chunked(b"zz\r\n"), None);
+}
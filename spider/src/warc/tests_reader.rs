@@ -0,0 +1,62 @@
+#[cfg(test)]
+use super::read_file;
+#[cfg(test)]
+use super::read_records;
+#[cfg(test)]
+use flate2::write::GzEncoder;
+#[cfg(test)]
+use std::io::Write;
+
+#[cfg(test)]
+fn record(record_type: &str, uri: &str, block: &str) -> String {
+    format!(
+        "WARC/1.1\r\nWARC-Type: {record_type}\r\nWARC-Target-URI: {uri}\r\nContent-Length: {}\r\n\r\n{block}\r\n\r\n",
+        block.len()
+    )
+}
+
+#[test]
+fn test_read_records() {
+    let data = record("request", "http://example.com/", "GET / HTTP/1.1\r\n\r\n")
+        + &record(
+            "response",
+            "<http://example.com/>",
+            "HTTP/1.1 200 OK\r\n\r\nhello",
+        );
+    let records = read_records(data.as_bytes()).unwrap();
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0].version, "WARC/1.1");
+    assert_eq!(records[0].record_type(), Some("request"));
+    assert_eq!(records[1].header("warc-type"), Some("response"));
+    assert_eq!(records[1].target_uri(), Some("http://example.com/"));
+    assert_eq!(records[1].block, b"HTTP/1.1 200 OK\r\n\r\nhello");
+}
+
+#[test]
+fn test_read_records_invalid() {
+    assert!(read_records("HTTP/1.1 200 OK\r\n\r\n".as_bytes()).is_err());
+    assert!(read_records("WARC/1.1\r\nWARC-Type: response\r\n\r\n".as_bytes()).is_err());
+    // A huge length is not allocated up front.
+    let record = "WARC/1.1\r\nContent-Length: 18446744073709551615\r\n\r\nshort";
+    let err = read_records(record.as_bytes()).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    assert!(read_records("".as_bytes()).unwrap().is_empty());
+}
+
+#[test]
+fn test_read_file_gzip_per_record() {
+    let path = std::env::temp_dir().join("spider_test_reader.warc.gz");
+    let mut data = Vec::new();
+    for uri in ["http://example.com/a", "http://example.com/b"] {
+        let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder
+            .write_all(record("response", uri, "HTTP/1.1 200 OK\r\n\r\n").as_bytes())
+            .unwrap();
+        data.extend(encoder.finish().unwrap());
+    }
+    std::fs::write(&path, data).unwrap();
+    let records = read_file(path.to_str().unwrap()).unwrap();
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[1].target_uri(), Some("http://example.com/b"));
+    std::fs::remove_file(&path).unwrap();
+}
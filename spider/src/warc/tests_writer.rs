@@ -0,0 +1,77 @@
+#[cfg(test)]
+use super::writer::format_record;
+#[cfg(test)]
+use super::{format_request, format_response, parse_http_response, read_file, WarcWriter};
+
+#[test]
+fn test_format_request() {
+    let request = format_request(
+        "GET",
+        "http://example.com:8080/a/b.png?x=1#top",
+        &[("accept".to_string(), "*/*".to_string())],
+    );
+    assert_eq!(
+        String::from_utf8(request).unwrap(),
+        "GET /a/b.png?x=1 HTTP/1.1\r\nHost: example.com:8080\r\naccept: */*\r\n\r\n"
+    );
+}
+
+#[test]
+fn test_format_response() {
+    let headers = vec![
+        ("content-type".to_string(), "text/html".to_string()),
+        ("transfer-encoding".to_string(), "chunked".to_string()),
+    ];
+    let response = format_response("HTTP/1.1", 200, "OK", &headers, b"hello");
+    assert_eq!(
+        String::from_utf8(response.clone()).unwrap(),
+        "HTTP/1.1 200 OK\r\ncontent-type: text/html\r\nContent-Length: 5\r\n\r\nhello"
+    );
+    let parsed = parse_http_response(&response).unwrap();
+    assert_eq!(parsed.status, 200);
+    assert_eq!(parsed.body, b"hello");
+}
+
+#[test]
+fn test_format_record() {
+    let record = format_record(&[("WARC-Type", "resource".to_string())], b"abc");
+    let record = String::from_utf8(record).unwrap();
+    assert!(record.starts_with("WARC/1.1\r\nWARC-Type: resource\r\n"));
+    assert!(record.contains(
+        "WARC-Block-Digest: sha256:ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad\r\n"
+    ));
+    assert!(record.ends_with("Content-Length: 3\r\n\r\nabc\r\n\r\n"));
+}
+
+#[test]
+fn test_warc_writer() {
+    let path = std::env::temp_dir().join("spider_test_writer.warc.gz");
+    let path = path.to_str().unwrap();
+    let writer = WarcWriter::create(path).unwrap();
+    let request = format_request("GET", "http://example.com/", &[]);
+    let response = format_response("HTTP/1.1", 200, "OK", &[], b"<html></html>");
+    writer
+        .write_exchange(
+            "http://example.com/",
+            &request,
+            &response,
+            Some("127.0.0.1".to_string()),
+        )
+        .unwrap();
+
+    let data = std::fs::read(path).unwrap();
+    assert_eq!(&data[..2], &[0x1f, 0x8b]);
+    let records = read_file(path).unwrap();
+    assert_eq!(records.len(), 3);
+    assert_eq!(records[0].record_type(), Some("warcinfo"));
+    assert_eq!(records[1].record_type(), Some("request"));
+    assert_eq!(records[2].record_type(), Some("response"));
+    assert_eq!(
+        records[1].header("WARC-Concurrent-To"),
+        records[2].header("WARC-Record-ID")
+    );
+    assert_eq!(records[2].target_uri(), Some("http://example.com/"));
+    assert_eq!(records[2].header("WARC-IP-Address"), Some("127.0.0.1"));
+    assert_eq!(records[2].block, response);
+    std::fs::remove_file(path).unwrap();
+}
@@ -0,0 +1,126 @@
+use chrono::{SecondsFormat, Utc};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use sha2::{Digest, Sha256};
+use std::fs::File;
+use std::io::{self, Write};
+use std::sync::Mutex;
+
+/// WARC/1.1 file written while crawling.
+/// Every record is compressed in its own gzip member, so the file can be
+/// read with any WARC tool and records can be seeked to one by one.
+pub struct WarcWriter {
+    pub path: String,
+    file: Mutex<File>,
+}
+
+impl WarcWriter {
+    /// Function that creates the file and writes the `warcinfo` record.
+    pub fn create(path: &str) -> io::Result<WarcWriter> {
+        let writer = WarcWriter {
+            path: path.to_string(),
+            file: Mutex::new(File::create(path)?),
+        };
+        let info = format!(
+            "software: spider/{}\r\nformat: WARC File Format 1.1\r\n",
+            env!("CARGO_PKG_VERSION")
+        );
+        let filename = std::path::Path::new(path)
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let record = format_record(
+            &[
+                ("WARC-Type", "warcinfo".to_string()),
+                ("WARC-Record-ID", record_id()),
+                ("WARC-Date", warc_date()),
+                ("WARC-Filename", filename),
+                ("Content-Type", "application/warc-fields".to_string()),
+            ],
+            info.as_bytes(),
+        );
+        writer.write_all(&[record])?;
+        Ok(writer)
+    }
+
+    /// Function that writes the request and the response of one fetch.
+    /// `request` and `response` are the raw HTTP messages.
+    pub fn write_exchange(
+        &self,
+        url: &str,
+        request: &[u8],
+        response: &[u8],
+        ip_address: Option<String>,
+    ) -> io::Result<()> {
+        let date = warc_date();
+        let response_id = record_id();
+        let mut response_headers = vec![
+            ("WARC-Type", "response".to_string()),
+            ("WARC-Record-ID", response_id.clone()),
+            ("WARC-Date", date.clone()),
+            ("WARC-Target-URI", url.to_string()),
+            (
+                "Content-Type",
+                "application/http;msgtype=response".to_string(),
+            ),
+        ];
+        if let Some(ip_address) = ip_address {
+            response_headers.push(("WARC-IP-Address", ip_address));
+        }
+        let response_record = format_record(&response_headers, response);
+        let request_record = format_record(
+            &[
+                ("WARC-Type", "request".to_string()),
+                ("WARC-Record-ID", record_id()),
+                ("WARC-Date", date),
+                ("WARC-Target-URI", url.to_string()),
+                ("WARC-Concurrent-To", response_id),
+                (
+                    "Content-Type",
+                    "application/http;msgtype=request".to_string(),
+                ),
+            ],
+            request,
+        );
+        self.write_all(&[request_record, response_record])
+    }
+
+    /// Function that compresses the records and appends them in one write,
+    /// so records of concurrent downloads are never interleaved.
+    fn write_all(&self, records: &[Vec<u8>]) -> io::Result<()> {
+        let mut data = Vec::new();
+        for record in records {
+            let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+            encoder.write_all(record)?;
+            data.extend(encoder.finish()?);
+        }
+        let mut file = self.file.lock().unwrap();
+        file.write_all(&data)?;
+        file.flush()
+    }
+}
+
+/// Function that formats one uncompressed record with its length and block digest.
+pub fn format_record(headers: &[(&str, String)], block: &[u8]) -> Vec<u8> {
+    let mut record = String::from("WARC/1.1\r\n");
+    for (name, value) in headers {
+        record.push_str(&format!("{name}: {value}\r\n"));
+    }
+    record.push_str(&format!(
+        "WARC-Block-Digest: sha256:{}\r\n",
+        hex::encode(Sha256::digest(block))
+    ));
+    record.push_str(&format!("Content-Length: {}\r\n\r\n", block.len()));
+    let mut record = record.into_bytes();
+    record.extend_from_slice(block);
+    record.extend_from_slice(b"\r\n\r\n");
+    record
+}
+
+fn record_id() -> String {
+    format!("<urn:uuid:{}>", uuid::Uuid::new_v4())
+}
+
+fn warc_date() -> String {
+    Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true)
+}
@@ -0,0 +1,238 @@
+//! In-process HTTP server serving a fixture site for the end-to-end tests.
+
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// One canned response of the mock server.
+#[derive(Clone)]
+pub struct MockResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+    pub delay: Option<Duration>,
+    /// Number of requests answered by closing the connection before this response is sent.
+    pub drop_first: usize,
+}
+
+impl MockResponse {
+    pub fn new(status: u16, content_type: &str, body: &[u8]) -> MockResponse {
+        MockResponse {
+            status,
+            headers: vec![("Content-Type".to_string(), content_type.to_string())],
+            body: body.to_vec(),
+            delay: None,
+            drop_first: 0,
+        }
+    }
+
+    pub fn html(body: &str) -> MockResponse {
+        MockResponse::new(200, "text/html; charset=utf-8", body.as_bytes())
+    }
+
+    pub fn image(content_type: &str, body: &[u8]) -> MockResponse {
+        MockResponse::new(200, content_type, body)
+    }
+
+    pub fn redirect(status: u16, location: &str) -> MockResponse {
+        let mut response = MockResponse::new(status, "text/html", b"");
+        response
+            .headers
+            .push(("Location".to_string(), location.to_string()));
+        response
+    }
+
+    pub fn not_found() -> MockResponse {
+        MockResponse::new(404, "text/html", b"not found")
+    }
+
+    pub fn delayed(mut self, delay: Duration) -> MockResponse {
+        self.delay = Some(delay);
+        self
+    }
+
+    pub fn dropped(mut self, times: usize) -> MockResponse {
+        self.drop_first = times;
+        self
+    }
+}
+
+/// HTTP/1.1 server listening on a random local port until the test ends.
+/// Every request is counted, so tests can check what was fetched and how often.
+pub struct MockServer {
+    pub port: u16,
+    hits: Arc<Mutex<HashMap<String, usize>>>,
+}
+
+impl MockServer {
+    pub fn start(routes: HashMap<String, MockResponse>) -> MockServer {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let hits = Arc::new(Mutex::new(HashMap::new()));
+        let routes = Arc::new(routes);
+        let server_hits = Arc::clone(&hits);
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let routes = Arc::clone(&routes);
+                let hits = Arc::clone(&server_hits);
+                thread::spawn(move || handle(stream, &routes, &hits));
+            }
+        });
+        MockServer { port, hits }
+    }
+
+    pub fn url(&self, path: &str) -> String {
+        format!("http://127.0.0.1:{}{path}", self.port)
+    }
+
+    /// Number of requests received for the path.
+    /// GET requests are counted under the path, other methods under `"METHOD path"`.
+    pub fn hits(&self, path: &str) -> usize {
+        *self.hits.lock().unwrap().get(path).unwrap_or(&0)
+    }
+}
+
+fn handle(
+    mut stream: TcpStream,
+    routes: &HashMap<String, MockResponse>,
+    hits: &Mutex<HashMap<String, usize>>,
+) {
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    let mut request_line = String::new();
+    if reader.read_line(&mut request_line).is_err() {
+        return;
+    }
+    let mut line = String::new();
+    while reader.read_line(&mut line).is_ok_and(|read| read > 2) {
+        line.clear();
+    }
+    let mut words = request_line.split_whitespace();
+    let method = words.next().unwrap_or("GET").to_string();
+    let target = words.next().unwrap_or("/").to_string();
+    let key = match method.as_str() {
+        "GET" => target.clone(),
+        _ => format!("{method} {target}"),
+    };
+    let count = {
+        let mut hits = hits.lock().unwrap();
+        let count = hits.entry(key).or_insert(0);
+        *count += 1;
+        *count
+    };
+    let path = target.split('?').next().unwrap_or("/");
+    let response = routes
+        .get(&target)
+        .or_else(|| routes.get(path))
+        .cloned()
+        .unwrap_or_else(MockResponse::not_found);
+    if count <= response.drop_first {
+        return;
+    }
+    if let Some(delay) = response.delay {
+        thread::sleep(delay);
+    }
+    let mut head = format!("HTTP/1.1 {} Mock\r\n", response.status);
+    for (name, value) in &response.headers {
+        head.push_str(&format!("{name}: {value}\r\n"));
+    }
+    head.push_str(&format!(
+        "Content-Length: {}\r\nConnection: close\r\n\r\n",
+        response.body.len()
+    ));
+    let _ = stream.write_all(head.as_bytes());
+    if method != "HEAD" {
+        let _ = stream.write_all(&response.body);
+    }
+    let _ = stream.flush();
+}
+
+pub const PNG: &[u8] = &[0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+pub const JPEG: &[u8] = &[0xff, 0xd8, 0xff, 0xe0, 0x00, 0x10];
+pub const GIF: &[u8] = b"GIF89a";
+
+/// Function that builds the fixture site:
+/// - `/` links to three levels of pages, a redirect, a missing page and a slow page
+/// - images are served with the right content type, except `/img/fake.png` which is HTML
+/// - `/flaky.html` drops the first connection, to exercise the retries
+pub fn fixture_site() -> HashMap<String, MockResponse> {
+    let mut routes = HashMap::new();
+    let mut add = |path: &str, response: MockResponse| {
+        routes.insert(path.to_string(), response);
+    };
+    add(
+        "/",
+        MockResponse::html(
+            r#"<html><body>
+            <a href="/page1.html">page 1</a>
+            <a href="/redirect">redirect</a>
+            <a href="/missing.html">missing</a>
+            <img src="/img/a.png">
+            <img src="img/b.jpg">
+            <img src="/img/fake.png">
+            <img src="/img/missing.png">
+            </body></html>"#,
+        ),
+    );
+    add(
+        "/page1.html",
+        MockResponse::html(
+            r#"<a href="/page2.html">page 2</a><img src="/img/c.gif"><img src="/img/a.png">"#,
+        ),
+    );
+    add(
+        "/page2.html",
+        MockResponse::html(r#"<a href="/page3.html">page 3</a><img src="/img/d.png">"#),
+    );
+    add(
+        "/page3.html",
+        MockResponse::html(r#"<img src="/img/e.png">"#),
+    );
+    add("/redirect", MockResponse::redirect(302, "/page1.html"));
+    add(
+        "/slow.html",
+        MockResponse::html(r#"<img src="/img/slow.png">"#).delayed(Duration::from_millis(300)),
+    );
+    add(
+        "/flaky.html",
+        MockResponse::html(r#"<img src="/img/flaky.png">"#).dropped(1),
+    );
+    add("/img/a.png", MockResponse::image("image/png", PNG));
+    add("/img/b.jpg", MockResponse::image("image/jpeg", JPEG));
+    add("/img/c.gif", MockResponse::image("image/gif", GIF));
+    add("/img/d.png", MockResponse::image("image/png", PNG));
+    add("/img/e.png", MockResponse::image("image/png", PNG));
+    add("/img/slow.png", MockResponse::image("image/png", PNG));
+    add("/img/flaky.png", MockResponse::image("image/png", PNG));
+    add(
+        "/img/fake.png",
+        MockResponse::html("<html>not an image</html>"),
+    );
+    routes
+}
+
+/// Function that returns an empty output directory for a test.
+pub fn output_dir(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("spider_it_{name}"));
+    let _ = std::fs::remove_dir_all(&path);
+    path
+}
+
+/// Function that lists the files of a directory, sorted.
+pub fn list_files(path: &PathBuf) -> Vec<String> {
+    let mut files = std::fs::read_dir(path)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| entry.file_name().to_string_lossy().to_string())
+                .collect::<Vec<String>>()
+        })
+        .unwrap_or_default();
+    files.sort();
+    files
+}
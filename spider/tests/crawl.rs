@@ -0,0 +1,757 @@
+mod common;
+
+use common::{fixture_site, list_files, output_dir, MockResponse, MockServer, PNG};
+use spider::crawler::Limit;
+use spider::http::ClientOptions;
+use spider::storage::{Existing, FsStorage};
+use spider::{CrawlError, Crawler};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[test]
+fn test_single_page() {
+    let server = MockServer::start(fixture_site());
+    let output = output_dir("single_page");
+    let report = Crawler::builder(&server.url("/"))
+        .output_dir(output.to_str().unwrap())
+        .build()
+        .unwrap()
+        .run()
+        .unwrap();
+
+    assert_eq!(report.images.len(), 4);
+    assert_eq!(report.saved_images.len(), 2);
+    assert_eq!(list_files(&output), vec!["img_a.png", "img_b.jpg"]);
+    assert_eq!(std::fs::read(output.join("img_a.png")).unwrap(), PNG);
+    // The seed is fetched once, to check it and to crawl it.
+    assert_eq!(server.hits("/"), 1);
+    // Not recursive: the links of the seed are not followed.
+    assert_eq!(server.hits("/page1.html"), 0);
+    // The image served as HTML is fetched but not saved.
+    assert_eq!(server.hits("/img/fake.png"), 1);
+    assert_eq!(server.hits("/img/missing.png"), 1);
+}
+
+#[test]
+fn test_recursion_depth() {
+    let server = MockServer::start(fixture_site());
+    let output = output_dir("recursion_depth");
+    let report = Crawler::builder(&server.url("/"))
+        .recursive(true)
+        .depth(2)
+        .output_dir(output.to_str().unwrap())
+        .build()
+        .unwrap()
+        .run()
+        .unwrap();
+
+    // Once directly and once through `/redirect`.
+    assert_eq!(server.hits("/page1.html"), 2);
+    assert_eq!(server.hits("/page2.html"), 1);
+    // The links of the pages of the last level are still followed.
+    assert_eq!(server.hits("/page3.html"), 1);
+    assert_eq!(
+        list_files(&output),
+        vec![
+            "img_a.png",
+            "img_b.jpg",
+            "img_c.gif",
+            "img_d.png",
+            "img_e.png"
+        ]
+    );
+    // `/img/a.png` is on two pages but downloaded once.
+    assert_eq!(server.hits("/img/a.png"), 1);
+    assert!(report
+        .visited_urls
+        .iter()
+        .any(|url| url.ends_with("/missing.html")));
+}
+
+#[test]
+fn test_redirect_is_crawled_once() {
+    let server = MockServer::start(fixture_site());
+    let output = output_dir("redirect");
+    let fetched = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let events = Arc::clone(&fetched);
+    Crawler::builder(&server.url("/"))
+        .recursive(true)
+        .depth(1)
+        .output_dir(output.to_str().unwrap())
+        .on_page_fetched(move |page| {
+            events.lock().unwrap().push(page.final_url.clone());
+        })
+        .build()
+        .unwrap()
+        .run()
+        .unwrap();
+
+    assert_eq!(server.hits("/redirect"), 1);
+    // The redirect target is fetched again but recognized as already crawled.
+    assert_eq!(server.hits("/page1.html"), 2);
+    assert_eq!(server.hits("/page2.html"), 1);
+    assert_eq!(server.hits("/page3.html"), 0);
+    assert_eq!(server.hits("/img/c.gif"), 1);
+    // The page reached twice is reported once to the callbacks.
+    let fetched = fetched.lock().unwrap();
+    let page1 = fetched.iter().filter(|url| url.ends_with("/page1.html"));
+    assert_eq!(page1.count(), 1);
+}
+
+#[test]
+fn test_seed_redirect() {
+    let mut routes = fixture_site();
+    routes.insert(
+        "/start".to_string(),
+        MockResponse::redirect(301, "/page3.html"),
+    );
+    let server = MockServer::start(routes);
+    let output = output_dir("seed_redirect");
+    let report = Crawler::builder(&server.url("/start"))
+        .output_dir(output.to_str().unwrap())
+        .build()
+        .unwrap()
+        .run()
+        .unwrap();
+
+    assert_eq!(report.seed, server.url("/page3.html"));
+    assert_eq!(list_files(&output), vec!["img_e.png"]);
+}
+
+#[test]
+fn test_retry_after_dropped_connection() {
+    let mut routes = fixture_site();
+    routes.insert(
+        "/retry.html".to_string(),
+        MockResponse::html(r#"<a href="/flaky.html">flaky</a>"#),
+    );
+    let server = MockServer::start(routes);
+    let output = output_dir("retry");
+    let pages = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&pages);
+    Crawler::builder(&server.url("/retry.html"))
+        .recursive(true)
+        .depth(1)
+        .output_dir(output.to_str().unwrap())
+        .on_page_fetched(move |_| {
+            counter.fetch_add(1, Ordering::SeqCst);
+        })
+        .build()
+        .unwrap()
+        .run()
+        .unwrap();
+
+    // The first connection is closed without a response, the second one succeeds.
+    assert_eq!(server.hits("/flaky.html"), 2);
+    assert_eq!(pages.load(Ordering::SeqCst), 2);
+    assert_eq!(list_files(&output), vec!["img_flaky.png"]);
+}
+
+#[test]
+fn test_slow_response() {
+    let server = MockServer::start(fixture_site());
+    let output = output_dir("slow");
+    let report = Crawler::builder(&server.url("/slow.html"))
+        .output_dir(output.to_str().unwrap())
+        .build()
+        .unwrap()
+        .run()
+        .unwrap();
+
+    assert_eq!(report.saved_images.len(), 1);
+    assert_eq!(list_files(&output), vec!["img_slow.png"]);
+}
+
+#[test]
+fn test_concurrent_downloads() {
+    let mut routes = fixture_site();
+    let mut page = String::new();
+    for index in 0..8 {
+        page.push_str(&format!("<img src=\"/img/{index}.png\">"));
+        routes.insert(
+            format!("/img/{index}.png"),
+            MockResponse::image("image/png", PNG).delayed(Duration::from_millis(100)),
+        );
+    }
+    routes.insert("/gallery.html".to_string(), MockResponse::html(&page));
+    let server = MockServer::start(routes);
+    let output = output_dir("concurrent");
+    let saved = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&saved);
+    let report = Crawler::builder(&server.url("/gallery.html"))
+        .output_dir(output.to_str().unwrap())
+        .concurrency(4)
+        .on_image_saved(move |_| {
+            counter.fetch_add(1, Ordering::SeqCst);
+        })
+        .build()
+        .unwrap()
+        .run()
+        .unwrap();
+
+    assert_eq!(report.saved_images.len(), 8);
+    assert_eq!(saved.load(Ordering::SeqCst), 8);
+    assert_eq!(list_files(&output).len(), 8);
+}
+
+#[test]
+fn test_manifest() {
+    let server = MockServer::start(fixture_site());
+    let output = output_dir("manifest");
+    Crawler::builder(&server.url("/"))
+        .output_dir(output.to_str().unwrap())
+        .manifest(true)
+        .build()
+        .unwrap()
+        .run()
+        .unwrap();
+
+    let manifest = std::fs::read_to_string(output.join("manifest.json")).unwrap();
+    let manifest: serde_json::Value = serde_json::from_str(&manifest).unwrap();
+    assert_eq!(manifest["seed"], server.url("/"));
+    assert_eq!(manifest["images"].as_array().unwrap().len(), 2);
+    assert_eq!(manifest["images"][0]["page_url"], server.url("/"));
+}
+
+#[test]
+fn test_unreachable_seed() {
+    let server = MockServer::start(fixture_site());
+    let output = output_dir("unreachable");
+    let result = Crawler::builder(&server.url("/missing.html"))
+        .output_dir(output.to_str().unwrap())
+        .build()
+        .unwrap()
+        .run();
+
+    assert!(matches!(result, Err(CrawlError::Seed(_, _))));
+    assert!(list_files(&output).is_empty());
+}
+
+#[test]
+fn test_warc_record_and_replay() {
+    let server = MockServer::start(fixture_site());
+    let warc = output_dir("replay.warc.gz");
+    let recorded = output_dir("recorded");
+    let options = ClientOptions {
+        warc: Some(warc.to_str().unwrap().to_string()),
+        ..ClientOptions::default()
+    };
+    Crawler::builder(&server.url("/"))
+        .recursive(true)
+        .depth(1)
+        .output_dir(recorded.to_str().unwrap())
+        .client_options(options)
+        .build()
+        .unwrap()
+        .run()
+        .unwrap();
+
+    let replayed = output_dir("replayed");
+    let options = ClientOptions {
+        offline: Some(warc.to_str().unwrap().to_string()),
+        ..ClientOptions::default()
+    };
+    let hits = server.hits("/");
+    Crawler::builder(&server.url("/"))
+        .recursive(true)
+        .depth(1)
+        .output_dir(replayed.to_str().unwrap())
+        .client_options(options)
+        .build()
+        .unwrap()
+        .run()
+        .unwrap();
+
+    assert_eq!(server.hits("/"), hits);
+    assert_eq!(list_files(&replayed), list_files(&recorded));
+    assert_eq!(list_files(&replayed).len(), 4);
+}
+
+#[test]
+fn test_warc_records_the_sent_headers() {
+    let mut routes = HashMap::new();
+    let mut page = MockResponse::html(r#"<a href="/notes.xyz">notes</a>"#);
+    page.headers
+        .push(("Set-Cookie".to_string(), "session=abc".to_string()));
+    routes.insert("/".to_string(), page);
+    routes.insert(
+        "/notes.xyz".to_string(),
+        MockResponse::new(200, "text/plain", b"notes"),
+    );
+    let server = MockServer::start(routes);
+    let warc = output_dir("headers.warc.gz");
+    let options = ClientOptions {
+        warc: Some(warc.to_str().unwrap().to_string()),
+        headers: vec![("User-Agent".to_string(), "spider-test".to_string())],
+        ..ClientOptions::default()
+    };
+    Crawler::builder(&server.url("/"))
+        .recursive(true)
+        .depth(1)
+        .output_dir(output_dir("headers").to_str().unwrap())
+        .client_options(options)
+        .build()
+        .unwrap()
+        .run()
+        .unwrap();
+
+    let requests: Vec<String> = spider::warc::read_file(warc.to_str().unwrap())
+        .unwrap()
+        .into_iter()
+        .filter(|record| record.record_type() == Some("request"))
+        .map(|record| String::from_utf8(record.block).unwrap())
+        .collect();
+    assert_eq!(requests.len(), 2);
+    assert!(requests[0].starts_with("GET / HTTP/1.1\r\n"));
+    assert!(requests[0].contains("\r\nuser-agent: spider-test\r\n"));
+    // The link with an unknown extension is checked with a HEAD request,
+    // sent with the cookie set by the page.
+    assert!(requests[1].starts_with("HEAD /notes.xyz HTTP/1.1\r\n"));
+    assert!(requests[1].contains("\r\nuser-agent: spider-test\r\n"));
+    assert!(requests[1].contains("\r\ncookie: session=abc\r\n"));
+    assert_eq!(server.hits("HEAD /notes.xyz"), 1);
+    assert_eq!(server.hits("/notes.xyz"), 0);
+}
+
+#[test]
+fn test_responses_without_content_type() {
+    let untyped = |body: &[u8]| {
+        let mut response = MockResponse::new(200, "", body);
+        response.headers.clear();
+        response
+    };
+    let mut routes = fixture_site();
+    routes.insert(
+        "/untyped.html".to_string(),
+        untyped(
+            br#"<!DOCTYPE html><a href="/raw">raw</a><a href="/blob.xyz">blob</a>
+            <img src="/img/a.png">"#,
+        ),
+    );
+    routes.insert(
+        "/raw".to_string(),
+        untyped(b"\x00\x01binary<a href=\"/page1.html\">"),
+    );
+    routes.insert("/blob.xyz".to_string(), untyped(b"\x00\x01binary"));
+    let server = MockServer::start(routes);
+    let output = output_dir("untyped");
+    Crawler::builder(&server.url("/untyped.html"))
+        .recursive(true)
+        .depth(2)
+        .output_dir(output.to_str().unwrap())
+        .build()
+        .unwrap()
+        .run()
+        .unwrap();
+
+    // The body of the seed looks like HTML, so it is crawled.
+    assert_eq!(list_files(&output), vec!["img_a.png"]);
+    // A binary body is not parsed for links.
+    assert_eq!(server.hits("/raw"), 1);
+    assert_eq!(server.hits("/page1.html"), 0);
+    // A HEAD response without content type does not make a page.
+    assert_eq!(server.hits("HEAD /blob.xyz"), 1);
+    assert_eq!(server.hits("/blob.xyz"), 0);
+}
+
+#[test]
+fn test_stylesheet_images() {
+    let mut routes = fixture_site();
+    routes.insert(
+        "/styled.html".to_string(),
+        MockResponse::html(
+            r#"<link rel="stylesheet" href="/css/style.css"><a href="/page1.html">page 1</a>"#,
+        ),
+    );
+    routes.insert(
+        "/css/style.css".to_string(),
+        MockResponse::new(
+            200,
+            "text/css",
+            br#"@import "print.css"; body { background: url(../img/a.png); }"#,
+        ),
+    );
+    routes.insert(
+        "/css/print.css".to_string(),
+        MockResponse::new(
+            200,
+            "text/css",
+            br#".hero { background: image-set("icons/hero.png" 1x); } @font-face { src: url(font.woff); }"#,
+        ),
+    );
+    routes.insert(
+        "/css/icons/hero.png".to_string(),
+        MockResponse::image("image/png", PNG),
+    );
+    let server = MockServer::start(routes);
+    let output = output_dir("stylesheet");
+    let report = Crawler::builder(&server.url("/styled.html"))
+        .output_dir(output.to_str().unwrap())
+        .build()
+        .unwrap()
+        .run()
+        .unwrap();
+
+    assert_eq!(list_files(&output), vec!["css_icons_hero.png", "img_a.png"]);
+    assert!(report
+        .saved_images
+        .iter()
+        .all(|image| image.page_url == server.url("/styled.html")));
+    assert_eq!(server.hits("/css/style.css"), 1);
+    assert_eq!(server.hits("/css/font.woff"), 0);
+    // Not recursive: the stylesheets are crawled, the other links are not.
+    assert_eq!(server.hits("/page1.html"), 0);
+}
+
+#[test]
+fn test_assets_are_not_crawled_as_pages() {
+    let mut routes = fixture_site();
+    routes.insert(
+        "/assets.html".to_string(),
+        MockResponse::html(
+            r#"<script src="/app.js"></script>
+            <a href="/app.js">script</a>
+            <a href="/files/report.pdf">report</a>
+            <a href="/fonts/font.woff2">font</a>
+            <a href="mailto:someone@example.com">mail</a>
+            <a href="javascript:void(0)">menu</a>
+            <a href="/download.cgi">download</a>
+            <a href="/gallery.cgi">gallery</a>
+            <a href="/page3.html">page 3</a>"#,
+        ),
+    );
+    routes.insert(
+        "/download.cgi".to_string(),
+        MockResponse::new(200, "application/zip", b"PK"),
+    );
+    routes.insert(
+        "/gallery.cgi".to_string(),
+        MockResponse::html(r#"<img src="/img/d.png">"#),
+    );
+    let server = MockServer::start(routes);
+    let output = output_dir("assets");
+    Crawler::builder(&server.url("/assets.html"))
+        .recursive(true)
+        .depth(1)
+        .output_dir(output.to_str().unwrap())
+        .build()
+        .unwrap()
+        .run()
+        .unwrap();
+
+    assert_eq!(server.hits("/app.js"), 0);
+    assert_eq!(server.hits("/files/report.pdf"), 0);
+    assert_eq!(server.hits("/fonts/font.woff2"), 0);
+    // Unknown extensions are checked with a HEAD request first.
+    assert_eq!(server.hits("HEAD /download.cgi"), 1);
+    assert_eq!(server.hits("/download.cgi"), 0);
+    assert_eq!(server.hits("HEAD /gallery.cgi"), 1);
+    assert_eq!(server.hits("/gallery.cgi"), 1);
+    assert_eq!(server.hits("/page3.html"), 1);
+    assert_eq!(list_files(&output), vec!["img_d.png", "img_e.png"]);
+}
+
+#[test]
+fn test_inline_images() {
+    let mut routes = fixture_site();
+    let page = r#"<img src="data:image/png;base64,iVBORw0KGgoAAAA=">
+        <img src="data:image/png;base64,iVBORw0KGgoAAAA=">
+        <svg width="1" height="1"><rect/></svg>
+        <a href="/inline2.html">more</a>"#;
+    routes.insert("/inline.html".to_string(), MockResponse::html(page));
+    routes.insert(
+        "/inline2.html".to_string(),
+        MockResponse::html(r#"<img src="data:image/png;base64,iVBORw0KGgoAAAA=">"#),
+    );
+    let server = MockServer::start(routes);
+
+    let output = output_dir("inline");
+    let report = Crawler::builder(&server.url("/inline.html"))
+        .recursive(true)
+        .depth(1)
+        .output_dir(output.to_str().unwrap())
+        .build()
+        .unwrap()
+        .run()
+        .unwrap();
+    let files = list_files(&output);
+    assert_eq!(files.len(), 1);
+    assert!(files[0].ends_with(".png"));
+    assert_eq!(report.saved_images[0].url, "data:image/png");
+    assert_eq!(report.saved_images[0].page_url, server.url("/inline.html"));
+
+    let output = output_dir("inline_svg");
+    Crawler::builder(&server.url("/inline.html"))
+        .inline_svg(true)
+        .output_dir(output.to_str().unwrap())
+        .build()
+        .unwrap()
+        .run()
+        .unwrap();
+    let files = list_files(&output);
+    assert_eq!(files.len(), 2);
+    assert!(files.iter().any(|file| file.ends_with(".svg")));
+}
+
+#[test]
+fn test_dry_run() {
+    let server = MockServer::start(fixture_site());
+    let output = output_dir("dry_run");
+    let report = Crawler::builder(&server.url("/"))
+        .recursive(true)
+        .depth(1)
+        .output_dir(output.to_str().unwrap())
+        .manifest(true)
+        .dry_run(true)
+        .build()
+        .unwrap()
+        .run()
+        .unwrap();
+
+    assert_eq!(server.hits("/page1.html"), 2);
+    assert_eq!(report.images.len(), 6);
+    assert_eq!(report.found_images.len(), 6);
+    assert!(report
+        .found_images
+        .iter()
+        .any(|image| image.url == server.url("/img/c.gif")
+            && image.page_url == server.url("/page1.html")));
+    assert!(report.saved_images.is_empty());
+    // Nothing is downloaded nor written.
+    assert_eq!(server.hits("/img/a.png"), 0);
+    assert!(!output.exists());
+}
+
+#[test]
+fn test_list_links() {
+    let mut routes = fixture_site();
+    routes.insert(
+        "/list.html".to_string(),
+        MockResponse::html(
+            r#"<link rel="stylesheet" href="/style.css">
+            <a href="/page1.html">page</a><a href="/search.cgi">search</a>
+            <a href="mailto:me@example.com">mail</a><a href="/doc.pdf">doc</a>
+            <img src="/img/a.png"><img src="data:image/png;base64,iVBORw0KGgo=">"#,
+        ),
+    );
+    let server = MockServer::start(routes);
+    let links = Crawler::builder(&server.url("/list.html"))
+        .build()
+        .unwrap()
+        .list()
+        .unwrap();
+
+    assert_eq!(links.url, server.url("/list.html"));
+    assert_eq!(links.pages, vec![server.url("/page1.html")]);
+    assert_eq!(links.unknown, vec![server.url("/search.cgi")]);
+    assert_eq!(links.images, vec![server.url("/img/a.png")]);
+    assert_eq!(links.stylesheets, vec![server.url("/style.css")]);
+    assert_eq!(links.assets, vec![server.url("/doc.pdf")]);
+    assert_eq!(links.ignored, vec!["mailto:me@example.com"]);
+    assert_eq!(links.inline_images, 1);
+    // Only the page itself is fetched.
+    assert_eq!(server.hits("/page1.html"), 0);
+    assert_eq!(server.hits("/img/a.png"), 0);
+}
+
+#[test]
+fn test_max_pages() {
+    let server = MockServer::start(fixture_site());
+    let output = output_dir("max_pages");
+    let report = Crawler::builder(&server.url("/page1.html"))
+        .recursive(true)
+        .depth(5)
+        .max_pages(2)
+        .output_dir(output.to_str().unwrap())
+        .build()
+        .unwrap()
+        .run()
+        .unwrap();
+
+    assert_eq!(report.budget_hit, Some(Limit::Pages));
+    assert_eq!(server.hits("/page2.html"), 1);
+    assert_eq!(server.hits("/page3.html"), 0);
+    assert_eq!(
+        list_files(&output),
+        vec!["img_a.png", "img_c.gif", "img_d.png"]
+    );
+}
+
+#[test]
+fn test_max_images() {
+    let server = MockServer::start(fixture_site());
+    let output = output_dir("max_images");
+    let report = Crawler::builder(&server.url("/"))
+        .recursive(true)
+        .depth(3)
+        .max_images(2)
+        .manifest(true)
+        .output_dir(output.to_str().unwrap())
+        .build()
+        .unwrap()
+        .run()
+        .unwrap();
+
+    assert_eq!(report.budget_hit, Some(Limit::Images));
+    assert_eq!(report.saved_images.len(), 2);
+    assert_eq!(
+        list_files(&output),
+        vec!["img_a.png", "img_b.jpg", "manifest.json"]
+    );
+    // The image found after the last one allowed is not downloaded.
+    assert_eq!(server.hits("/img/c.gif"), 0);
+    // The crawl stopped before following the links.
+    assert_eq!(server.hits("/page2.html"), 0);
+}
+
+#[test]
+fn test_max_bytes() {
+    let mut routes = fixture_site();
+    let mut big = PNG.to_vec();
+    big.resize(100_000, 0);
+    routes.insert(
+        "/big.html".to_string(),
+        MockResponse::html(r#"<img src="/img/big.png">"#),
+    );
+    routes.insert(
+        "/img/big.png".to_string(),
+        MockResponse::image("image/png", &big),
+    );
+    let server = MockServer::start(routes);
+    let output = output_dir("max_bytes");
+    let report = Crawler::builder(&server.url("/big.html"))
+        .max_bytes(1000)
+        .output_dir(output.to_str().unwrap())
+        .build()
+        .unwrap()
+        .run()
+        .unwrap();
+
+    // The image is bigger than what is left of the budget, its body is not read.
+    assert_eq!(report.budget_hit, Some(Limit::Bytes));
+    assert_eq!(server.hits("/img/big.png"), 1);
+    assert!(list_files(&output).is_empty());
+    assert!(report.stats.bytes <= 1000);
+    assert!(!report.stats.has_failures());
+}
+
+#[test]
+fn test_no_budget_hit() {
+    let server = MockServer::start(fixture_site());
+    let report = Crawler::builder(&server.url("/page3.html"))
+        .max_pages(1)
+        .max_images(1)
+        .output_dir(output_dir("no_budget_hit").to_str().unwrap())
+        .build()
+        .unwrap()
+        .run()
+        .unwrap();
+
+    assert_eq!(report.budget_hit, None);
+    assert_eq!(report.saved_images.len(), 1);
+}
+
+#[test]
+fn test_stats() {
+    let server = MockServer::start(fixture_site());
+    let report = Crawler::builder(&server.url("/"))
+        .output_dir(output_dir("stats").to_str().unwrap())
+        .build()
+        .unwrap()
+        .run()
+        .unwrap();
+
+    assert_eq!(report.stats.pages_fetched, 1);
+    assert_eq!(report.stats.pages_failed, 0);
+    // a.png and b.jpg are saved, fake.png is HTML and missing.png is a 404.
+    assert_eq!(report.stats.images_saved, 2);
+    assert_eq!(report.stats.images_skipped, 1);
+    assert_eq!(report.stats.images_failed, 1);
+    assert!(report.stats.has_failures());
+    assert!(report.stats.bytes > 0);
+}
+
+#[test]
+fn test_existing_images() {
+    let server = MockServer::start(fixture_site());
+    let output = output_dir("existing_images");
+    let crawl = |existing: Existing| {
+        Crawler::builder(&server.url("/"))
+            .storage(FsStorage::new(output.to_str().unwrap()).existing(existing))
+            .build()
+            .unwrap()
+            .run()
+            .unwrap()
+    };
+
+    assert_eq!(crawl(Existing::Skip).stats.images_saved, 2);
+    // The images already saved are not downloaded again.
+    let report = crawl(Existing::Skip);
+    assert_eq!(report.stats.images_saved, 0);
+    assert_eq!(report.stats.images_skipped, 3);
+    assert_eq!(server.hits("/img/a.png"), 1);
+
+    assert_eq!(crawl(Existing::Rename).stats.images_saved, 2);
+    assert_eq!(
+        list_files(&output),
+        ["img_a-1.png", "img_a.png", "img_b-1.jpg", "img_b.jpg"]
+    );
+
+    assert_eq!(crawl(Existing::Overwrite).stats.images_saved, 2);
+    assert_eq!(list_files(&output).len(), 4);
+    assert_eq!(server.hits("/img/a.png"), 3);
+}
+
+#[test]
+fn test_image_metadata() {
+    let mut routes = HashMap::new();
+    routes.insert(
+        "/".to_string(),
+        MockResponse::html(
+            r#"<html><head><title>Gallery</title></head><body>
+            <figure><img src="/a.png" alt="A cat" width="640" height="480">
+            <figcaption>Tom, after lunch</figcaption></figure>
+            <div style="background-image: url(/b.png)"></div>
+            </body></html>"#,
+        ),
+    );
+    routes.insert("/a.png".to_string(), MockResponse::image("image/png", PNG));
+    routes.insert("/b.png".to_string(), MockResponse::image("image/png", PNG));
+    let server = MockServer::start(routes);
+    let output = output_dir("image_metadata");
+    let report = Crawler::builder(&server.url("/"))
+        .output_dir(output.to_str().unwrap())
+        .manifest(true)
+        .build()
+        .unwrap()
+        .run()
+        .unwrap();
+
+    let mut saved_images = report.saved_images.clone();
+    saved_images.sort_by(|a, b| a.url.cmp(&b.url));
+    let metadata = &saved_images[0].metadata;
+    assert_eq!(metadata.alt.as_deref(), Some("A cat"));
+    assert_eq!(metadata.width.as_deref(), Some("640"));
+    assert_eq!(metadata.height.as_deref(), Some("480"));
+    assert_eq!(metadata.figcaption.as_deref(), Some("Tom, after lunch"));
+    assert_eq!(metadata.page_title.as_deref(), Some("Gallery"));
+    // An image without <img> element only has the page title.
+    assert_eq!(saved_images[1].metadata.alt, None);
+    assert_eq!(
+        saved_images[1].metadata.page_title.as_deref(),
+        Some("Gallery")
+    );
+
+    let manifest = std::fs::read_to_string(output.join("manifest.json")).unwrap();
+    let manifest: serde_json::Value = serde_json::from_str(&manifest).unwrap();
+    let image = manifest["images"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|image| image["url"] == server.url("/a.png"))
+        .unwrap();
+    assert_eq!(image["metadata"]["figcaption"], "Tom, after lunch");
+    assert!(image["metadata"].get("title").is_none());
+}